@@ -6,4 +6,5 @@ export RUSTFLAGS="-D warnings"
 
 cargo build --verbose &&
 cargo test --verbose &&
+cargo test --verbose --features serde,timing-stats &&
 cargo doc
@@ -5,7 +5,10 @@ use core::{
     fmt, ops,
 };
 
-use embedded_hal::{digital::blocking::OutputPin, timer::nb::CountDown};
+use embedded_hal::{
+    delay::blocking::DelayUs, digital::blocking::OutputPin,
+    timer::nb::CountDown,
+};
 use embedded_hal_stable::{
     digital::v2::OutputPin as StableOutputPin,
     timer::CountDown as StableCountDown,
@@ -66,7 +69,7 @@ where
     fn wait(&mut self) -> nb::Result<(), Self::Error> {
         match self.0.wait() {
             Ok(()) => Ok(()),
-            Err(nb::Error::WouldBlock) => return Err(nb::Error::WouldBlock),
+            Err(nb::Error::WouldBlock) => Err(nb::Error::WouldBlock),
             Err(nb::Error::Other(_)) => {
                 unreachable!("Caught error from infallible method")
             }
@@ -74,6 +77,64 @@ where
     }
 }
 
+/// Wrapper around a blocking delay
+///
+/// Provides an implementation of [`embedded_hal::timer::nb::CountDown`] for
+/// all types that implement [`DelayUs`], for platforms that don't provide a
+/// timer. [`CountDown::start`] only records the duration, and
+/// [`CountDown::wait`] blocks for all of it, then returns `Ok`.
+///
+/// This means no future or motion ever returns `WouldBlock` while waiting, and
+/// polling it blocks for as long as the delay takes. There's no way to do
+/// other work in the meantime, or to set up an interrupt. Durations are
+/// counted in whole microseconds, and shorter remainders are dropped, so make
+/// sure the driver's timing requirements are multiples of a microsecond (or
+/// check them with [`Stepper::from_driver_checked`]).
+///
+/// [`Stepper::from_driver_checked`]: crate::Stepper::from_driver_checked
+pub struct DelayTimer<T> {
+    delay: T,
+    us: Option<u32>,
+}
+
+impl<T> DelayTimer<T> {
+    /// Create a new instance of `DelayTimer`
+    pub fn new(delay: T) -> Self {
+        Self { delay, us: None }
+    }
+
+    /// Release the wrapped delay
+    pub fn release(self) -> T {
+        self.delay
+    }
+}
+
+impl<T> CountDown for DelayTimer<T>
+where
+    T: DelayUs,
+{
+    type Error = T::Error;
+
+    type Time = Ticks<u32, 1_000_000>;
+
+    fn start<Ticks>(&mut self, ticks: Ticks) -> Result<(), Self::Error>
+    where
+        Ticks: Into<Self::Time>,
+    {
+        self.us = Some(ticks.into().0);
+        Ok(())
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        // If the timer hasn't been started, there's nothing to wait for.
+        if let Some(us) = self.us.take() {
+            self.delay.delay_us(us)?;
+        }
+
+        Ok(())
+    }
+}
+
 /// Timer ticks for a timer with frequency `FREQ`
 ///
 /// Provides conversions from various duration types from `embedded-time` into
@@ -83,6 +144,7 @@ where
 /// valid for the timer.
 ///
 /// `FREQ` is defined in Hz.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Ticks<T, const FREQ: u32>(pub T);
 
 macro_rules! impl_conversions {
@@ -116,6 +178,17 @@ impl_conversions!(
     Hours,
 );
 
+impl<T, const FREQ: u32> ops::Add for Ticks<T, FREQ>
+where
+    T: TimeInt + ops::Add,
+{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        Ticks(self.0 + other.0)
+    }
+}
+
 impl<T, const FREQ: u32> ops::Sub for Ticks<T, FREQ>
 where
     T: TimeInt + ops::Sub,
@@ -126,3 +199,47 @@ where
         Ticks(self.0 - other.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::convert::Infallible;
+
+    use embedded_hal::delay::blocking::DelayUs;
+
+    use crate::{
+        test_util::{Log, MockDriver},
+        Direction, Stepper,
+    };
+
+    use super::DelayTimer;
+
+    /// A delay that records the requested durations
+    #[derive(Default)]
+    struct MockDelay(Vec<u32>);
+
+    impl DelayUs for MockDelay {
+        type Error = Infallible;
+
+        fn delay_us(&mut self, us: u32) -> Result<(), Self::Error> {
+            self.0.push(us);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn delay_timer_should_block_for_started_duration() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(MockDriver::new(&log));
+        let mut timer = DelayTimer::new(MockDelay::default());
+
+        stepper
+            .set_direction(Direction::Forward, &mut timer)
+            .wait()
+            .unwrap();
+        stepper.step(&mut timer).wait().unwrap();
+
+        // The DIR setup time is 1 µs, the STEP pulse 2 µs.
+        assert_eq!(timer.release().0, [1, 2]);
+    }
+}
@@ -0,0 +1,235 @@
+//! Adapter for drivers behind inverting outputs
+//!
+//! Some boards connect the driver's inputs through inverting level shifters or
+//! open-collector (sinking) outputs, which means every level the
+//! microcontroller outputs arrives at the driver inverted. [`ActiveLow`] wraps
+//! a driver and inverts both its STEP pulses and its DIR signal, so the levels
+//! are correct once they have passed through the inverting stage.
+//!
+//! This is different from a driver with an active-low STEP input (see
+//! [`Step::PULSE_POLARITY`]), which only affects STEP. Both can be combined:
+//! A driver with an active-low STEP input behind an inverting buffer gets
+//! pulses that go from low to high and back.
+//!
+//! # Example
+//!
+//! ``` rust
+//! # fn main() -> Result<(), stepper::SignalError<
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! # >> {
+//! use stepper::{
+//!     drivers::{active_low::ActiveLow, generic::GenericDriver},
+//!     Direction, Stepper,
+//! };
+//!
+//! # struct Pin;
+//! # impl stepper::embedded_hal::digital::blocking::OutputPin for Pin {
+//! #     type Error = core::convert::Infallible;
+//! #     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+//! #     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+//! # }
+//! #
+//! # struct Timer;
+//! # impl stepper::embedded_hal::timer::nb::CountDown for Timer {
+//! #     type Error = core::convert::Infallible;
+//! #     type Time = Ticks;
+//! #     fn start<T>(&mut self, count: T) -> Result<(), Self::Error>
+//! #         where T: Into<Self::Time>
+//! #     {
+//! #         Ok(())
+//! #     }
+//! #     fn wait(&mut self) -> nb::Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! #
+//! # pub struct Ticks;
+//! # impl From<stepper::embedded_time::duration::Nanoseconds> for Ticks {
+//! #     fn from(_: stepper::embedded_time::duration::Nanoseconds) -> Self {
+//! #         Self
+//! #     }
+//! # }
+//! #
+//! // The STEP and DIR pins of an A4988 driver, which are connected through an
+//! // inverting buffer, and a timer.
+//! let driver = GenericDriver::<_, _, 1000, 200>::new(Pin, Pin);
+//! let mut timer = Timer;
+//!
+//! let mut stepper = Stepper::from_driver(ActiveLow::new(driver));
+//!
+//! // Sets DIR low, then pulses STEP from high to low and back.
+//! stepper
+//!     .set_direction(Direction::Forward, &mut timer)
+//!     .wait()?;
+//! stepper.step(&mut timer).wait()?;
+//! #
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Step::PULSE_POLARITY`]: crate::traits::Step::PULSE_POLARITY
+
+use embedded_hal::digital::PinState;
+use embedded_time::duration::Nanoseconds;
+
+use crate::traits::{Capabilities, SetDirection, Step};
+
+/// Inverts the STEP and DIR signals of a driver
+///
+/// Implements [`Step`] and [`SetDirection`], if the wrapped driver does, and
+/// inverts [`Step::pulse_polarity`] and [`SetDirection::dir_polarity`]. All
+/// timing requirements are those of the wrapped driver.
+///
+/// See the [module documentation](self) for an example.
+///
+/// [`Step`]: crate::traits::Step
+/// [`Step::pulse_polarity`]: crate::traits::Step::pulse_polarity
+/// [`SetDirection`]: crate::traits::SetDirection
+/// [`SetDirection::dir_polarity`]: crate::traits::SetDirection::dir_polarity
+pub struct ActiveLow<Driver> {
+    driver: Driver,
+}
+
+impl<Driver> ActiveLow<Driver> {
+    /// Create a new instance of `ActiveLow`
+    pub fn new(driver: Driver) -> Self {
+        Self { driver }
+    }
+
+    /// Access the wrapped driver
+    pub fn driver(&self) -> &Driver {
+        &self.driver
+    }
+
+    /// Mutably access the wrapped driver
+    pub fn driver_mut(&mut self) -> &mut Driver {
+        &mut self.driver
+    }
+
+    /// Release the wrapped driver
+    pub fn release(self) -> Driver {
+        self.driver
+    }
+}
+
+// Only STEP and DIR are available through this adapter.
+impl<Driver> Capabilities for ActiveLow<Driver> {}
+
+impl<Driver> Step for ActiveLow<Driver>
+where
+    Driver: Step,
+{
+    const PULSE_LENGTH: Nanoseconds = Driver::PULSE_LENGTH;
+    const PULSE_POLARITY: PinState = match Driver::PULSE_POLARITY {
+        PinState::High => PinState::Low,
+        PinState::Low => PinState::High,
+    };
+    const LOW_TIME: Nanoseconds = Driver::LOW_TIME;
+
+    type Step = Driver::Step;
+    type Error = Driver::Error;
+
+    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+        self.driver.step()
+    }
+
+    fn pulse_polarity(&self) -> PinState {
+        !self.driver.pulse_polarity()
+    }
+
+    fn pulse_length(&self) -> Nanoseconds {
+        self.driver.pulse_length()
+    }
+
+    fn low_time(&self) -> Nanoseconds {
+        self.driver.low_time()
+    }
+}
+
+impl<Driver> SetDirection for ActiveLow<Driver>
+where
+    Driver: SetDirection,
+{
+    const SETUP_TIME: Nanoseconds = Driver::SETUP_TIME;
+
+    type Dir = Driver::Dir;
+    type Error = Driver::Error;
+
+    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+        self.driver.dir()
+    }
+
+    fn setup_time(&self) -> Nanoseconds {
+        self.driver.setup_time()
+    }
+
+    fn dir_polarity(&self) -> PinState {
+        !self.driver.dir_polarity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::digital::PinState::{High, Low};
+    use ramp_maker::Flat;
+
+    use crate::{
+        test_util::{Event, Log, MockDelayToTicks, MockDriver, MockTimer},
+        Direction, Stepper,
+    };
+
+    use super::ActiveLow;
+
+    #[test]
+    fn active_low_should_invert_step_and_dir_together() {
+        let log = Log::new();
+        let mut stepper =
+            Stepper::from_driver(ActiveLow::new(MockDriver::new(&log)));
+        let mut timer = MockTimer::new(&log);
+
+        for direction in
+            IntoIterator::into_iter([Direction::Forward, Direction::Backward])
+        {
+            stepper.set_direction(direction, &mut timer).wait().unwrap();
+            stepper.step(&mut timer).wait().unwrap();
+        }
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("dir", Low),
+                Event::TimerStart(1),
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                Event::Pin("step", High),
+                Event::Pin("dir", High),
+                Event::TimerStart(1),
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                Event::Pin("step", High),
+            ]
+        );
+
+        // Motion control inverts them too, including when aborting.
+        log.clear();
+        let mut stepper = stepper.enable_motion_control((
+            MockTimer::new(&log),
+            Flat::new(),
+            MockDelayToTicks,
+        ));
+        stepper.move_to_position(0.01, 1).wait().unwrap();
+        stepper.abort().unwrap();
+
+        let events = log.events();
+        assert_eq!(events.first(), Some(&Event::Pin("dir", Low)));
+        assert_eq!(log.count(Event::Pin("step", Low)), 1);
+        assert_eq!(events.last(), Some(&Event::Pin("step", High)));
+    }
+}
@@ -15,7 +15,8 @@ use embedded_hal::digital::blocking::OutputPin;
 use embedded_time::duration::Nanoseconds;
 
 use crate::traits::{
-    EnableDirectionControl, EnableStepControl, SetDirection, Step as StepTrait,
+    Capabilities, EnableDirectionControl, EnableStepControl, SetDirection,
+    Step as StepTrait,
 };
 
 /// The DQ542MA driver API
@@ -40,6 +41,14 @@ impl DQ542MA<(), (), ()> {
     }
 }
 
+impl Default for DQ542MA<(), (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Enable, Step, Dir> Capabilities for DQ542MA<Enable, Step, Dir> {}
+
 impl<Step, Dir, OutputPinError> EnableDirectionControl<Dir>
     for DQ542MA<(), Step, ()>
 where
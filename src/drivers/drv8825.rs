@@ -15,10 +15,12 @@ use embedded_hal::digital::{blocking::OutputPin, PinState};
 use embedded_time::duration::Nanoseconds;
 
 use crate::{
+    drivers::mode_pins::{ModePinError, ModePinTable},
     step_mode::StepMode32,
     traits::{
-        EnableDirectionControl, EnableStepControl, EnableStepModeControl,
-        SetDirection, SetStepMode, Step as StepTrait,
+        Capabilities, EnableDirectionControl, EnableDriverControl,
+        EnableStepControl, EnableStepModeControl, SetDirection, SetEnable,
+        SetStepMode, Step as StepTrait,
     },
 };
 
@@ -38,6 +40,8 @@ pub struct DRV8825<Enable, Fault, Sleep, Reset, Mode0, Mode1, Mode2, Step, Dir>
     mode2: Mode2,
     step: Step,
     dir: Dir,
+    enabled: Option<bool>,
+    step_mode: Option<StepMode32>,
 }
 
 impl DRV8825<(), (), (), (), (), (), (), (), ()> {
@@ -53,13 +57,87 @@ impl DRV8825<(), (), (), (), (), (), (), (), ()> {
             mode2: (),
             step: (),
             dir: (),
+            enabled: None,
+            step_mode: None,
         }
     }
 }
 
-impl<Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError>
+impl Default for DRV8825<(), (), (), (), (), (), (), (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Enable, Fault, Sleep, Reset, Mode0, Mode1, Mode2, Step, Dir> Capabilities
+    for DRV8825<Enable, Fault, Sleep, Reset, Mode0, Mode1, Mode2, Step, Dir>
+{
+    const SUPPORTS_STEP_MODE_CONTROL: bool = true;
+    const SUPPORTS_DRIVER_CONTROL: bool = true;
+}
+
+impl<Enable, Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError>
+    EnableDriverControl<Enable>
+    for DRV8825<(), (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>
+where
+    Enable: OutputPin<Error = OutputPinError>,
+{
+    type WithDriverControl =
+        DRV8825<Enable, (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>;
+
+    fn enable_driver_control(self, enable: Enable) -> Self::WithDriverControl {
+        DRV8825 {
+            enable,
+            fault: self.fault,
+            sleep: self.sleep,
+            reset: self.reset,
+            mode0: self.mode0,
+            mode1: self.mode1,
+            mode2: self.mode2,
+            step: self.step,
+            dir: self.dir,
+            enabled: self.enabled,
+            step_mode: self.step_mode,
+        }
+    }
+}
+
+impl<Enable, Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError> SetEnable
+    for DRV8825<Enable, (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>
+where
+    Enable: OutputPin<Error = OutputPinError>,
+{
+    // The datasheet doesn't specify a timing requirement for nENBL. Use the
+    // same time that applies to the other control inputs, to be safe.
+    // 7.6 Timing Requirements (page 7)
+    // https://www.ti.com/lit/ds/symlink/drv8825.pdf
+    const HOLD_TIME: Nanoseconds = Nanoseconds(650);
+
+    type Error = OutputPinError;
+
+    fn enable(&mut self) -> Result<(), Self::Error> {
+        // nENBL is active-low.
+        self.enable.set_low()?;
+        self.enabled = Some(true);
+        Ok(())
+    }
+
+    fn disable(&mut self) -> Result<(), Self::Error> {
+        self.enable.set_high()?;
+        self.enabled = Some(false);
+        Ok(())
+    }
+
+    // The nENBL pin can't be read back, so this reflects the last successful
+    // call to `enable` or `disable`.
+    fn is_enabled(&self) -> Option<bool> {
+        self.enabled
+    }
+}
+
+impl<Enable, Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError>
     EnableStepModeControl<(Reset, Mode0, Mode1, Mode2)>
-    for DRV8825<(), (), (), (), (), (), (), Step, Dir>
+    for DRV8825<Enable, (), (), (), (), (), (), Step, Dir>
 where
     Reset: OutputPin<Error = OutputPinError>,
     Mode0: OutputPin<Error = OutputPinError>,
@@ -67,7 +145,7 @@ where
     Mode2: OutputPin<Error = OutputPinError>,
 {
     type WithStepModeControl =
-        DRV8825<(), (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>;
+        DRV8825<Enable, (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>;
 
     fn enable_step_mode_control(
         self,
@@ -83,12 +161,32 @@ where
             mode2,
             step: self.step,
             dir: self.dir,
+            enabled: self.enabled,
+            step_mode: self.step_mode,
         }
     }
 }
 
-impl<Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError> SetStepMode
-    for DRV8825<(), (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>
+/// The states of the MODE0, MODE1, and MODE2 pins for each step mode
+///
+/// See the
+/// [DRV8825 datasheet](https://www.ti.com/lit/ds/symlink/drv8825.pdf).
+const MODE_PINS: ModePinTable<StepMode32, 3> = {
+    use PinState::*;
+    use StepMode32::*;
+
+    ModePinTable::new(&[
+        (Full, [Low, Low, Low]),
+        (M2, [High, Low, Low]),
+        (M4, [Low, High, Low]),
+        (M8, [High, High, Low]),
+        (M16, [Low, Low, High]),
+        (M32, [High, High, High]),
+    ])
+};
+
+impl<Enable, Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError> SetStepMode
+    for DRV8825<Enable, (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>
 where
     Reset: OutputPin<Error = OutputPinError>,
     Mode0: OutputPin<Error = OutputPinError>,
@@ -100,48 +198,46 @@ where
     const SETUP_TIME: Nanoseconds = Nanoseconds(650);
     const HOLD_TIME: Nanoseconds = Nanoseconds(650);
 
-    type Error = OutputPinError;
+    // `MODE_PINS` covers every step mode, so this never is
+    // `ModePinError::UnsupportedStepMode`.
+    type Error = ModePinError<OutputPinError>;
     type StepMode = StepMode32;
 
     fn apply_mode_config(
         &mut self,
         step_mode: Self::StepMode,
     ) -> Result<(), Self::Error> {
-        // Reset the device's internal logic and disable the h-bridge drivers.
-        self.reset.set_low()?;
-
-        use PinState::*;
-        use StepMode32::*;
-        let (mode0, mode1, mode2) = match step_mode {
-            Full => (Low, Low, Low),
-            M2 => (High, Low, Low),
-            M4 => (Low, High, Low),
-            M8 => (High, High, Low),
-            M16 => (Low, Low, High),
-            M32 => (High, High, High),
-        };
-
-        // Set mode signals.
-        self.mode0.set_state(mode0)?;
-        self.mode1.set_state(mode1)?;
-        self.mode2.set_state(mode2)?;
+        // Resets the device's internal logic and disables the h-bridge
+        // drivers, then sets the mode signals.
+        MODE_PINS.apply_mode_config(
+            step_mode,
+            &mut self.reset,
+            (&mut self.mode0, &mut self.mode1, &mut self.mode2),
+        )?;
 
+        self.step_mode = Some(step_mode);
         Ok(())
     }
 
     fn enable_driver(&mut self) -> Result<(), Self::Error> {
-        self.reset.set_high()
+        MODE_PINS
+            .enable_driver(&mut self.reset)
+            .map_err(|err| ModePinError::Pin(err))
+    }
+
+    fn step_mode(&self) -> Option<Self::StepMode> {
+        self.step_mode
     }
 }
 
-impl<Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError>
+impl<Enable, Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError>
     EnableDirectionControl<Dir>
-    for DRV8825<(), (), (), Reset, Mode0, Mode1, Mode2, Step, ()>
+    for DRV8825<Enable, (), (), Reset, Mode0, Mode1, Mode2, Step, ()>
 where
     Dir: OutputPin<Error = OutputPinError>,
 {
     type WithDirectionControl =
-        DRV8825<(), (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>;
+        DRV8825<Enable, (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>;
 
     fn enable_direction_control(self, dir: Dir) -> Self::WithDirectionControl {
         DRV8825 {
@@ -154,12 +250,14 @@ where
             mode2: self.mode2,
             step: self.step,
             dir,
+            enabled: self.enabled,
+            step_mode: self.step_mode,
         }
     }
 }
 
-impl<Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError> SetDirection
-    for DRV8825<(), (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>
+impl<Enable, Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError> SetDirection
+    for DRV8825<Enable, (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>
 where
     Dir: OutputPin<Error = OutputPinError>,
 {
@@ -175,14 +273,14 @@ where
     }
 }
 
-impl<Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError>
+impl<Enable, Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError>
     EnableStepControl<Step>
-    for DRV8825<(), (), (), Reset, Mode0, Mode1, Mode2, (), Dir>
+    for DRV8825<Enable, (), (), Reset, Mode0, Mode1, Mode2, (), Dir>
 where
     Step: OutputPin<Error = OutputPinError>,
 {
     type WithStepControl =
-        DRV8825<(), (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>;
+        DRV8825<Enable, (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>;
 
     fn enable_step_control(self, step: Step) -> Self::WithStepControl {
         DRV8825 {
@@ -195,12 +293,14 @@ where
             mode2: self.mode2,
             step,
             dir: self.dir,
+            enabled: self.enabled,
+            step_mode: self.step_mode,
         }
     }
 }
 
-impl<Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError> StepTrait
-    for DRV8825<(), (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>
+impl<Enable, Reset, Mode0, Mode1, Mode2, Step, Dir, OutputPinError> StepTrait
+    for DRV8825<Enable, (), (), Reset, Mode0, Mode1, Mode2, Step, Dir>
 where
     Step: OutputPin<Error = OutputPinError>,
 {
@@ -215,3 +315,109 @@ where
         Ok(&mut self.step)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use embedded_hal::digital::PinState::{High, Low};
+
+    use crate::{
+        step_mode::{StepMode as _, StepMode32},
+        test_util::{Event, Log, MockPin, MockTimer},
+        Stepper,
+    };
+
+    use super::{DRV8825, MODE_PINS};
+
+    #[test]
+    fn is_enabled_should_reflect_last_enable_or_disable() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(DRV8825::new())
+            .enable_driver_control(MockPin::new("enable", &log));
+        let mut timer = MockTimer::new(&log);
+
+        assert_eq!(stepper.is_enabled(), None);
+
+        stepper.enable(&mut timer).wait().unwrap();
+        assert_eq!(stepper.is_enabled(), Some(true));
+        assert_eq!(log.events()[0], Event::Pin("enable", Low));
+
+        log.clear();
+        stepper.disable().unwrap();
+        assert_eq!(stepper.is_enabled(), Some(false));
+        assert_eq!(log.events(), [Event::Pin("enable", High)]);
+    }
+
+    #[test]
+    fn step_mode_should_reflect_last_applied_step_mode() {
+        let log = Log::new();
+        let mut timer = MockTimer::new(&log);
+        let mut stepper = Stepper::from_driver(DRV8825::new())
+            .enable_step_mode_control(
+                (
+                    MockPin::new("reset", &log),
+                    MockPin::new("mode0", &log),
+                    MockPin::new("mode1", &log),
+                    MockPin::new("mode2", &log),
+                ),
+                StepMode32::M8,
+                &mut timer,
+            )
+            .unwrap();
+        assert_eq!(stepper.step_mode(), Some(StepMode32::M8));
+
+        stepper
+            .set_step_mode(StepMode32::Full, &mut timer)
+            .wait()
+            .unwrap();
+        assert_eq!(stepper.step_mode(), Some(StepMode32::Full));
+    }
+
+    #[test]
+    fn set_step_mode_should_reset_and_set_mode_pins() {
+        let log = Log::new();
+        let mut timer = MockTimer::new(&log);
+        let mut stepper = Stepper::from_driver(DRV8825::new())
+            .enable_step_mode_control(
+                (
+                    MockPin::new("reset", &log),
+                    MockPin::new("mode0", &log),
+                    MockPin::new("mode1", &log),
+                    MockPin::new("mode2", &log),
+                ),
+                StepMode32::Full,
+                &mut timer,
+            )
+            .unwrap();
+        log.clear();
+
+        stepper
+            .set_step_mode(StepMode32::M16, &mut timer)
+            .wait()
+            .unwrap();
+
+        let pins: Vec<_> = log
+            .events()
+            .into_iter()
+            .filter(|event| matches!(event, Event::Pin(..)))
+            .collect();
+        assert_eq!(
+            pins,
+            [
+                Event::Pin("reset", Low),
+                Event::Pin("mode0", Low),
+                Event::Pin("mode1", Low),
+                Event::Pin("mode2", High),
+                Event::Pin("reset", High),
+            ]
+        );
+    }
+
+    #[test]
+    fn mode_pins_should_cover_all_step_modes() {
+        for step_mode in StepMode32::iter() {
+            assert!(MODE_PINS.pin_states(step_mode).is_some());
+        }
+    }
+}
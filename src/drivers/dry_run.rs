@@ -0,0 +1,131 @@
+//! Adapter for running motions without generating any signals
+//!
+//! [`DryRun`] wraps a driver, but doesn't use it. It implements [`Step`] and
+//! [`SetDirection`] with a virtual pin ([`NoPin`]) that ignores everything
+//! written to it, and with timing requirements of zero. Combined with
+//! [`SoftwareMotionControl`], this runs motions through the full state machine
+//! and counts steps as usual, without moving the motor. This is useful for
+//! validating a motion plan (for example, the coordinate math of a machine)
+//! on the device.
+//!
+//! Please note that the delays between steps, as defined by the motion
+//! profile, are still waited for. Use a fast timer to speed up the dry run.
+//!
+//! [`Step`]: crate::traits::Step
+//! [`SetDirection`]: crate::traits::SetDirection
+//! [`SoftwareMotionControl`]: crate::motion_control::SoftwareMotionControl
+
+use core::convert::Infallible;
+
+use embedded_hal::digital::blocking::OutputPin;
+use embedded_time::duration::Nanoseconds;
+
+use crate::traits::{Capabilities, SetDirection, Step};
+
+/// Implements [`Step`] and [`SetDirection`], without generating any signals
+///
+/// See the [module documentation](self) for details.
+///
+/// [`Step`]: crate::traits::Step
+/// [`SetDirection`]: crate::traits::SetDirection
+pub struct DryRun<Driver> {
+    driver: Driver,
+    pin: NoPin,
+}
+
+impl<Driver> DryRun<Driver> {
+    /// Create a new instance of `DryRun`
+    ///
+    /// `driver` is not used, until it is returned by [`DryRun::release`].
+    pub fn new(driver: Driver) -> Self {
+        Self { driver, pin: NoPin }
+    }
+
+    /// Release the wrapped driver
+    pub fn release(self) -> Driver {
+        self.driver
+    }
+}
+
+// The wrapped driver isn't used, so none of its capabilities are available.
+impl<Driver> Capabilities for DryRun<Driver> {}
+
+impl<Driver> Step for DryRun<Driver> {
+    // There's no driver to wait for.
+    const PULSE_LENGTH: Nanoseconds = Nanoseconds(0);
+
+    type Step = NoPin;
+    type Error = Infallible;
+
+    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+        Ok(&mut self.pin)
+    }
+}
+
+impl<Driver> SetDirection for DryRun<Driver> {
+    // There's no driver to wait for.
+    const SETUP_TIME: Nanoseconds = Nanoseconds(0);
+
+    type Dir = NoPin;
+    type Error = Infallible;
+
+    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+        Ok(&mut self.pin)
+    }
+}
+
+/// A virtual pin that ignores everything written to it
+///
+/// Returned by the [`Step`] and [`SetDirection`] implementations of
+/// [`DryRun`].
+///
+/// [`Step`]: crate::traits::Step
+/// [`SetDirection`]: crate::traits::SetDirection
+pub struct NoPin;
+
+impl OutputPin for NoPin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_util::{
+            mock_motion_control_with, Event, Log, MockDriver, MockTimer,
+        },
+        Stepper,
+    };
+
+    use super::DryRun;
+
+    #[test]
+    fn dry_run_should_count_steps_without_signals() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control_with(
+            DryRun::new(MockDriver::new(&log)),
+            MockTimer::new(&log),
+        ));
+
+        stepper.move_to_position(0.01, 5).wait().unwrap();
+        stepper.move_to_position(0.01, -2).wait().unwrap();
+
+        assert_eq!(stepper.current_step().unwrap(), -2);
+        assert!(log
+            .events()
+            .iter()
+            .all(|event| !matches!(event, Event::Pin(..))));
+
+        // The timing requirements are zero, so the timer is only used for the
+        // delays between steps.
+        assert_eq!(log.count(Event::TimerStart(0)), 0);
+        assert_eq!(log.count(Event::TimerStart(100)), 12);
+    }
+}
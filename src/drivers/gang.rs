@@ -0,0 +1,407 @@
+//! Adapter for driving a group of motors with a single `Stepper`
+//!
+//! Some machines have several motors that are mechanically linked and always
+//! move together, like the motors on both sides of a gantry. [`GangDriver`]
+//! controls the STEP and DIR pins of all of those motors' drivers, so a single
+//! [`Stepper`] (and a single timer) can drive the whole group.
+//!
+//! All STEP pins are set one after the other, without any delay in between, so
+//! the pulses start and end at practically the same time. Each DIR pin can be
+//! inverted separately, for motors that are mounted the other way around.
+//!
+//! Like [`GenericDriver`], this requires all drivers to be simple STEP/DIR
+//! drivers with identical timing requirements, which are provided as const
+//! generic parameters.
+//!
+//! # Example
+//!
+//! ``` rust
+//! # fn main() -> Result<(), stepper::SignalError<
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! # >> {
+//! use stepper::{drivers::gang::GangDriver, Direction, Stepper};
+//!
+//! # struct Pin;
+//! # impl stepper::embedded_hal::digital::blocking::OutputPin for Pin {
+//! #     type Error = core::convert::Infallible;
+//! #     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+//! #     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+//! # }
+//! #
+//! # struct Timer;
+//! # impl stepper::embedded_hal::timer::nb::CountDown for Timer {
+//! #     type Error = core::convert::Infallible;
+//! #     type Time = Ticks;
+//! #     fn start<T>(&mut self, count: T) -> Result<(), Self::Error>
+//! #         where T: Into<Self::Time>
+//! #     {
+//! #         Ok(())
+//! #     }
+//! #     fn wait(&mut self) -> nb::Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! #
+//! # pub struct Ticks;
+//! # impl From<stepper::embedded_time::duration::Nanoseconds> for Ticks {
+//! #     fn from(_: stepper::embedded_time::duration::Nanoseconds) -> Self {
+//! #         Self
+//! #     }
+//! # }
+//! #
+//! // The STEP and DIR pins of two A4988 drivers, and a timer. How you acquire
+//! // those depends on the platform you run on.
+//! let step = [Pin, Pin];
+//! let dir = [Pin, Pin];
+//! let mut timer = Timer;
+//!
+//! // The second motor is mounted mirrored, so it needs to turn the other way.
+//! let driver = GangDriver::<_, _, 2, 1000, 200>::new(step, dir)
+//!     .with_dir_inverted([false, true]);
+//! let mut stepper = Stepper::from_driver(driver);
+//!
+//! stepper
+//!     .set_direction(Direction::Forward, &mut timer)
+//!     .wait()?;
+//! stepper.step(&mut timer).wait()?;
+//! #
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Stepper`]: crate::Stepper
+//! [`GenericDriver`]: super::generic::GenericDriver
+
+use core::convert::Infallible;
+
+use embedded_hal::digital::{blocking::OutputPin, PinState};
+use embedded_time::duration::Nanoseconds;
+
+use crate::traits::{Capabilities, SetDirection, Step as StepTrait};
+
+/// A group of drivers that are controlled as one
+///
+/// `N` is the number of drivers. `PULSE_LENGTH_NS` and `SETUP_TIME_NS` work
+/// like they do for [`GenericDriver`]. Since all drivers are controlled at
+/// once, use the strictest requirements of any of them.
+///
+/// See the [module documentation](self) for an example.
+///
+/// [`GenericDriver`]: super::generic::GenericDriver
+pub struct GangDriver<
+    Step,
+    Dir,
+    const N: usize,
+    const PULSE_LENGTH_NS: u32,
+    const SETUP_TIME_NS: u32,
+> {
+    step: StepPins<Step, N>,
+    dir: DirPins<Dir, N>,
+}
+
+impl<
+        Step,
+        Dir,
+        const N: usize,
+        const PULSE_LENGTH_NS: u32,
+        const SETUP_TIME_NS: u32,
+    > GangDriver<Step, Dir, N, PULSE_LENGTH_NS, SETUP_TIME_NS>
+where
+    Step: OutputPin,
+    Dir: OutputPin,
+{
+    /// Create a new instance of `GangDriver`
+    ///
+    /// `step[i]` and `dir[i]` are the STEP and DIR pins of the same driver. No
+    /// DIR pin is inverted.
+    pub fn new(step: [Step; N], dir: [Dir; N]) -> Self {
+        Self {
+            step: StepPins { pins: step },
+            dir: DirPins {
+                pins: dir,
+                inverted: [false; N],
+            },
+        }
+    }
+
+    /// Invert the DIR pins of some drivers
+    ///
+    /// If `inverted[i]` is `true`, the DIR pin of the driver at index `i` is
+    /// set to the opposite of the requested level, making that motor turn the
+    /// other way.
+    pub fn with_dir_inverted(mut self, inverted: [bool; N]) -> Self {
+        self.dir.inverted = inverted;
+        self
+    }
+
+    /// Release the STEP and DIR pins
+    pub fn release(self) -> ([Step; N], [Dir; N]) {
+        (self.step.pins, self.dir.pins)
+    }
+}
+
+impl<
+        Step,
+        Dir,
+        const N: usize,
+        const PULSE_LENGTH_NS: u32,
+        const SETUP_TIME_NS: u32,
+    > Capabilities
+    for GangDriver<Step, Dir, N, PULSE_LENGTH_NS, SETUP_TIME_NS>
+{
+}
+
+impl<
+        Step,
+        Dir,
+        const N: usize,
+        const PULSE_LENGTH_NS: u32,
+        const SETUP_TIME_NS: u32,
+    > StepTrait for GangDriver<Step, Dir, N, PULSE_LENGTH_NS, SETUP_TIME_NS>
+where
+    Step: OutputPin,
+{
+    const PULSE_LENGTH: Nanoseconds = Nanoseconds(PULSE_LENGTH_NS);
+
+    type Step = StepPins<Step, N>;
+    type Error = Infallible;
+
+    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+        Ok(&mut self.step)
+    }
+}
+
+impl<
+        Step,
+        Dir,
+        const N: usize,
+        const PULSE_LENGTH_NS: u32,
+        const SETUP_TIME_NS: u32,
+    > SetDirection for GangDriver<Step, Dir, N, PULSE_LENGTH_NS, SETUP_TIME_NS>
+where
+    Dir: OutputPin,
+{
+    const SETUP_TIME: Nanoseconds = Nanoseconds(SETUP_TIME_NS);
+
+    type Dir = DirPins<Dir, N>;
+    type Error = Infallible;
+
+    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+        Ok(&mut self.dir)
+    }
+}
+
+/// The STEP pins of a [`GangDriver`]
+///
+/// Sets all pins to the same level. If setting a pin fails, the others are
+/// still set, and the first error is returned.
+pub struct StepPins<Pin, const N: usize> {
+    pins: [Pin; N],
+}
+
+impl<Pin, const N: usize> OutputPin for StepPins<Pin, N>
+where
+    Pin: OutputPin,
+{
+    type Error = Pin::Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.set_state(PinState::Low)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set_state(PinState::High)
+    }
+
+    fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+        let mut result = Ok(());
+        for pin in &mut self.pins {
+            // Keep going, so one failing pin doesn't hold back the others.
+            result = result.and(pin.set_state(state));
+        }
+
+        result
+    }
+}
+
+/// The DIR pins of a [`GangDriver`]
+///
+/// Sets all pins to the same level, except for inverted ones (see
+/// [`GangDriver::with_dir_inverted`]), which are set to the opposite level.
+/// Like [`StepPins`], this sets every pin and returns the first error.
+pub struct DirPins<Pin, const N: usize> {
+    pins: [Pin; N],
+    inverted: [bool; N],
+}
+
+impl<Pin, const N: usize> OutputPin for DirPins<Pin, N>
+where
+    Pin: OutputPin,
+{
+    type Error = Pin::Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.set_state(PinState::Low)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.set_state(PinState::High)
+    }
+
+    fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+        let mut result = Ok(());
+        for (pin, &inverted) in self.pins.iter_mut().zip(&self.inverted) {
+            let state = if inverted { !state } else { state };
+            result = result.and(pin.set_state(state));
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::digital::{
+        blocking::OutputPin,
+        PinState::{self, High, Low},
+    };
+
+    use crate::{
+        test_util::{Event, Log, MockPin, MockTimer},
+        traits::{SetDirection as _, Step as _},
+        Direction, Stepper,
+    };
+
+    use super::GangDriver;
+
+    /// Fails to be set, if `error` is set, without recording anything
+    struct FailingPin {
+        pin: MockPin,
+        error: Option<&'static str>,
+    }
+
+    impl FailingPin {
+        fn new(
+            name: &'static str,
+            log: &Log,
+            error: Option<&'static str>,
+        ) -> Self {
+            Self {
+                pin: MockPin::new(name, log),
+                error,
+            }
+        }
+    }
+
+    impl OutputPin for FailingPin {
+        type Error = &'static str;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.set_state(Low)
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.set_state(High)
+        }
+
+        fn set_state(&mut self, state: PinState) -> Result<(), Self::Error> {
+            if let Some(error) = self.error {
+                return Err(error);
+            }
+            self.pin.set_state(state).map_err(|err| match err {})
+        }
+    }
+
+    #[test]
+    fn gang_driver_should_pulse_all_step_pins() {
+        let log = Log::new();
+        let driver: GangDriver<_, _, 3, 3000, 4000> = GangDriver::new(
+            [
+                MockPin::new("step0", &log),
+                MockPin::new("step1", &log),
+                MockPin::new("step2", &log),
+            ],
+            [
+                MockPin::new("dir0", &log),
+                MockPin::new("dir1", &log),
+                MockPin::new("dir2", &log),
+            ],
+        );
+        let mut stepper = Stepper::from_driver(driver);
+        let mut timer = MockTimer::new(&log);
+
+        stepper.step(&mut timer).wait().unwrap();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("step0", High),
+                Event::Pin("step1", High),
+                Event::Pin("step2", High),
+                Event::TimerStart(3),
+                Event::TimerFinished,
+                Event::Pin("step0", Low),
+                Event::Pin("step1", Low),
+                Event::Pin("step2", Low),
+            ]
+        );
+    }
+
+    #[test]
+    fn gang_driver_should_invert_dir_per_motor() {
+        let log = Log::new();
+        let driver: GangDriver<_, _, 2, 3000, 4000> = GangDriver::new(
+            [MockPin::new("step0", &log), MockPin::new("step1", &log)],
+            [MockPin::new("dir0", &log), MockPin::new("dir1", &log)],
+        )
+        .with_dir_inverted([false, true]);
+        let mut stepper = Stepper::from_driver(driver);
+        let mut timer = MockTimer::new(&log);
+
+        for direction in
+            IntoIterator::into_iter([Direction::Forward, Direction::Backward])
+        {
+            stepper.set_direction(direction, &mut timer).wait().unwrap();
+        }
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("dir0", High),
+                Event::Pin("dir1", Low),
+                Event::TimerStart(4),
+                Event::TimerFinished,
+                Event::Pin("dir0", Low),
+                Event::Pin("dir1", High),
+                Event::TimerStart(4),
+                Event::TimerFinished,
+            ]
+        );
+    }
+
+    #[test]
+    fn gang_driver_should_set_all_pins_and_return_first_error() {
+        let log = Log::new();
+        let pins = |name: [&'static str; 3]| {
+            [
+                FailingPin::new(name[0], &log, Some("first")),
+                FailingPin::new(name[1], &log, None),
+                FailingPin::new(name[2], &log, Some("second")),
+            ]
+        };
+        let mut driver: GangDriver<_, _, 3, 3000, 4000> = GangDriver::new(
+            pins(["step0", "step1", "step2"]),
+            pins(["dir0", "dir1", "dir2"]),
+        );
+
+        assert_eq!(driver.step().unwrap().set_high(), Err("first"));
+        assert_eq!(driver.dir().unwrap().set_high(), Err("first"));
+        assert_eq!(
+            log.events(),
+            [Event::Pin("step1", High), Event::Pin("dir1", High)]
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Generic driver for STEP/DIR pins
+//!
+//! Many simple drivers, like the A4988, don't need anything but a STEP and a
+//! DIR pin, and only differ in their timing requirements. [`GenericDriver`]
+//! supports all of those, without requiring a dedicated driver implementation.
+//! The timing requirements are provided as const generic parameters.
+//!
+//! If the timing requirements are only known at runtime, or const generic
+//! parameters are inconvenient, use [`RuntimeDriverBuilder`] instead. It builds
+//! a [`RuntimeDriver`], which stores its timing requirements as regular values.
+//!
+//! Since both drivers implement [`Step`] and [`SetDirection`], they can be
+//! used with the software motion control fallback, via
+//! [`Stepper::enable_motion_control`].
+//!
+//! # Example
+//!
+//! ``` rust
+//! # fn main() -> Result<(), stepper::SignalError<
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! # >> {
+//! use stepper::{drivers::generic::GenericDriver, Direction, Stepper};
+//!
+//! # struct Pin;
+//! # impl stepper::embedded_hal::digital::blocking::OutputPin for Pin {
+//! #     type Error = core::convert::Infallible;
+//! #     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+//! #     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+//! # }
+//! #
+//! # struct Timer;
+//! # impl stepper::embedded_hal::timer::nb::CountDown for Timer {
+//! #     type Error = core::convert::Infallible;
+//! #     type Time = Ticks;
+//! #     fn start<T>(&mut self, count: T) -> Result<(), Self::Error>
+//! #         where T: Into<Self::Time>
+//! #     {
+//! #         Ok(())
+//! #     }
+//! #     fn wait(&mut self) -> nb::Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! #
+//! # pub struct Ticks;
+//! # impl From<stepper::embedded_time::duration::Nanoseconds> for Ticks {
+//! #     fn from(_: stepper::embedded_time::duration::Nanoseconds) -> Self {
+//! #         Self
+//! #     }
+//! # }
+//! #
+//! // The STEP and DIR pins, and a timer. How you acquire those depends on the
+//! // platform you run on.
+//! let step = Pin;
+//! let dir = Pin;
+//! let mut timer = Timer;
+//!
+//! // The A4988 requires STEP pulses of at least 1 µs, and DIR to be set 200 ns
+//! // before the next STEP pulse.
+//! type A4988<Step, Dir> = GenericDriver<Step, Dir, 1000, 200>;
+//!
+//! let mut stepper = Stepper::from_driver(A4988::new(step, dir));
+//!
+//! stepper
+//!     .set_direction(Direction::Forward, &mut timer)
+//!     .wait()?;
+//! stepper.step(&mut timer).wait()?;
+//! #
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Step`]: crate::traits::Step
+//! [`SetDirection`]: crate::traits::SetDirection
+//! [`Stepper::enable_motion_control`]: crate::Stepper::enable_motion_control
+
+use core::convert::Infallible;
+
+use embedded_hal::digital::blocking::OutputPin;
+use embedded_time::duration::Nanoseconds;
+
+use crate::traits::{Capabilities, SetDirection, Step as StepTrait};
+
+/// A driver that is controlled by a STEP and a DIR pin
+///
+/// `PULSE_LENGTH_NS` is the minimum length of a STEP pulse, and
+/// `SETUP_TIME_NS` the minimum time between setting DIR and the next STEP
+/// pulse, both in nanoseconds. Look those up in the datasheet of your driver.
+///
+/// See the [module documentation](self) for an example.
+pub struct GenericDriver<
+    Step,
+    Dir,
+    const PULSE_LENGTH_NS: u32,
+    const SETUP_TIME_NS: u32,
+> {
+    step: Step,
+    dir: Dir,
+}
+
+impl<Step, Dir, const PULSE_LENGTH_NS: u32, const SETUP_TIME_NS: u32>
+    GenericDriver<Step, Dir, PULSE_LENGTH_NS, SETUP_TIME_NS>
+where
+    Step: OutputPin,
+    Dir: OutputPin,
+{
+    /// Create a new instance of `GenericDriver`
+    pub fn new(step: Step, dir: Dir) -> Self {
+        Self { step, dir }
+    }
+
+    /// Release the STEP and DIR pins
+    pub fn release(self) -> (Step, Dir) {
+        (self.step, self.dir)
+    }
+}
+
+impl<Step, Dir, const PULSE_LENGTH_NS: u32, const SETUP_TIME_NS: u32>
+    Capabilities for GenericDriver<Step, Dir, PULSE_LENGTH_NS, SETUP_TIME_NS>
+{
+}
+
+impl<Step, Dir, const PULSE_LENGTH_NS: u32, const SETUP_TIME_NS: u32> StepTrait
+    for GenericDriver<Step, Dir, PULSE_LENGTH_NS, SETUP_TIME_NS>
+where
+    Step: OutputPin,
+{
+    const PULSE_LENGTH: Nanoseconds = Nanoseconds(PULSE_LENGTH_NS);
+
+    type Step = Step;
+    type Error = Infallible;
+
+    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+        Ok(&mut self.step)
+    }
+}
+
+impl<Step, Dir, const PULSE_LENGTH_NS: u32, const SETUP_TIME_NS: u32>
+    SetDirection for GenericDriver<Step, Dir, PULSE_LENGTH_NS, SETUP_TIME_NS>
+where
+    Dir: OutputPin,
+{
+    const SETUP_TIME: Nanoseconds = Nanoseconds(SETUP_TIME_NS);
+
+    type Dir = Dir;
+    type Error = Infallible;
+
+    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+        Ok(&mut self.dir)
+    }
+}
+
+/// Builds [`RuntimeDriver`]s with the given timing requirements
+///
+/// All timing requirements are zero, until they are set. The builder can be
+/// copied, to build any number of drivers with the same, or slightly
+/// different, timing requirements.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RuntimeDriverBuilder {
+    pulse_length: Nanoseconds,
+    low_time: Nanoseconds,
+    setup_time: Nanoseconds,
+}
+
+impl RuntimeDriverBuilder {
+    /// Create a new instance of `RuntimeDriverBuilder`
+    pub fn new() -> Self {
+        Self {
+            pulse_length: Nanoseconds(0),
+            low_time: Nanoseconds(0),
+            setup_time: Nanoseconds(0),
+        }
+    }
+
+    /// Set the minimum length of a STEP pulse
+    ///
+    /// See [`StepTrait::PULSE_LENGTH`].
+    pub fn with_pulse_length(mut self, pulse_length: Nanoseconds) -> Self {
+        self.pulse_length = pulse_length;
+        self
+    }
+
+    /// Set the minimum time between two STEP pulses
+    ///
+    /// See [`StepTrait::LOW_TIME`].
+    pub fn with_low_time(mut self, low_time: Nanoseconds) -> Self {
+        self.low_time = low_time;
+        self
+    }
+
+    /// Set the minimum time between setting DIR and the next STEP pulse
+    ///
+    /// See [`SetDirection::SETUP_TIME`].
+    pub fn with_setup_time(mut self, setup_time: Nanoseconds) -> Self {
+        self.setup_time = setup_time;
+        self
+    }
+
+    /// Build a driver that is controlled by the given STEP and DIR pins
+    pub fn build<Step, Dir>(
+        &self,
+        step: Step,
+        dir: Dir,
+    ) -> RuntimeDriver<Step, Dir>
+    where
+        Step: OutputPin,
+        Dir: OutputPin,
+    {
+        RuntimeDriver {
+            step,
+            dir,
+            timing: *self,
+        }
+    }
+}
+
+impl Default for RuntimeDriverBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A driver that is controlled by a STEP and a DIR pin, with runtime timing
+///
+/// Works like [`GenericDriver`], except that its timing requirements are
+/// stored in the driver. Use [`RuntimeDriverBuilder`] to create one.
+///
+/// The timing constants of [`StepTrait`] and [`SetDirection`] are zero for
+/// this driver, as they can't depend on runtime values. Instead, it returns
+/// its timing requirements from [`StepTrait::pulse_length`],
+/// [`StepTrait::low_time`], and [`SetDirection::setup_time`], which is what
+/// this crate uses.
+pub struct RuntimeDriver<Step, Dir> {
+    step: Step,
+    dir: Dir,
+    timing: RuntimeDriverBuilder,
+}
+
+impl<Step, Dir> RuntimeDriver<Step, Dir> {
+    /// Release the STEP and DIR pins
+    pub fn release(self) -> (Step, Dir) {
+        (self.step, self.dir)
+    }
+}
+
+impl<Step, Dir> Capabilities for RuntimeDriver<Step, Dir> {}
+
+impl<Step, Dir> StepTrait for RuntimeDriver<Step, Dir>
+where
+    Step: OutputPin,
+{
+    const PULSE_LENGTH: Nanoseconds = Nanoseconds(0);
+
+    type Step = Step;
+    type Error = Infallible;
+
+    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+        Ok(&mut self.step)
+    }
+
+    fn pulse_length(&self) -> Nanoseconds {
+        self.timing.pulse_length
+    }
+
+    fn low_time(&self) -> Nanoseconds {
+        self.timing.low_time
+    }
+}
+
+impl<Step, Dir> SetDirection for RuntimeDriver<Step, Dir>
+where
+    Dir: OutputPin,
+{
+    const SETUP_TIME: Nanoseconds = Nanoseconds(0);
+
+    type Dir = Dir;
+    type Error = Infallible;
+
+    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+        Ok(&mut self.dir)
+    }
+
+    fn setup_time(&self) -> Nanoseconds {
+        self.timing.setup_time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::digital::PinState::{High, Low};
+    use embedded_time::duration::Nanoseconds;
+
+    use crate::{
+        test_util::{Event, Log, MockPin, MockTimer},
+        Direction, Stepper,
+    };
+
+    use super::{GenericDriver, RuntimeDriverBuilder};
+
+    #[test]
+    fn generic_driver_should_use_configured_timing() {
+        let log = Log::new();
+        let driver: GenericDriver<_, _, 3000, 4000> = GenericDriver::new(
+            MockPin::new("step", &log),
+            MockPin::new("dir", &log),
+        );
+        let mut stepper = Stepper::from_driver(driver);
+        let mut timer = MockTimer::new(&log);
+
+        stepper
+            .set_direction(Direction::Backward, &mut timer)
+            .wait()
+            .unwrap();
+        stepper.step(&mut timer).wait().unwrap();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("dir", Low),
+                Event::TimerStart(4),
+                Event::TimerFinished,
+                Event::Pin("step", High),
+                Event::TimerStart(3),
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+            ]
+        );
+    }
+
+    #[test]
+    fn runtime_driver_builder_should_build_drivers_with_own_timing() {
+        let builder =
+            RuntimeDriverBuilder::new().with_setup_time(Nanoseconds(4000));
+
+        for &(name, pulse_length) in &[("a", 3000), ("b", 5000)] {
+            let log = Log::new();
+            let driver = builder
+                .with_pulse_length(Nanoseconds(pulse_length))
+                .build(MockPin::new("step", &log), MockPin::new("dir", &log));
+            let mut stepper = Stepper::from_driver(driver);
+            let mut timer = MockTimer::new(&log);
+
+            assert_eq!(stepper.pulse_length(), Nanoseconds(pulse_length));
+
+            stepper
+                .set_direction(Direction::Backward, &mut timer)
+                .wait()
+                .unwrap();
+            stepper.step(&mut timer).wait().unwrap();
+
+            assert_eq!(
+                log.events(),
+                [
+                    Event::Pin("dir", Low),
+                    Event::TimerStart(4),
+                    Event::TimerFinished,
+                    Event::Pin("step", High),
+                    Event::TimerStart(pulse_length / 1000),
+                    Event::TimerFinished,
+                    Event::Pin("step", Low),
+                ],
+                "driver {}",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! Adapter for drivers that take step commands over I2C
+//!
+//! Some drivers, like certain I2C stepper expanders, don't expose STEP and DIR
+//! inputs. Instead, they take step and direction commands over a bus. The rest
+//! of this crate is built around STEP and DIR pins though, including
+//! [`SoftwareMotionControl`].
+//!
+//! [`I2cStepDir`] bridges that gap: It implements [`Step`] and
+//! [`SetDirection`], but provides a virtual pin ([`BusPin`]) instead of a real
+//! one. That pin translates the STEP pulses and DIR changes it sees into bus
+//! transactions. Which bytes are written is defined by an implementation of
+//! [`Commands`], which needs to be provided for the specific hardware.
+//!
+//! [`SoftwareMotionControl`]: crate::motion_control::SoftwareMotionControl
+//! [`Step`]: crate::traits::Step
+//! [`SetDirection`]: crate::traits::SetDirection
+
+use core::convert::Infallible;
+
+use embedded_hal::{digital::blocking::OutputPin, i2c::blocking::Write};
+use embedded_time::duration::Nanoseconds;
+
+use crate::{
+    traits::{Capabilities, SetDirection, Step},
+    Direction,
+};
+
+/// Defines the commands that [`I2cStepDir`] writes to the bus
+///
+/// Implement this for the protocol of the specific hardware. See
+/// [`RegisterCommands`] for a simple implementation.
+pub trait Commands {
+    /// The bytes of a single command
+    type Command: AsRef<[u8]>;
+
+    /// The command that makes the driver step once
+    fn step(&self) -> Self::Command;
+
+    /// The command that sets the direction for subsequent steps
+    fn set_direction(&self, direction: Direction) -> Self::Command;
+}
+
+/// Commands for drivers that are controlled by writing to registers
+///
+/// Every command consists of a register address, followed by a value. A step
+/// is triggered by writing `1` to the step register. The direction is set by
+/// writing `1` (forward) or `0` (backward) to the direction register.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RegisterCommands {
+    /// The address of the register that triggers a step
+    pub step_register: u8,
+
+    /// The address of the register that sets the direction
+    pub direction_register: u8,
+}
+
+impl Commands for RegisterCommands {
+    type Command = [u8; 2];
+
+    fn step(&self) -> Self::Command {
+        [self.step_register, 1]
+    }
+
+    fn set_direction(&self, direction: Direction) -> Self::Command {
+        let value = match direction {
+            Direction::Forward => 1,
+            Direction::Backward => 0,
+        };
+
+        [self.direction_register, value]
+    }
+}
+
+/// Implements [`Step`] and [`SetDirection`] by writing commands over I2C
+///
+/// See the [module documentation](self) for details.
+///
+/// [`Step`]: crate::traits::Step
+/// [`SetDirection`]: crate::traits::SetDirection
+pub struct I2cStepDir<I2c, Cmds> {
+    pin: BusPin<I2c, Cmds>,
+}
+
+impl<I2c, Cmds> I2cStepDir<I2c, Cmds>
+where
+    I2c: Write,
+    Cmds: Commands,
+{
+    /// Create a new instance of `I2cStepDir`
+    ///
+    /// `address` is the 7-bit I2C address of the device.
+    pub fn new(i2c: I2c, address: u8, commands: Cmds) -> Self {
+        Self {
+            pin: BusPin {
+                i2c,
+                address,
+                commands,
+                role: Role::Step,
+            },
+        }
+    }
+
+    /// Release the I2C bus
+    pub fn release(self) -> I2c {
+        self.pin.i2c
+    }
+}
+
+impl<I2c, Cmds> Capabilities for I2cStepDir<I2c, Cmds> {}
+
+impl<I2c, Cmds> Step for I2cStepDir<I2c, Cmds>
+where
+    I2c: Write,
+    Cmds: Commands,
+{
+    // The command has been written, once the write returns. There's nothing to
+    // wait for.
+    const PULSE_LENGTH: Nanoseconds = Nanoseconds(0);
+
+    type Step = BusPin<I2c, Cmds>;
+    type Error = Infallible;
+
+    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+        self.pin.role = Role::Step;
+        Ok(&mut self.pin)
+    }
+}
+
+impl<I2c, Cmds> SetDirection for I2cStepDir<I2c, Cmds>
+where
+    I2c: Write,
+    Cmds: Commands,
+{
+    // The command has been written, once the write returns. There's nothing to
+    // wait for.
+    const SETUP_TIME: Nanoseconds = Nanoseconds(0);
+
+    type Dir = BusPin<I2c, Cmds>;
+    type Error = Infallible;
+
+    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+        self.pin.role = Role::Dir;
+        Ok(&mut self.pin)
+    }
+}
+
+/// A virtual STEP or DIR pin that writes commands over I2C
+///
+/// Returned by the [`Step`] and [`SetDirection`] implementations of
+/// [`I2cStepDir`]. As a STEP pin, it writes a step command at the start of
+/// every pulse (when set high) and ignores the end of the pulse. As a DIR pin,
+/// it writes a direction command whenever it is set.
+///
+/// [`Step`]: crate::traits::Step
+/// [`SetDirection`]: crate::traits::SetDirection
+pub struct BusPin<I2c, Cmds> {
+    i2c: I2c,
+    address: u8,
+    commands: Cmds,
+    role: Role,
+}
+
+impl<I2c, Cmds> BusPin<I2c, Cmds>
+where
+    I2c: Write,
+    Cmds: Commands,
+{
+    fn write(&mut self, command: Cmds::Command) -> Result<(), I2c::Error> {
+        self.i2c.write(self.address, command.as_ref())
+    }
+}
+
+impl<I2c, Cmds> OutputPin for BusPin<I2c, Cmds>
+where
+    I2c: Write,
+    Cmds: Commands,
+{
+    type Error = I2c::Error;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        match self.role {
+            // The end of a STEP pulse has no equivalent on the bus.
+            Role::Step => Ok(()),
+            Role::Dir => {
+                let command = self.commands.set_direction(Direction::Backward);
+                self.write(command)
+            }
+        }
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        let command = match self.role {
+            Role::Step => self.commands.step(),
+            Role::Dir => self.commands.set_direction(Direction::Forward),
+        };
+        self.write(command)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Role {
+    Step,
+    Dir,
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        test_util::{Event, Log, MockI2c, MockTimer},
+        Direction, Stepper,
+    };
+
+    use super::{I2cStepDir, RegisterCommands};
+
+    const ADDRESS: u8 = 0x42;
+
+    #[test]
+    fn step_and_direction_should_be_written_as_commands() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(I2cStepDir::new(
+            MockI2c::new(&log),
+            ADDRESS,
+            RegisterCommands {
+                step_register: 0x01,
+                direction_register: 0x02,
+            },
+        ));
+        let mut timer = MockTimer::new(&log);
+
+        stepper
+            .set_direction(Direction::Backward, &mut timer)
+            .wait()
+            .unwrap();
+        stepper.step(&mut timer).wait().unwrap();
+        stepper
+            .set_direction(Direction::Forward, &mut timer)
+            .wait()
+            .unwrap();
+
+        let writes: Vec<_> = log
+            .events()
+            .into_iter()
+            .filter(|event| matches!(event, Event::I2cWrite(..)))
+            .collect();
+        assert_eq!(
+            writes,
+            [
+                Event::I2cWrite(ADDRESS, 0x02),
+                Event::I2cWrite(ADDRESS, 0),
+                Event::I2cWrite(ADDRESS, 0x01),
+                Event::I2cWrite(ADDRESS, 1),
+                Event::I2cWrite(ADDRESS, 0x02),
+                Event::I2cWrite(ADDRESS, 1),
+            ]
+        );
+
+        // There's nothing to wait for, so the timer isn't used.
+        assert!(log
+            .events()
+            .iter()
+            .all(|event| !matches!(event, Event::TimerStart(..))));
+    }
+}
@@ -3,6 +3,24 @@
 //! This module contains the driver implementations that are currently supported
 //! by Stepper. Each sub-module is behind a feature gate, to allow users to only
 //! enable the drivers they actually need. By default, all drivers are enabled.
+//!
+//! In addition, the [`mode_pins`] and [`spi`] modules provide helpers for
+//! implementing drivers, and the [`i2c`] module provides an adapter for
+//! drivers that are controlled over I2C. The [`generic`] module provides a
+//! driver for simple STEP/DIR drivers that don't have a dedicated
+//! implementation, the [`gang`] module provides an adapter for driving a group
+//! of linked motors as one, the [`dry_run`] module provides an adapter for
+//! running motions without generating signals, and the [`active_low`] module
+//! provides an adapter for drivers behind inverting outputs. These are always
+//! available.
+
+pub mod active_low;
+pub mod dry_run;
+pub mod gang;
+pub mod generic;
+pub mod i2c;
+pub mod mode_pins;
+pub mod spi;
 
 #[cfg(feature = "drv8825")]
 pub mod drv8825;
@@ -10,5 +28,11 @@ pub mod drv8825;
 #[cfg(feature = "stspin220")]
 pub mod stspin220;
 
+#[cfg(feature = "tmc2209")]
+pub mod tmc2209;
+
+#[cfg(feature = "tmc5160")]
+pub mod tmc5160;
+
 #[cfg(feature = "dq542ma")]
 pub mod dq542ma;
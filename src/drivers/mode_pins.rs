@@ -0,0 +1,342 @@
+//! Helper for drivers that select the step mode using mode pins
+//!
+//! Many STEP/DIR drivers select their microstepping mode through a number of
+//! mode pins, which are read by the driver while it is being reset. Drivers
+//! for such hardware can use [`ModePinTable`] to implement [`SetStepMode`],
+//! by providing a table that maps each step mode to the states of the mode
+//! pins. The mode pins are passed as [`ModePins`], which is implemented for
+//! arrays and tuples of pins.
+//!
+//! [`SetStepMode`]: crate::traits::SetStepMode
+
+use embedded_hal::digital::{blocking::OutputPin, PinState};
+
+use crate::step_mode::{StepMode, StepMode16};
+
+/// Maps step modes to the states of `N` mode pins
+///
+/// This only covers setting the pins. The timing is handled by the caller of
+/// [`SetStepMode`], typically [`SetStepModeFuture`], based on the driver's
+/// [`SetStepMode::SETUP_TIME`] and [`SetStepMode::HOLD_TIME`]. A driver's
+/// implementation of [`SetStepMode`] can forward to
+/// [`ModePinTable::apply_mode_config`] and [`ModePinTable::enable_driver`].
+/// Drivers that need the mode pins to change one at a time can also forward
+/// [`SetStepMode::apply_mode_pin`] to [`ModePinTable::apply_mode_pin`].
+///
+/// See [`A4988`] for an example, and the [`DRV8825`] driver for a table in use.
+///
+/// [`DRV8825`]: crate::drivers::drv8825::DRV8825
+/// [`SetStepMode`]: crate::traits::SetStepMode
+/// [`SetStepMode::apply_mode_pin`]: crate::traits::SetStepMode::apply_mode_pin
+/// [`SetStepMode::SETUP_TIME`]: crate::traits::SetStepMode::SETUP_TIME
+/// [`SetStepMode::HOLD_TIME`]: crate::traits::SetStepMode::HOLD_TIME
+/// [`SetStepModeFuture`]: crate::SetStepModeFuture
+pub struct ModePinTable<Mode: 'static, const N: usize> {
+    rows: &'static [(Mode, [PinState; N])],
+}
+
+impl<Mode, const N: usize> ModePinTable<Mode, N>
+where
+    Mode: StepMode,
+{
+    /// Create a new instance of `ModePinTable` from a list of rows
+    ///
+    /// Each row contains a step mode and the states of the mode pins that
+    /// select it.
+    pub const fn new(rows: &'static [(Mode, [PinState; N])]) -> Self {
+        Self { rows }
+    }
+
+    /// Returns the states of the mode pins that select the given step mode
+    ///
+    /// Returns `None`, if the table doesn't contain the step mode.
+    pub fn pin_states(&self, step_mode: Mode) -> Option<[PinState; N]> {
+        let step_mode: u16 = step_mode.into();
+
+        self.rows
+            .iter()
+            .find(|(mode, _)| Into::<u16>::into(*mode) == step_mode)
+            .map(|&(_, pin_states)| pin_states)
+    }
+
+    /// Put the driver into reset, then set the mode pins
+    ///
+    /// `reset` is the pin connected to the driver's active-low RESET signal.
+    /// `pins` are the mode pins, in the same order as the pin states in the
+    /// table.
+    pub fn apply_mode_config<Reset, Pins>(
+        &self,
+        step_mode: Mode,
+        reset: &mut Reset,
+        mut pins: Pins,
+    ) -> Result<(), ModePinError<Reset::Error>>
+    where
+        Reset: OutputPin,
+        Pins: ModePins<N, Error = Reset::Error>,
+    {
+        let pin_states = self
+            .pin_states(step_mode)
+            .ok_or(ModePinError::UnsupportedStepMode)?;
+
+        reset.set_low().map_err(ModePinError::Pin)?;
+
+        for (i, state) in IntoIterator::into_iter(pin_states).enumerate() {
+            pins.set_state(i, state).map_err(ModePinError::Pin)?;
+        }
+
+        Ok(())
+    }
+
+    /// Put the driver into reset, then set one of the mode pins
+    ///
+    /// Works like [`ModePinTable::apply_mode_config`], except that only the
+    /// mode pin at `index` is set, and the driver is only put into reset, if
+    /// `index` is zero. Returns `true`, once the last mode pin has been set.
+    ///
+    /// This is intended for implementing [`SetStepMode::apply_mode_pin`], for
+    /// drivers that glitch, if multiple mode pins change at the same time.
+    /// [`SetStepModeFuture`] then takes care of waiting for
+    /// [`SetStepMode::MODE_PIN_SETTLE_TIME`] between pins.
+    ///
+    /// [`SetStepMode::apply_mode_pin`]: crate::traits::SetStepMode::apply_mode_pin
+    /// [`SetStepMode::MODE_PIN_SETTLE_TIME`]: crate::traits::SetStepMode::MODE_PIN_SETTLE_TIME
+    /// [`SetStepModeFuture`]: crate::SetStepModeFuture
+    pub fn apply_mode_pin<Reset, Pins>(
+        &self,
+        step_mode: Mode,
+        reset: &mut Reset,
+        mut pins: Pins,
+        index: usize,
+    ) -> Result<bool, ModePinError<Reset::Error>>
+    where
+        Reset: OutputPin,
+        Pins: ModePins<N, Error = Reset::Error>,
+    {
+        let pin_states = self
+            .pin_states(step_mode)
+            .ok_or(ModePinError::UnsupportedStepMode)?;
+
+        if index == 0 {
+            reset.set_low().map_err(ModePinError::Pin)?;
+        }
+        if let Some(&state) = pin_states.get(index) {
+            pins.set_state(index, state).map_err(ModePinError::Pin)?;
+        }
+
+        Ok(index + 1 >= N)
+    }
+
+    /// Take the driver out of reset, after the mode pins have been set
+    pub fn enable_driver<Reset>(
+        &self,
+        reset: &mut Reset,
+    ) -> Result<(), Reset::Error>
+    where
+        Reset: OutputPin,
+    {
+        reset.set_high()
+    }
+}
+
+/// The `N` mode pins that a [`ModePinTable`] sets
+///
+/// Implemented for arrays of pins, and for tuples of 2 to 4 pins. The pins of
+/// a tuple can have different types, as long as they share an error type.
+/// Since `&mut Pin` implements [`OutputPin`], a driver can pass references to
+/// pins it owns.
+pub trait ModePins<const N: usize> {
+    /// The error that can occur while setting a pin
+    type Error;
+
+    /// Set the pin at `index` to the given state
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `index` is not smaller than `N`.
+    fn set_state(
+        &mut self,
+        index: usize,
+        state: PinState,
+    ) -> Result<(), Self::Error>;
+}
+
+impl<Pin, const N: usize> ModePins<N> for [Pin; N]
+where
+    Pin: OutputPin,
+{
+    type Error = Pin::Error;
+
+    fn set_state(
+        &mut self,
+        index: usize,
+        state: PinState,
+    ) -> Result<(), Self::Error> {
+        self[index].set_state(state)
+    }
+}
+
+macro_rules! impl_mode_pins_for_tuple {
+    ($n:literal, $($pin:ident: $index:tt),*) => {
+        impl<Error, $($pin,)*> ModePins<$n> for ($($pin,)*)
+        where
+            $($pin: OutputPin<Error = Error>,)*
+        {
+            type Error = Error;
+
+            fn set_state(
+                &mut self,
+                index: usize,
+                state: PinState,
+            ) -> Result<(), Self::Error> {
+                match index {
+                    $($index => self.$index.set_state(state),)*
+                    _ => panic!("No mode pin at index {}", index),
+                }
+            }
+        }
+    };
+}
+
+impl_mode_pins_for_tuple!(2, A: 0, B: 1);
+impl_mode_pins_for_tuple!(3, A: 0, B: 1, C: 2);
+impl_mode_pins_for_tuple!(4, A: 0, B: 1, C: 2, D: 3);
+
+/// An error that can occur while using [`ModePinTable`]
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ModePinError<PinError> {
+    /// The table doesn't contain the requested step mode
+    UnsupportedStepMode,
+
+    /// An error originated from using the [`OutputPin`] trait
+    Pin(PinError),
+}
+
+/// The mode pin table of the A4988, for its MS1, MS2, and MS3 pins
+///
+/// This serves as an example of how to define a table. See table 1 of the
+/// [A4988 datasheet](https://www.allegromicro.com/-/media/files/datasheets/a4988-datasheet.pdf).
+pub const A4988: ModePinTable<StepMode16, 3> = {
+    use PinState::*;
+    use StepMode16::*;
+
+    ModePinTable::new(&[
+        (Full, [Low, Low, Low]),
+        (M2, [High, Low, Low]),
+        (M4, [Low, High, Low]),
+        (M8, [High, High, Low]),
+        (M16, [High, High, High]),
+    ])
+};
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::digital::PinState::{self, High, Low};
+
+    use crate::{
+        step_mode::{StepMode as _, StepMode16},
+        test_util::{Event, Log, MockPin},
+    };
+
+    use super::{ModePinError, ModePinTable, A4988};
+
+    #[test]
+    fn a4988_should_set_pins_for_each_step_mode() {
+        use StepMode16::*;
+
+        let expected = [
+            (Full, [Low, Low, Low]),
+            (M2, [High, Low, Low]),
+            (M4, [Low, High, Low]),
+            (M8, [High, High, Low]),
+            (M16, [High, High, High]),
+        ];
+
+        for (step_mode, [ms1, ms2, ms3]) in expected {
+            let log = Log::new();
+            let mut reset = MockPin::new("reset", &log);
+            let mut pins = [
+                MockPin::new("ms1", &log),
+                MockPin::new("ms2", &log),
+                MockPin::new("ms3", &log),
+            ];
+
+            let [pin1, pin2, pin3] = &mut pins;
+            A4988
+                .apply_mode_config(step_mode, &mut reset, [pin1, pin2, pin3])
+                .unwrap();
+            A4988.enable_driver(&mut reset).unwrap();
+
+            assert_eq!(
+                log.events(),
+                [
+                    Event::Pin("reset", Low),
+                    Event::Pin("ms1", ms1),
+                    Event::Pin("ms2", ms2),
+                    Event::Pin("ms3", ms3),
+                    Event::Pin("reset", High),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn apply_mode_pin_should_set_one_pin_at_a_time() {
+        let log = Log::new();
+        let mut reset = MockPin::new("reset", &log);
+        let mut pins = [
+            MockPin::new("ms1", &log),
+            MockPin::new("ms2", &log),
+            MockPin::new("ms3", &log),
+        ];
+
+        let mut apply_mode_pin = |index| {
+            let [pin1, pin2, pin3] = &mut pins;
+            A4988
+                .apply_mode_pin(
+                    StepMode16::M8,
+                    &mut reset,
+                    [pin1, pin2, pin3],
+                    index,
+                )
+                .unwrap()
+        };
+
+        assert!(!apply_mode_pin(0));
+        assert_eq!(
+            log.events(),
+            [Event::Pin("reset", Low), Event::Pin("ms1", High)]
+        );
+
+        log.clear();
+        assert!(!apply_mode_pin(1));
+        assert_eq!(log.events(), [Event::Pin("ms2", High)]);
+
+        log.clear();
+        assert!(apply_mode_pin(2));
+        assert_eq!(log.events(), [Event::Pin("ms3", Low)]);
+    }
+
+    #[test]
+    fn a4988_should_cover_all_step_modes() {
+        for step_mode in StepMode16::iter() {
+            assert!(A4988.pin_states(step_mode).is_some());
+        }
+    }
+
+    #[test]
+    fn apply_mode_config_should_fail_for_missing_step_mode() {
+        const TABLE: ModePinTable<StepMode16, 1> =
+            ModePinTable::new(&[(StepMode16::Full, [PinState::Low])]);
+
+        let log = Log::new();
+        let mut reset = MockPin::new("reset", &log);
+        let mut pin = MockPin::new("ms1", &log);
+
+        let result =
+            TABLE.apply_mode_config(StepMode16::M2, &mut reset, [&mut pin]);
+
+        assert_eq!(result, Err(ModePinError::UnsupportedStepMode));
+        assert_eq!(log.events(), []);
+    }
+}
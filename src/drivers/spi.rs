@@ -0,0 +1,122 @@
+//! Helper for drivers that are configured over SPI
+//!
+//! Some drivers, like the TMC5160, are controlled through STEP and DIR signals,
+//! but configure their microstepping mode (and other settings) through
+//! registers that are written over SPI. Drivers for such hardware can use
+//! [`SpiRegisters`] to write those registers, for example to implement
+//! [`SetStepMode`].
+//!
+//! [`SetStepMode`]: crate::traits::SetStepMode
+
+use embedded_hal::{digital::blocking::OutputPin, spi::blocking::Write};
+
+/// Writes registers over an SPI bus, selecting the device with a CS pin
+///
+/// CS is expected to be active-low. It is driven low before writing, and high
+/// again afterwards.
+///
+/// Register writes use the format common to Trinamic drivers: an address byte,
+/// with the highest bit set to indicate a write, followed by a 32-bit value in
+/// big-endian byte order. Drivers that use a different format can use
+/// [`SpiRegisters::write`] directly.
+///
+/// The write is complete, once the write method returns. This means there's
+/// nothing to wait for, as far as the SPI interface is concerned. Drivers that
+/// need time to apply a new configuration should define their
+/// [`SetStepMode::SETUP_TIME`] and [`SetStepMode::HOLD_TIME`] accordingly,
+/// which the caller of [`SetStepMode`] (typically [`SetStepModeFuture`])
+/// takes care of.
+///
+/// [`SetStepMode`]: crate::traits::SetStepMode
+/// [`SetStepMode::SETUP_TIME`]: crate::traits::SetStepMode::SETUP_TIME
+/// [`SetStepMode::HOLD_TIME`]: crate::traits::SetStepMode::HOLD_TIME
+/// [`SetStepModeFuture`]: crate::SetStepModeFuture
+pub struct SpiRegisters<Spi, Cs> {
+    spi: Spi,
+    cs: Cs,
+}
+
+impl<Spi, Cs> SpiRegisters<Spi, Cs>
+where
+    Spi: Write<u8>,
+    Cs: OutputPin,
+{
+    /// Create a new instance of `SpiRegisters`
+    pub fn new(spi: Spi, cs: Cs) -> Self {
+        Self { spi, cs }
+    }
+
+    /// Write a 32-bit value to the register at `address`
+    pub fn write_register(
+        &mut self,
+        address: u8,
+        value: u32,
+    ) -> Result<(), SpiError<Spi::Error, Cs::Error>> {
+        const WRITE: u8 = 0x80;
+
+        let [data0, data1, data2, data3] = value.to_be_bytes();
+        self.write(&[address | WRITE, data0, data1, data2, data3])
+    }
+
+    /// Write raw bytes to the device
+    ///
+    /// Drives CS low, writes the bytes, then drives CS high again. CS is driven
+    /// high, even if the write fails.
+    pub fn write(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<(), SpiError<Spi::Error, Cs::Error>> {
+        self.cs.set_low().map_err(SpiError::Cs)?;
+        let result = self.spi.write(bytes).map_err(SpiError::Spi);
+        self.cs.set_high().map_err(SpiError::Cs)?;
+
+        result
+    }
+
+    /// Release the SPI bus and CS pin
+    pub fn release(self) -> (Spi, Cs) {
+        (self.spi, self.cs)
+    }
+}
+
+/// An error that can occur while using [`SpiRegisters`]
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpiError<BusError, CsError> {
+    /// An error originated from writing to the SPI bus
+    Spi(BusError),
+
+    /// An error originated from using the CS pin
+    Cs(CsError),
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::digital::PinState::{High, Low};
+
+    use crate::test_util::{Event, Log, MockPin, MockSpi};
+
+    use super::SpiRegisters;
+
+    #[test]
+    fn write_register_should_frame_datagram_with_cs() {
+        let log = Log::new();
+        let mut registers =
+            SpiRegisters::new(MockSpi::new(&log), MockPin::new("cs", &log));
+
+        registers.write_register(0x6c, 0x1234_5678).unwrap();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("cs", Low),
+                Event::SpiWrite(0xec),
+                Event::SpiWrite(0x12),
+                Event::SpiWrite(0x34),
+                Event::SpiWrite(0x56),
+                Event::SpiWrite(0x78),
+                Event::Pin("cs", High),
+            ]
+        );
+    }
+}
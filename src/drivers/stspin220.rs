@@ -17,8 +17,8 @@ use embedded_time::duration::Nanoseconds;
 use crate::{
     step_mode::StepMode256,
     traits::{
-        EnableDirectionControl, EnableStepControl, EnableStepModeControl,
-        SetDirection, SetStepMode, Step,
+        Capabilities, EnableDirectionControl, EnableStepControl,
+        EnableStepModeControl, SetDirection, SetStepMode, Step,
     },
 };
 
@@ -41,6 +41,7 @@ pub struct STSPIN220<
     mode2: Mode2,
     step_mode3: StepMode3,
     dir_mode4: DirMode4,
+    step_mode: Option<StepMode256>,
 }
 
 impl STSPIN220<(), (), (), (), (), ()> {
@@ -53,10 +54,23 @@ impl STSPIN220<(), (), (), (), (), ()> {
             mode2: (),
             step_mode3: (),
             dir_mode4: (),
+            step_mode: None,
         }
     }
 }
 
+impl Default for STSPIN220<(), (), (), (), (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<EnableFault, StandbyReset, Mode1, Mode2, StepMode3, DirMode4> Capabilities
+    for STSPIN220<EnableFault, StandbyReset, Mode1, Mode2, StepMode3, DirMode4>
+{
+    const SUPPORTS_STEP_MODE_CONTROL: bool = true;
+}
+
 impl<
         EnableFault,
         StandbyReset,
@@ -88,6 +102,7 @@ where
             mode2,
             step_mode3: self.step_mode3,
             dir_mode4: self.dir_mode4,
+            step_mode: self.step_mode,
         }
     }
 }
@@ -142,6 +157,7 @@ where
         self.step_mode3.set_state(mode3)?;
         self.dir_mode4.set_state(mode4)?;
 
+        self.step_mode = Some(step_mode);
         Ok(())
     }
 
@@ -149,6 +165,10 @@ where
         // Leave standby mode.
         self.standby_reset.set_high()
     }
+
+    fn step_mode(&self) -> Option<Self::StepMode> {
+        self.step_mode
+    }
 }
 
 impl<
@@ -178,6 +198,7 @@ where
             mode2: self.mode2,
             step_mode3: self.step_mode3,
             dir_mode4,
+            step_mode: self.step_mode,
         }
     }
 }
@@ -232,6 +253,7 @@ where
             mode2: self.mode2,
             step_mode3,
             dir_mode4: self.dir_mode4,
+            step_mode: self.step_mode,
         }
     }
 }
@@ -0,0 +1,462 @@
+//! TMC2209 Driver
+//!
+//! Platform-agnostic driver API for the TMC2209 stepper motor driver. Can be
+//! used on any platform for which implementations of the required
+//! [embedded-hal] traits are available.
+//!
+//! The TMC2209 is controlled through STEP and DIR signals, like other stepper
+//! drivers, but its configuration (including the microstepping mode) is done
+//! through registers that are written over a single-wire UART interface. This
+//! driver only writes registers. If the UART's TX and RX signals are connected
+//! to the same wire, the user is responsible for discarding the echo of each
+//! datagram written.
+//!
+//! For the most part, users are not expected to use this API directly. Please
+//! check out [`Stepper`](crate::Stepper) instead.
+//!
+//! [embedded-hal]: https://crates.io/crates/embedded-hal
+
+use core::convert::Infallible;
+
+use embedded_hal::{digital::blocking::OutputPin, serial::blocking::Write};
+use embedded_time::duration::Nanoseconds;
+
+use crate::{
+    step_mode::StepMode256,
+    traits::{
+        Capabilities, ChopperMode, EnableDirectionControl, EnableStepControl,
+        EnableStepModeControl, SetDirection, SetStepMode, Step as StepTrait,
+    },
+};
+
+// Register addresses and bits, from the register map in the datasheet
+// https://www.trinamic.com/fileadmin/assets/Products/ICs_Documents/TMC2209_Datasheet_V103.pdf
+const GCONF: u8 = 0x00;
+const IHOLD_IRUN: u8 = 0x10;
+const CHOPCONF: u8 = 0x6c;
+
+// GCONF bits
+const GCONF_EN_SPREAD_CYCLE: u32 = 1 << 2;
+const GCONF_PDN_DISABLE: u32 = 1 << 6;
+const GCONF_MSTEP_REG_SELECT: u32 = 1 << 7;
+
+// Reset default of CHOPCONF, as documented in the datasheet
+const CHOPCONF_DEFAULT: u32 = 0x1000_0053;
+const CHOPCONF_MRES_SHIFT: u32 = 24;
+const CHOPCONF_MRES_MASK: u32 = 0xf << CHOPCONF_MRES_SHIFT;
+
+// Reset default of IHOLDDELAY
+const IHOLD_IRUN_IHOLDDELAY: u32 = 1 << 16;
+
+/// The TMC2209 driver API
+///
+/// Users are not expected to use this API directly, except to create an
+/// instance using [`TMC2209::new`], and to access the configuration methods
+/// that are specific to this driver. Please check out
+/// [`Stepper`](crate::Stepper) instead.
+pub struct TMC2209<Serial, Step, Dir> {
+    serial: Serial,
+    step: Step,
+    dir: Dir,
+
+    address: u8,
+
+    // Registers can only be written as a whole, so we need to remember what
+    // we've written before.
+    gconf: u32,
+    chopconf: u32,
+
+    step_mode: Option<StepMode256>,
+}
+
+impl TMC2209<(), (), ()> {
+    /// Create a new instance of `TMC2209`
+    ///
+    /// Uses UART address `0`, which is selected by pulling both MS1 and MS2
+    /// low. Use [`TMC2209::with_address`], if your hardware uses a different
+    /// address.
+    pub fn new() -> Self {
+        Self::with_address(0)
+    }
+
+    /// Create a new instance of `TMC2209` with the given UART address
+    ///
+    /// The address is selected by the MS1 and MS2 pins and must be in the
+    /// range `0` to `3`.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `address` is larger than `3`.
+    pub fn with_address(address: u8) -> Self {
+        assert!(address <= 3, "UART address must be in the range 0 to 3");
+
+        Self {
+            serial: (),
+            step: (),
+            dir: (),
+
+            address,
+
+            // Use the UART interface for configuration, instead of the PDN_UART
+            // and MS1/MS2 pins.
+            gconf: GCONF_PDN_DISABLE | GCONF_MSTEP_REG_SELECT,
+            chopconf: CHOPCONF_DEFAULT,
+
+            step_mode: None,
+        }
+    }
+}
+
+impl Default for TMC2209<(), (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Serial, Step, Dir> Capabilities for TMC2209<Serial, Step, Dir> {
+    const SUPPORTS_STEP_MODE_CONTROL: bool = true;
+    const SUPPORTS_CHOPPER_MODE: bool = true;
+    const SUPPORTS_CURRENT_CONTROL: bool = true;
+    const SUPPORTS_UART_CONFIG: bool = true;
+}
+
+impl<Serial, Step, Dir> TMC2209<Serial, Step, Dir>
+where
+    Serial: Write<u8>,
+{
+    /// Set the motor current
+    ///
+    /// `run` is the current used while the motor is moving, `hold` is the
+    /// current used while it stands still. Both values are given as a fraction
+    /// of the full-scale current in 32ths, minus one, meaning they must be in
+    /// the range `0` to `31`. Larger values are clamped to `31`.
+    pub fn set_current(
+        &mut self,
+        run: u8,
+        hold: u8,
+    ) -> Result<(), Serial::Error> {
+        let run = u32::from(run.min(31));
+        let hold = u32::from(hold.min(31));
+
+        let ihold_irun = IHOLD_IRUN_IHOLDDELAY | run << 8 | hold;
+        self.write_register(IHOLD_IRUN, ihold_irun)
+    }
+
+    /// Select the chopper mode
+    ///
+    /// Selects spreadCycle, if `spread_cycle` is `true`, or stealthChop, if it
+    /// is `false`. stealthChop is the default. It's quieter, while spreadCycle
+    /// provides more torque at higher velocities.
+    pub fn set_spread_cycle(
+        &mut self,
+        spread_cycle: bool,
+    ) -> Result<(), Serial::Error> {
+        if spread_cycle {
+            self.gconf |= GCONF_EN_SPREAD_CYCLE;
+        } else {
+            self.gconf &= !GCONF_EN_SPREAD_CYCLE;
+        }
+
+        self.write_register(GCONF, self.gconf)
+    }
+
+    fn write_register(
+        &mut self,
+        register: u8,
+        value: u32,
+    ) -> Result<(), Serial::Error> {
+        let datagram = write_datagram(self.address, register, value);
+
+        self.serial.write(&datagram)?;
+        self.serial.flush()?;
+
+        Ok(())
+    }
+}
+
+impl<Serial, Step, Dir> ChopperMode for TMC2209<Serial, Step, Dir>
+where
+    Serial: Write<u8>,
+{
+    type Error = Serial::Error;
+
+    /// Select stealthChop, if `quiet` is `true`, or spreadCycle otherwise
+    ///
+    /// See [`TMC2209::set_spread_cycle`]. This writes a register over UART, so
+    /// it's only available after step mode control has been enabled.
+    fn set_quiet(&mut self, quiet: bool) -> Result<(), Self::Error> {
+        self.set_spread_cycle(!quiet)
+    }
+}
+
+impl<Serial, Step, Dir> EnableStepModeControl<Serial> for TMC2209<(), Step, Dir>
+where
+    Serial: Write<u8>,
+{
+    type WithStepModeControl = TMC2209<Serial, Step, Dir>;
+
+    fn enable_step_mode_control(
+        self,
+        serial: Serial,
+    ) -> Self::WithStepModeControl {
+        TMC2209 {
+            serial,
+            step: self.step,
+            dir: self.dir,
+            address: self.address,
+            gconf: self.gconf,
+            chopconf: self.chopconf,
+            step_mode: self.step_mode,
+        }
+    }
+}
+
+impl<Serial, Step, Dir> SetStepMode for TMC2209<Serial, Step, Dir>
+where
+    Serial: Write<u8>,
+{
+    // The new configuration applies as soon as the datagram has been received,
+    // and `apply_mode_config` waits until it has been sent.
+    const SETUP_TIME: Nanoseconds = Nanoseconds(0);
+    const HOLD_TIME: Nanoseconds = Nanoseconds(0);
+
+    type Error = Serial::Error;
+    type StepMode = StepMode256;
+
+    fn apply_mode_config(
+        &mut self,
+        step_mode: Self::StepMode,
+    ) -> Result<(), Self::Error> {
+        use StepMode256::*;
+        let mres = match step_mode {
+            M256 => 0b0000,
+            M128 => 0b0001,
+            M64 => 0b0010,
+            M32 => 0b0011,
+            M16 => 0b0100,
+            M8 => 0b0101,
+            M4 => 0b0110,
+            M2 => 0b0111,
+            Full => 0b1000,
+        };
+
+        // Make sure the driver takes microstepping mode from the register,
+        // not the MS1/MS2 pins.
+        self.write_register(GCONF, self.gconf)?;
+
+        self.chopconf =
+            (self.chopconf & !CHOPCONF_MRES_MASK) | mres << CHOPCONF_MRES_SHIFT;
+        self.write_register(CHOPCONF, self.chopconf)?;
+
+        self.step_mode = Some(step_mode);
+        Ok(())
+    }
+
+    fn enable_driver(&mut self) -> Result<(), Self::Error> {
+        // The TMC2209 doesn't need to be reset to apply a new step mode.
+        Ok(())
+    }
+
+    fn step_mode(&self) -> Option<Self::StepMode> {
+        self.step_mode
+    }
+}
+
+impl<Serial, Step, Dir, OutputPinError> EnableDirectionControl<Dir>
+    for TMC2209<Serial, Step, ()>
+where
+    Dir: OutputPin<Error = OutputPinError>,
+{
+    type WithDirectionControl = TMC2209<Serial, Step, Dir>;
+
+    fn enable_direction_control(self, dir: Dir) -> Self::WithDirectionControl {
+        TMC2209 {
+            serial: self.serial,
+            step: self.step,
+            dir,
+            address: self.address,
+            gconf: self.gconf,
+            chopconf: self.chopconf,
+            step_mode: self.step_mode,
+        }
+    }
+}
+
+impl<Serial, Step, Dir, OutputPinError> SetDirection
+    for TMC2209<Serial, Step, Dir>
+where
+    Dir: OutputPin<Error = OutputPinError>,
+{
+    // STEP/DIR timing, from the electrical characteristics
+    // https://www.trinamic.com/fileadmin/assets/Products/ICs_Documents/TMC2209_Datasheet_V103.pdf
+    const SETUP_TIME: Nanoseconds = Nanoseconds(20);
+
+    type Dir = Dir;
+    type Error = Infallible;
+
+    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+        Ok(&mut self.dir)
+    }
+}
+
+impl<Serial, Step, Dir, OutputPinError> EnableStepControl<Step>
+    for TMC2209<Serial, (), Dir>
+where
+    Step: OutputPin<Error = OutputPinError>,
+{
+    type WithStepControl = TMC2209<Serial, Step, Dir>;
+
+    fn enable_step_control(self, step: Step) -> Self::WithStepControl {
+        TMC2209 {
+            serial: self.serial,
+            step,
+            dir: self.dir,
+            address: self.address,
+            gconf: self.gconf,
+            chopconf: self.chopconf,
+            step_mode: self.step_mode,
+        }
+    }
+}
+
+impl<Serial, Step, Dir, OutputPinError> StepTrait for TMC2209<Serial, Step, Dir>
+where
+    Step: OutputPin<Error = OutputPinError>,
+{
+    // STEP/DIR timing, from the electrical characteristics
+    // https://www.trinamic.com/fileadmin/assets/Products/ICs_Documents/TMC2209_Datasheet_V103.pdf
+    const PULSE_LENGTH: Nanoseconds = Nanoseconds(100);
+
+    type Step = Step;
+    type Error = Infallible;
+
+    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+        Ok(&mut self.step)
+    }
+}
+
+/// Assemble a write access datagram
+///
+/// See the description of the UART write access datagram in the datasheet:
+/// https://www.trinamic.com/fileadmin/assets/Products/ICs_Documents/TMC2209_Datasheet_V103.pdf
+fn write_datagram(address: u8, register: u8, value: u32) -> [u8; 8] {
+    const SYNC: u8 = 0x05;
+    const WRITE: u8 = 0x80;
+
+    let [data0, data1, data2, data3] = value.to_be_bytes();
+
+    let mut datagram = [
+        SYNC,
+        address,
+        register | WRITE,
+        data0,
+        data1,
+        data2,
+        data3,
+        0,
+    ];
+    datagram[7] = crc(&datagram[..7]);
+
+    datagram
+}
+
+/// Compute the CRC8 of a datagram
+///
+/// See the CRC calculation in the datasheet:
+/// https://www.trinamic.com/fileadmin/assets/Products/ICs_Documents/TMC2209_Datasheet_V103.pdf
+fn crc(bytes: &[u8]) -> u8 {
+    let mut crc: u8 = 0;
+
+    for &byte in bytes {
+        let mut byte = byte;
+        for _ in 0..8 {
+            if (crc >> 7) ^ (byte & 0x01) != 0 {
+                crc = (crc << 1) ^ 0x07;
+            } else {
+                crc <<= 1;
+            }
+            byte >>= 1;
+        }
+    }
+
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        test_util::{Event, Log, MockSerial},
+        traits::EnableStepModeControl as _,
+        Stepper,
+    };
+
+    use super::{
+        crc, write_datagram, CHOPCONF, GCONF, GCONF_EN_SPREAD_CYCLE,
+        GCONF_MSTEP_REG_SELECT, GCONF_PDN_DISABLE, TMC2209,
+    };
+
+    #[test]
+    fn set_quiet_should_toggle_spread_cycle_in_gconf() {
+        let log = Log::new();
+        let driver =
+            TMC2209::new().enable_step_mode_control(MockSerial::new(&log));
+        let mut stepper = Stepper::from_driver(driver);
+
+        let written = |value| -> Vec<_> {
+            write_datagram(0, GCONF, value)
+                .iter()
+                .map(|&byte| Event::SerialWrite(byte))
+                .collect()
+        };
+        let gconf = GCONF_PDN_DISABLE | GCONF_MSTEP_REG_SELECT;
+
+        stepper.set_quiet(false).unwrap();
+        assert_eq!(log.events(), written(gconf | GCONF_EN_SPREAD_CYCLE));
+
+        log.clear();
+        stepper.set_quiet(true).unwrap();
+        assert_eq!(log.events(), written(gconf));
+    }
+
+    #[test]
+    fn with_address_should_accept_all_valid_addresses() {
+        for address in 0..=3 {
+            TMC2209::with_address(address);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_address_should_panic_on_invalid_address() {
+        TMC2209::with_address(4);
+    }
+
+    #[test]
+    fn crc_should_match_known_datagrams() {
+        // Read access datagrams for GCONF and IOIN at address 0, as sent by
+        // existing TMC2209 implementations.
+        assert_eq!(crc(&[0x05, 0x00, 0x00]), 0x48);
+        assert_eq!(crc(&[0x05, 0x00, 0x06]), 0x6f);
+    }
+
+    #[test]
+    fn write_datagram_should_follow_datasheet_layout() {
+        let datagram = write_datagram(3, CHOPCONF, 0x1234_5678);
+
+        assert_eq!(
+            datagram[..7],
+            [
+                0x05,            // sync
+                3,               // address
+                CHOPCONF | 0x80, // register, with the write bit
+                0x12,            // data, most significant byte first
+                0x34,
+                0x56,
+                0x78,
+            ]
+        );
+        assert_eq!(datagram[7], crc(&datagram[..7]));
+    }
+}
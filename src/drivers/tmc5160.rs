@@ -0,0 +1,259 @@
+//! TMC5160 Driver
+//!
+//! Platform-agnostic driver API for the TMC5160 stepper motor driver. Can be
+//! used on any platform for which implementations of the required
+//! [embedded-hal] traits are available.
+//!
+//! The TMC5160 has an integrated motion controller, but this driver uses it in
+//! STEP/DIR mode (the SD_MODE pin must be pulled high). The microstepping mode
+//! is configured through registers that are written over SPI.
+//!
+//! For the most part, users are not expected to use this API directly. Please
+//! check out [`Stepper`](crate::Stepper) instead.
+//!
+//! [embedded-hal]: https://crates.io/crates/embedded-hal
+
+use core::convert::Infallible;
+
+use embedded_hal::{digital::blocking::OutputPin, spi::blocking::Write};
+use embedded_time::duration::Nanoseconds;
+
+use crate::{
+    step_mode::StepMode256,
+    traits::{
+        Capabilities, EnableDirectionControl, EnableStepControl,
+        EnableStepModeControl, SetDirection, SetStepMode, Step as StepTrait,
+    },
+};
+
+use super::spi::{SpiError, SpiRegisters};
+
+// Register address, from the register map in the datasheet
+// https://www.trinamic.com/products/integrated-circuits/details/tmc5160/
+const CHOPCONF: u8 = 0x6c;
+
+// The CHOPCONF value from the datasheet's getting started example: TOFF=3,
+// HSTRT=4, HEND=1, TBL=2, spreadCycle. The reset default has TOFF=0, which
+// disables the driver.
+const CHOPCONF_INITIAL: u32 = 0x0001_00c3;
+const CHOPCONF_MRES_SHIFT: u32 = 24;
+const CHOPCONF_MRES_MASK: u32 = 0xf << CHOPCONF_MRES_SHIFT;
+
+/// The TMC5160 driver API
+///
+/// Users are not expected to use this API directly, except to create an
+/// instance using [`TMC5160::new`]. Please check out
+/// [`Stepper`](crate::Stepper) instead.
+pub struct TMC5160<Spi, Step, Dir> {
+    spi: Spi,
+    step: Step,
+    dir: Dir,
+
+    // Registers can only be written as a whole, so we need to remember what
+    // we've written before.
+    chopconf: u32,
+
+    step_mode: Option<StepMode256>,
+}
+
+impl TMC5160<(), (), ()> {
+    /// Create a new instance of `TMC5160`
+    pub fn new() -> Self {
+        Self {
+            spi: (),
+            step: (),
+            dir: (),
+            chopconf: CHOPCONF_INITIAL,
+            step_mode: None,
+        }
+    }
+}
+
+impl Default for TMC5160<(), (), ()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Spi, Step, Dir> Capabilities for TMC5160<Spi, Step, Dir> {
+    const SUPPORTS_STEP_MODE_CONTROL: bool = true;
+    const SUPPORTS_SPI_CONFIG: bool = true;
+}
+
+impl<Spi, Cs, Step, Dir> EnableStepModeControl<(Spi, Cs)>
+    for TMC5160<(), Step, Dir>
+where
+    Spi: Write<u8>,
+    Cs: OutputPin,
+{
+    type WithStepModeControl = TMC5160<SpiRegisters<Spi, Cs>, Step, Dir>;
+
+    fn enable_step_mode_control(
+        self,
+        (spi, cs): (Spi, Cs),
+    ) -> Self::WithStepModeControl {
+        TMC5160 {
+            spi: SpiRegisters::new(spi, cs),
+            step: self.step,
+            dir: self.dir,
+            chopconf: self.chopconf,
+            step_mode: self.step_mode,
+        }
+    }
+}
+
+impl<Spi, Cs, Step, Dir> SetStepMode
+    for TMC5160<SpiRegisters<Spi, Cs>, Step, Dir>
+where
+    Spi: Write<u8>,
+    Cs: OutputPin,
+{
+    // The new configuration applies as soon as the register has been written,
+    // and `apply_mode_config` waits until it has been.
+    const SETUP_TIME: Nanoseconds = Nanoseconds(0);
+    const HOLD_TIME: Nanoseconds = Nanoseconds(0);
+
+    type Error = SpiError<Spi::Error, Cs::Error>;
+    type StepMode = StepMode256;
+
+    fn apply_mode_config(
+        &mut self,
+        step_mode: Self::StepMode,
+    ) -> Result<(), Self::Error> {
+        use StepMode256::*;
+        let mres = match step_mode {
+            M256 => 0b0000,
+            M128 => 0b0001,
+            M64 => 0b0010,
+            M32 => 0b0011,
+            M16 => 0b0100,
+            M8 => 0b0101,
+            M4 => 0b0110,
+            M2 => 0b0111,
+            Full => 0b1000,
+        };
+
+        self.chopconf =
+            (self.chopconf & !CHOPCONF_MRES_MASK) | mres << CHOPCONF_MRES_SHIFT;
+        self.spi.write_register(CHOPCONF, self.chopconf)?;
+
+        self.step_mode = Some(step_mode);
+        Ok(())
+    }
+
+    fn enable_driver(&mut self) -> Result<(), Self::Error> {
+        // The TMC5160 doesn't need to be reset to apply a new step mode.
+        Ok(())
+    }
+
+    fn step_mode(&self) -> Option<Self::StepMode> {
+        self.step_mode
+    }
+}
+
+impl<Spi, Step, Dir, OutputPinError> EnableDirectionControl<Dir>
+    for TMC5160<Spi, Step, ()>
+where
+    Dir: OutputPin<Error = OutputPinError>,
+{
+    type WithDirectionControl = TMC5160<Spi, Step, Dir>;
+
+    fn enable_direction_control(self, dir: Dir) -> Self::WithDirectionControl {
+        TMC5160 {
+            spi: self.spi,
+            step: self.step,
+            dir,
+            chopconf: self.chopconf,
+            step_mode: self.step_mode,
+        }
+    }
+}
+
+impl<Spi, Step, Dir, OutputPinError> SetDirection for TMC5160<Spi, Step, Dir>
+where
+    Dir: OutputPin<Error = OutputPinError>,
+{
+    // STEP/DIR timing, from the electrical characteristics
+    // https://www.trinamic.com/products/integrated-circuits/details/tmc5160/
+    const SETUP_TIME: Nanoseconds = Nanoseconds(20);
+
+    type Dir = Dir;
+    type Error = Infallible;
+
+    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+        Ok(&mut self.dir)
+    }
+}
+
+impl<Spi, Step, Dir, OutputPinError> EnableStepControl<Step>
+    for TMC5160<Spi, (), Dir>
+where
+    Step: OutputPin<Error = OutputPinError>,
+{
+    type WithStepControl = TMC5160<Spi, Step, Dir>;
+
+    fn enable_step_control(self, step: Step) -> Self::WithStepControl {
+        TMC5160 {
+            spi: self.spi,
+            step,
+            dir: self.dir,
+            chopconf: self.chopconf,
+            step_mode: self.step_mode,
+        }
+    }
+}
+
+impl<Spi, Step, Dir, OutputPinError> StepTrait for TMC5160<Spi, Step, Dir>
+where
+    Step: OutputPin<Error = OutputPinError>,
+{
+    // STEP/DIR timing, from the electrical characteristics
+    // https://www.trinamic.com/products/integrated-circuits/details/tmc5160/
+    const PULSE_LENGTH: Nanoseconds = Nanoseconds(100);
+
+    type Step = Step;
+    type Error = Infallible;
+
+    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+        Ok(&mut self.step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::digital::PinState::{High, Low};
+
+    use crate::{
+        step_mode::StepMode256,
+        test_util::{Event, Log, MockPin, MockSpi},
+        traits::{EnableStepModeControl as _, SetStepMode as _},
+    };
+
+    use super::TMC5160;
+
+    #[test]
+    fn apply_mode_config_should_write_chopconf() {
+        let log = Log::new();
+        let mut driver = TMC5160::new().enable_step_mode_control((
+            MockSpi::new(&log),
+            MockPin::new("cs", &log),
+        ));
+
+        driver.apply_mode_config(StepMode256::M16).unwrap();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("cs", Low),
+                // CHOPCONF, with the write bit set
+                Event::SpiWrite(0xec),
+                // MRES=4, the rest is unchanged
+                Event::SpiWrite(0x04),
+                Event::SpiWrite(0x01),
+                Event::SpiWrite(0x00),
+                Event::SpiWrite(0xc3),
+                Event::Pin("cs", High),
+            ]
+        );
+    }
+}
@@ -9,6 +9,8 @@
 //!
 //! - [DRV8825](crate::drivers::drv8825::DRV8825)
 //! - [STSPIN220](crate::drivers::stspin220::STSPIN220)
+//! - [TMC2209](crate::drivers::tmc2209::TMC2209)
+//! - [TMC5160](crate::drivers::tmc5160::TMC5160)
 //!
 //! Please check out the documentation of [`Stepper`], which is the main entry
 //! point to this API.
@@ -61,16 +63,23 @@
 //! #     }
 //! # }
 //! #
+//! # #[derive(Clone, Copy, PartialEq, PartialOrd)]
 //! # pub struct Ticks(Num);
 //! # impl From<Nanoseconds> for Ticks {
 //! #     fn from(_: Nanoseconds) -> Self {
 //! #         Self(Num::from_num(0))
 //! #     }
 //! # }
+//! # impl core::ops::Add for Ticks {
+//! #     type Output = Self;
+//! #     fn add(self, rhs: Self) -> Self::Output {
+//! #         Self(self.0 + rhs.0)
+//! #     }
+//! # }
 //! # impl core::ops::Sub for Ticks {
 //! #     type Output = Self;
 //! #     fn sub(self, rhs: Self) -> Self::Output {
-//! #         Self(Num::from_num(0))
+//! #         Self(self.0 - rhs.0)
 //! #     }
 //! # }
 //! #
@@ -163,12 +172,16 @@
 #![cfg_attr(not(test), no_std)]
 #![deny(missing_docs, rustdoc::broken_intra_doc_links)]
 
+#[cfg(any(test, feature = "test-util"))]
+extern crate alloc;
+
 pub extern crate embedded_hal;
 pub extern crate embedded_time;
 pub extern crate ramp_maker;
 
 pub mod compat;
 pub mod drivers;
+pub mod linear;
 pub mod motion_control;
 pub mod step_mode;
 pub mod traits;
@@ -176,10 +189,17 @@ pub mod util;
 
 mod stepper;
 
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+
 pub use self::stepper::*;
 
 /// Defines the direction in which to rotate the motor
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+///
+/// With the `serde` feature enabled, this is serialized as the name of the
+/// variant (`"Forward"` or `"Backward"`) in human-readable formats.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     /// Rotate the motor forward
     ///
@@ -193,3 +213,81 @@ pub enum Direction {
     /// driver's DIR signal set is LOW.
     Backward = -1,
 }
+
+impl Direction {
+    /// Returns the opposite direction
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::Forward => Direction::Backward,
+            Direction::Backward => Direction::Forward,
+        }
+    }
+
+    /// Applies the direction to a number of steps
+    ///
+    /// Returns `steps` for [`Direction::Forward`] and `-steps` for
+    /// [`Direction::Backward`], which can be added to a position to compute a
+    /// target position. Saturates, if the result doesn't fit into `i32`.
+    pub fn apply(self, steps: u32) -> i32 {
+        let steps = self as i64 * i64::from(steps);
+        steps.clamp(i32::MIN.into(), i32::MAX.into()) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Direction;
+
+    #[test]
+    fn opposite_should_return_other_direction() {
+        assert_eq!(Direction::Forward.opposite(), Direction::Backward);
+        assert_eq!(Direction::Backward.opposite(), Direction::Forward);
+
+        for direction in [Direction::Forward, Direction::Backward] {
+            assert_eq!(direction.opposite().opposite(), direction);
+        }
+    }
+
+    #[test]
+    fn direction_should_apply_sign_to_steps() {
+        assert_eq!(Direction::Forward.apply(40), 40);
+        assert_eq!(Direction::Backward.apply(40), -40);
+        assert_eq!(Direction::Forward.apply(0), 0);
+        assert_eq!(Direction::Backward.apply(0), 0);
+
+        assert_eq!(Direction::Forward.apply(u32::MAX), i32::MAX);
+        assert_eq!(Direction::Backward.apply(u32::MAX), i32::MIN);
+        assert_eq!(Direction::Backward.apply(1 << 31), i32::MIN);
+    }
+
+    #[test]
+    fn direction_should_be_usable_as_map_key() {
+        let mut steps = std::collections::HashMap::new();
+        for direction in IntoIterator::into_iter([
+            Direction::Forward,
+            Direction::Backward,
+            Direction::Forward,
+        ]) {
+            *steps.entry(direction).or_insert(0) += 1;
+        }
+
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[&Direction::Forward], 2);
+        assert_eq!(steps[&Direction::Backward], 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn direction_should_round_trip_through_serde() {
+        for (direction, json) in IntoIterator::into_iter([
+            (Direction::Forward, "\"Forward\""),
+            (Direction::Backward, "\"Backward\""),
+        ]) {
+            assert_eq!(serde_json::to_string(&direction).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<Direction>(json).unwrap(),
+                direction
+            );
+        }
+    }
+}
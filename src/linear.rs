@@ -0,0 +1,140 @@
+//! Helpers for linear axes
+//!
+//! Motors that drive a linear axis (for example through a lead screw or a
+//! belt) are commanded in steps, while positions on the axis are usually
+//! defined in millimeters. [`StepsPerMm`] converts between the two, using
+//! integer math only, which is much cheaper than floating point on
+//! microcontrollers without an FPU.
+
+use core::convert::TryFrom as _;
+
+/// The resolution of a linear axis
+///
+/// Defined as a ratio of steps to millimeters, so resolutions that are not a
+/// whole number of steps per millimeter can be represented exactly. For
+/// example, a motor with 200 steps per revolution, using 16 microsteps, on a
+/// lead screw with a lead of 8 mm, has a resolution of
+/// `StepsPerMm::new(200 * 16, 8)`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StepsPerMm {
+    steps: u32,
+    mm: u32,
+}
+
+impl StepsPerMm {
+    /// Create a new instance of `StepsPerMm`
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `mm` is zero.
+    pub const fn new(steps: u32, mm: u32) -> Self {
+        assert!(mm != 0, "Distance must not be zero");
+        Self { steps, mm }
+    }
+
+    /// Convert a distance into steps
+    ///
+    /// `micrometers` is the distance to convert. The result is suitable for
+    /// [`Stepper::move_by`]. Distances that don't correspond to a whole number
+    /// of steps are rounded according to `rounding`. Results that don't fit
+    /// into `i32` saturate.
+    ///
+    /// [`Stepper::move_by`]: crate::Stepper::move_by
+    pub fn steps(&self, micrometers: i32, rounding: Rounding) -> i32 {
+        // Can't overflow: `i32::MAX * u32::MAX` fits into `i64`.
+        let numerator = i64::from(micrometers) * i64::from(self.steps);
+        let denominator = i64::from(self.mm) * 1000;
+
+        divide(numerator, denominator, rounding)
+    }
+}
+
+/// Divide, rounding as requested and saturating to `i32`
+///
+/// `denominator` must be positive.
+pub(crate) fn divide(
+    numerator: i64,
+    denominator: i64,
+    rounding: Rounding,
+) -> i32 {
+    // Division rounds toward zero.
+    let mut quotient = numerator / denominator;
+
+    if rounding == Rounding::Nearest {
+        // Round half away from zero. Can't overflow, as the remainder is
+        // smaller than the denominator.
+        let remainder = numerator % denominator;
+        if remainder.abs() * 2 >= denominator {
+            quotient += remainder.signum();
+        }
+    }
+
+    i32::try_from(quotient).unwrap_or(if quotient < 0 {
+        i32::MIN
+    } else {
+        i32::MAX
+    })
+}
+
+/// How to round distances that don't correspond to a whole number of steps
+///
+/// See [`StepsPerMm::steps`]. Also used to round positions given in full steps
+/// or revolutions (see [`Stepper::full_steps`]).
+///
+/// [`Stepper::full_steps`]: crate::Stepper::full_steps
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Rounding {
+    /// Round to the nearest step, rounding half a step away from zero
+    Nearest,
+
+    /// Round toward zero, dropping any fraction of a step
+    TowardZero,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rounding, StepsPerMm};
+
+    #[test]
+    fn steps_should_round_at_half_step_boundaries() {
+        // Exactly one step every 2 µm, so odd distances are half a step off.
+        let resolution = StepsPerMm::new(500, 1);
+
+        let cases = [
+            (0, 0, 0),
+            (1, 1, 0),
+            (2, 1, 1),
+            (3, 2, 1),
+            (-1, -1, 0),
+            (-3, -2, -1),
+        ];
+        for (micrometers, nearest, toward_zero) in
+            IntoIterator::into_iter(cases)
+        {
+            assert_eq!(
+                resolution.steps(micrometers, Rounding::Nearest),
+                nearest
+            );
+            assert_eq!(
+                resolution.steps(micrometers, Rounding::TowardZero),
+                toward_zero
+            );
+        }
+
+        // 0.4 and 0.8 steps.
+        let resolution = StepsPerMm::new(3200, 8);
+        assert_eq!(resolution.steps(1, Rounding::Nearest), 0);
+        assert_eq!(resolution.steps(2, Rounding::Nearest), 1);
+        assert_eq!(resolution.steps(10_000, Rounding::Nearest), 4_000);
+    }
+
+    #[test]
+    fn steps_should_saturate() {
+        let resolution = StepsPerMm::new(u32::MAX, 1);
+
+        assert_eq!(resolution.steps(i32::MAX, Rounding::Nearest), i32::MAX);
+        assert_eq!(resolution.steps(i32::MIN, Rounding::TowardZero), i32::MIN);
+    }
+}
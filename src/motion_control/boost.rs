@@ -0,0 +1,261 @@
+use ramp_maker::MotionProfile;
+
+/// Skips the ramp of a motion profile for short moves
+///
+/// Wraps a motion profile (like [`ramp_maker::Trapezoidal`]). Moves of fewer
+/// steps than a threshold don't use the wrapped profile. Instead, they are
+/// made at a flat, constant velocity (the "boost" velocity), which avoids the
+/// overhead of accelerating and decelerating for just a handful of steps. This
+/// is useful for jogging a motor.
+///
+/// This only happens while the wrapped profile is at rest. If a move is
+/// changed to a short one while the wrapped profile is still moving (for
+/// example, by [`MotionControl::move_to_position`] during a motion), the
+/// wrapped profile decelerates, as usual.
+///
+/// The boost velocity is in the same unit as any other velocity passed to the
+/// motion profile. The maximum velocity of a move is still respected: If it is
+/// lower than the boost velocity, short moves are made at the maximum velocity
+/// instead.
+///
+/// Use this as the motion profile of [`SoftwareMotionControl`], to make
+/// [`Stepper`] move like this.
+///
+/// [`MotionControl::move_to_position`]:
+///     crate::traits::MotionControl::move_to_position
+/// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+/// [`Stepper`]: crate::Stepper
+pub struct BoostProfile<Profile>
+where
+    Profile: MotionProfile,
+{
+    profile: Profile,
+    threshold: u32,
+    boost_velocity: Profile::Velocity,
+
+    /// The delay and the remaining steps of an ongoing short move
+    boost: Option<(Option<Profile::Delay>, u32)>,
+
+    /// Whether the wrapped profile might still produce delays
+    profile_active: bool,
+}
+
+impl<Profile> BoostProfile<Profile>
+where
+    Profile: MotionProfile,
+{
+    /// Create a new instance of `BoostProfile`
+    ///
+    /// Moves of fewer than `threshold` steps are made at `boost_velocity`,
+    /// without using `profile`.
+    pub fn new(
+        profile: Profile,
+        threshold: u32,
+        boost_velocity: Profile::Velocity,
+    ) -> Self {
+        Self {
+            profile,
+            threshold,
+            boost_velocity,
+            boost: None,
+            profile_active: false,
+        }
+    }
+
+    /// Access the wrapped motion profile
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// Mutably access the wrapped motion profile
+    pub fn profile_mut(&mut self) -> &mut Profile {
+        &mut self.profile
+    }
+
+    /// Change the threshold and velocity for short moves
+    ///
+    /// Takes effect with the next move.
+    pub fn set_boost(
+        &mut self,
+        threshold: u32,
+        boost_velocity: Profile::Velocity,
+    ) {
+        self.threshold = threshold;
+        self.boost_velocity = boost_velocity;
+    }
+
+    /// Release the wrapped motion profile
+    pub fn release(self) -> Profile {
+        self.profile
+    }
+}
+
+impl<Profile, Num> MotionProfile for BoostProfile<Profile>
+where
+    Profile: MotionProfile<Velocity = Num, Delay = Num>,
+    Num: Copy + PartialOrd + num_traits::Zero + num_traits::Inv<Output = Num>,
+{
+    type Velocity = Num;
+    type Delay = Num;
+
+    fn enter_position_mode(
+        &mut self,
+        max_velocity: Self::Velocity,
+        num_steps: u32,
+    ) {
+        // A move of zero steps is how `SoftwareMotionControl` tells the motion
+        // profile to stop, so the wrapped profile needs to know about it. And
+        // if the wrapped profile is still moving, skipping it would make the
+        // motor jump to the boost velocity.
+        if num_steps == 0 || num_steps >= self.threshold || self.profile_active
+        {
+            self.boost = None;
+            self.profile_active = true;
+            self.profile.enter_position_mode(max_velocity, num_steps);
+            return;
+        }
+
+        let velocity = if max_velocity < self.boost_velocity {
+            max_velocity
+        } else {
+            self.boost_velocity
+        };
+
+        // Same as `ramp_maker::Flat`: No delays for a velocity of zero.
+        let delay = if velocity.is_zero() {
+            None
+        } else {
+            Some(velocity.inv())
+        };
+
+        self.boost = Some((delay, num_steps));
+    }
+
+    fn next_delay(&mut self) -> Option<Self::Delay> {
+        match &mut self.boost {
+            Some((delay, num_steps)) => {
+                if *num_steps == 0 {
+                    return None;
+                }
+
+                *num_steps -= 1;
+                *delay
+            }
+            None => {
+                let delay = self.profile.next_delay();
+                if delay.is_none() {
+                    self.profile_active = false;
+                }
+                delay
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use ramp_maker::{MotionProfile as _, Trapezoidal};
+
+    use crate::motion_control::profile_delays;
+
+    use super::BoostProfile;
+
+    // See the test in `profile_delays`, for why this doesn't use `f32`.
+    type Num = fixed::FixedI64<typenum::U32>;
+
+    fn delays(
+        profile: BoostProfile<Trapezoidal<Num>>,
+        num_steps: u32,
+    ) -> (BoostProfile<Trapezoidal<Num>>, Vec<u32>) {
+        let mut delays =
+            profile_delays(profile, Num::from_num(0.01), num_steps);
+        let result = delays.by_ref().map(|delay| delay.to_num()).collect();
+        (delays.release(), result)
+    }
+
+    #[test]
+    fn boost_profile_should_skip_ramp_for_short_moves() {
+        let profile = BoostProfile::new(
+            Trapezoidal::new(Num::from_num(0.000_000_1)),
+            10,
+            Num::from_num(0.002),
+        );
+
+        // A short move is made at the boost velocity, without a ramp.
+        let (profile, short) = delays(profile, 3);
+        assert_eq!(short.len(), 3);
+        assert!(short.windows(2).all(|delays| delays[0] == delays[1]));
+        // Not exactly 500, due to the rounding of the fixed-point number.
+        assert!((499..=500).contains(&short[0]), "{:?}", short);
+
+        // A long move uses the wrapped profile, which ramps.
+        let (_, ramp) = delays(profile, 300);
+        assert_eq!(ramp.len(), 300);
+        assert!(ramp[0] > ramp[150], "{:?}", ramp);
+        assert!(ramp[299] > ramp[150], "{:?}", ramp);
+    }
+
+    #[test]
+    fn boost_profile_should_respect_max_velocity() {
+        let profile = BoostProfile::new(
+            Trapezoidal::new(Num::from_num(0.000_000_1)),
+            10,
+            Num::from_num(0.002),
+        );
+
+        let delays: Vec<u32> = profile_delays(profile, Num::from_num(0.001), 2)
+            .map(|delay| delay.to_num())
+            .collect();
+        assert_eq!(delays, [1000, 1000]);
+    }
+
+    #[test]
+    fn boost_profile_should_let_wrapped_profile_come_to_a_stop() {
+        let mut profile = BoostProfile::new(
+            Trapezoidal::new(Num::from_num(0.000_000_1)),
+            10,
+            Num::from_num(0.002),
+        );
+        let velocity = Num::from_num(0.01);
+
+        profile.enter_position_mode(velocity, 300);
+        let initial_delay = profile.next_delay().unwrap();
+        for _ in 0..50 {
+            profile.next_delay().unwrap();
+        }
+
+        // A short move during the ramp doesn't skip the deceleration.
+        profile.enter_position_mode(velocity, 5);
+        let ramp_down: Vec<Num> =
+            (0..5).map(|_| profile.next_delay().unwrap()).collect();
+        assert!(
+            ramp_down.windows(2).all(|delays| delays[0] < delays[1]),
+            "{:?}",
+            ramp_down
+        );
+
+        // Abort during the ramp, like `SoftwareMotionControl` does.
+        profile.enter_position_mode(velocity, 300);
+        for _ in 0..50 {
+            profile.next_delay().unwrap();
+        }
+        profile.enter_position_mode(velocity, 0);
+        while profile.next_delay().is_some() {}
+
+        // The next move starts from a standstill.
+        profile.enter_position_mode(velocity, 300);
+        assert_eq!(profile.next_delay(), Some(initial_delay));
+
+        // Once the wrapped profile is at rest, short moves are boosted again.
+        profile.enter_position_mode(velocity, 0);
+        while profile.next_delay().is_some() {}
+        profile.enter_position_mode(velocity, 3);
+        let short: Vec<u32> = (0..3)
+            .map(|_| profile.next_delay().unwrap().to_num())
+            .collect();
+        assert!((499..=500).contains(&short[0]), "{:?}", short);
+        assert_eq!(profile.next_delay(), None);
+    }
+}
@@ -1,9 +1,17 @@
+use core::{cell::Cell, convert::TryInto as _, ops};
+
+use embedded_time::{duration, ConversionError};
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
+
+use crate::compat::Ticks;
+
 /// Converts delay values from RampMaker into timer ticks
 ///
 /// RampMaker is agnostic over the units used, and the unit of the timer ticks
 /// depend on the target platform. This trait allows Stepper to convert between
 /// both types. The user must supply an implementation that matches their
-/// environment.
+/// environment, or use [`DelayToTicksConst`], if their timer has a fixed
+/// frequency.
 ///
 /// The `Delay` parameter specifies the type of delay value used by RampMaker.
 pub trait DelayToTicks<Delay> {
@@ -16,3 +24,206 @@ pub trait DelayToTicks<Delay> {
     /// Convert delay value into timer ticks
     fn delay_to_ticks(&self, delay: Delay) -> Result<Self::Ticks, Self::Error>;
 }
+
+/// A [`DelayToTicks`] implementation for timers with a fixed frequency
+///
+/// Converts delays given as any of the `embedded-time` duration types into
+/// [`Ticks`] for a timer with the frequency defined by `HZ`. This covers the
+/// common case of a timer whose frequency is known at compile time, making a
+/// custom implementation of [`DelayToTicks`] unnecessary.
+///
+/// The conversion rounds down to the nearest whole tick.
+///
+/// `HZ` is defined in Hz.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DelayToTicksConst<const HZ: u32>;
+
+macro_rules! impl_delay_to_ticks_const {
+    ($($duration:ident,)*) => {
+        $(
+            impl<const HZ: u32> DelayToTicks<duration::$duration>
+                for DelayToTicksConst<HZ>
+            {
+                type Ticks = Ticks<u32, HZ>;
+                type Error = ConversionError;
+
+                fn delay_to_ticks(&self, delay: duration::$duration)
+                    -> Result<Self::Ticks, Self::Error>
+                {
+                    delay.try_into()
+                }
+            }
+        )*
+    };
+}
+
+impl_delay_to_ticks_const!(
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+    Minutes,
+    Hours,
+);
+
+/// A [`DelayToTicks`] implementation that dithers fractional delays
+///
+/// Converts delays that are given in timer ticks, but might not be a whole
+/// number of them, into [`Ticks`] for a timer with the frequency defined by
+/// `HZ`, in Hz. This is the case, if velocities are given in steps per tick,
+/// as in the example in the [crate documentation](crate).
+///
+/// Simply rounding each delay would make the motor run slightly faster or
+/// slower than requested, which is noticeable at low speeds, where the error
+/// adds up over many steps. Instead, this rounds each delay down, but carries
+/// the fraction over to the next one. As a result, the delays alternate
+/// between the nearest whole numbers of ticks, and their average matches the
+/// requested delay.
+///
+/// The tradeoff is jitter: Consecutive delays can differ by one tick, even if
+/// the requested delay is constant. Relative to the delay, that's small at low
+/// speeds, where dithering matters most, but grows as delays get shorter. At
+/// high speeds, with delays of only a few ticks, [`DelayToTicksConst`] or a
+/// faster timer might be the better choice.
+///
+/// The fraction is carried across motions, which makes no practical
+/// difference, as it's less than a tick. Returns
+/// [`ConversionError::NegDuration`] for negative delays, and
+/// [`ConversionError::Overflow`] for delays that don't fit into `u32`.
+pub struct DelayToTicksDithered<Delay, const HZ: u32> {
+    fraction: Cell<Delay>,
+}
+
+impl<Delay, const HZ: u32> DelayToTicksDithered<Delay, HZ>
+where
+    Delay: Zero,
+{
+    /// Create a new instance of `DelayToTicksDithered`
+    pub fn new() -> Self {
+        Self {
+            fraction: Cell::new(Delay::zero()),
+        }
+    }
+}
+
+impl<Delay, const HZ: u32> Default for DelayToTicksDithered<Delay, HZ>
+where
+    Delay: Zero,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Delay, const HZ: u32> DelayToTicks<Delay>
+    for DelayToTicksDithered<Delay, HZ>
+where
+    Delay: Copy
+        + PartialOrd
+        + Zero
+        + ops::Sub<Output = Delay>
+        + ToPrimitive
+        + FromPrimitive,
+{
+    type Ticks = Ticks<u32, HZ>;
+    type Error = ConversionError;
+
+    fn delay_to_ticks(&self, delay: Delay) -> Result<Self::Ticks, Self::Error> {
+        let delay = delay + self.fraction.get();
+        if delay < Delay::zero() {
+            return Err(ConversionError::NegDuration);
+        }
+
+        // Converting to an integer truncates, which rounds down here.
+        let ticks = delay.to_u32().ok_or(ConversionError::Overflow)?;
+        let whole = Delay::from_u32(ticks).ok_or(ConversionError::Overflow)?;
+        self.fraction.set(delay - whole);
+
+        Ok(Ticks(ticks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_time::{
+        duration::{Microseconds, Milliseconds, Nanoseconds},
+        ConversionError,
+    };
+
+    use crate::compat::Ticks;
+
+    use super::{DelayToTicks as _, DelayToTicksConst, DelayToTicksDithered};
+
+    #[test]
+    fn delay_to_ticks_const_should_convert_for_1_mhz_timer() {
+        let convert = DelayToTicksConst::<1_000_000>;
+
+        assert_eq!(convert.delay_to_ticks(Nanoseconds(1_000u32)), Ok(Ticks(1)));
+        assert_eq!(
+            convert.delay_to_ticks(Microseconds(250u32)),
+            Ok(Ticks(250))
+        );
+        assert_eq!(
+            convert.delay_to_ticks(Milliseconds(2u32)),
+            Ok(Ticks(2_000))
+        );
+
+        // Fractional ticks are rounded down.
+        assert_eq!(convert.delay_to_ticks(Nanoseconds(999u32)), Ok(Ticks(0)));
+        assert_eq!(convert.delay_to_ticks(Nanoseconds(1_999u32)), Ok(Ticks(1)));
+    }
+
+    #[test]
+    fn delay_to_ticks_const_should_convert_for_72_mhz_timer() {
+        let convert = DelayToTicksConst::<72_000_000>;
+
+        assert_eq!(
+            convert.delay_to_ticks(Nanoseconds(1_000u32)),
+            Ok(Ticks(72))
+        );
+        assert_eq!(convert.delay_to_ticks(Microseconds(10u32)), Ok(Ticks(720)));
+        assert_eq!(
+            convert.delay_to_ticks(Milliseconds(1u32)),
+            Ok(Ticks(72_000))
+        );
+
+        // 100 ns are 7.2 ticks, 10 ns are 0.72 ticks. Both are rounded down.
+        assert_eq!(convert.delay_to_ticks(Nanoseconds(100u32)), Ok(Ticks(7)));
+        assert_eq!(convert.delay_to_ticks(Nanoseconds(10u32)), Ok(Ticks(0)));
+    }
+
+    #[test]
+    fn delay_to_ticks_dithered_should_average_to_fractional_delay() {
+        // See the test of `Trapezoidal` in `motion_control`, for why this
+        // doesn't use `f32`.
+        type Num = fixed::FixedI64<typenum::U32>;
+
+        let convert = DelayToTicksDithered::<Num, 1_000_000>::new();
+        let delay = Num::from_num(100.25);
+
+        let mut total = 0;
+        for _ in 0..1000 {
+            let Ticks(ticks) = convert.delay_to_ticks(delay).unwrap();
+            assert!(ticks == 100 || ticks == 101, "{}", ticks);
+            total += ticks;
+        }
+
+        // Rounding each delay would have resulted in 100_000.
+        assert_eq!(total, 100_250);
+    }
+
+    #[test]
+    fn delay_to_ticks_dithered_should_reject_invalid_delays() {
+        let convert = DelayToTicksDithered::<f32, 1_000_000>::new();
+
+        assert_eq!(
+            convert.delay_to_ticks(-1.0),
+            Err(ConversionError::NegDuration)
+        );
+        assert_eq!(
+            convert.delay_to_ticks(1e10),
+            Err(ConversionError::Overflow)
+        );
+        assert_eq!(convert.delay_to_ticks(2.0), Ok(Ticks(2)));
+    }
+}
@@ -1,7 +1,10 @@
+use core::fmt;
+
 /// An error that can occur while using [`SoftwareMotionControl`]
 ///
 /// [`SoftwareMotionControl`]: super::SoftwareMotionControl
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error<
     SetDirectionPinUnavailable,
     SetDirectionError,
@@ -38,20 +41,215 @@ pub enum Error<
 
     /// Error while waiting for a step to finish
     StepDelay(TimerError),
+
+    /// The delay for a step was shorter than the driver's STEP pulse
+    ///
+    /// This means the motion profile requested a higher speed than the driver
+    /// can support. The step has been made, but the step delay was skipped.
+    DelayTooShort,
+
+    /// The motion profile returned a delay of zero (or less) timer ticks
+    ///
+    /// Waiting for zero ticks would make the motor step as fast as possible,
+    /// which is most likely the result of a misconfigured motion profile. The
+    /// step has been made, but the step delay was skipped.
+    ZeroDelay,
+
+    /// The delay for a step was no longer than the driver's STEP pulse
+    ///
+    /// Converted into timer ticks, the delay requested by the motion profile
+    /// is the same as the driver's STEP pulse, which leaves no time between
+    /// steps. This means the speed exceeds what the resolution of the timer
+    /// can represent. Use a timer with a higher frequency, or a lower speed.
+    /// The step has been made, but the step delay was skipped.
+    SpeedExceedsTimerResolution,
+
+    /// The internal state is invalid and can't be recovered
+    ///
+    /// This happens after a panic during a previous call to
+    /// [`MotionControl::update`], which lost the driver and timer. Any further
+    /// calls to [`MotionControl::update`] return this error. See
+    /// [`SoftwareMotionControl::reset`].
+    ///
+    /// [`MotionControl::update`]: crate::traits::MotionControl::update
+    /// [`SoftwareMotionControl::reset`]: super::SoftwareMotionControl::reset
+    InvalidState,
+
+    /// A move took longer than the timeout allows
+    ///
+    /// The move has been aborted. See
+    /// [`SoftwareMotionControl::set_timeout`].
+    ///
+    /// [`SoftwareMotionControl::set_timeout`]:
+    ///     super::SoftwareMotionControl::set_timeout
+    Timeout,
+
+    /// The target of a move is outside of the soft limits
+    ///
+    /// The move has not been started. See
+    /// [`SoftwareMotionControl::set_soft_limits`].
+    ///
+    /// [`SoftwareMotionControl::set_soft_limits`]:
+    ///     super::SoftwareMotionControl::set_soft_limits
+    OutOfBounds,
+
+    /// A motion is ongoing, pending, or paused
+    ///
+    /// The requested change would corrupt the motion, which is why it has been
+    /// refused. See [`MotionControl::reset_position`].
+    ///
+    /// [`MotionControl::reset_position`]:
+    ///     crate::traits::MotionControl::reset_position
+    Busy,
+
+    /// Error while canceling the timer
+    ///
+    /// See [`SoftwareMotionControl::safe_state`].
+    ///
+    /// [`SoftwareMotionControl::safe_state`]:
+    ///     super::SoftwareMotionControl::safe_state
+    CancelTimer(TimerError),
+}
+
+impl<
+        SetDirectionPinUnavailable,
+        SetDirectionError,
+        StepPinUnavailable,
+        StepError,
+        TimerError,
+        NanosecondsToTicksError,
+        DelayToTicksError,
+    > fmt::Display
+    for Error<
+        SetDirectionPinUnavailable,
+        SetDirectionError,
+        StepPinUnavailable,
+        StepError,
+        TimerError,
+        NanosecondsToTicksError,
+        DelayToTicksError,
+    >
+where
+    SetDirectionPinUnavailable: fmt::Debug,
+    SetDirectionError: fmt::Debug,
+    StepPinUnavailable: fmt::Debug,
+    StepError: fmt::Debug,
+    TimerError: fmt::Debug,
+    NanosecondsToTicksError: fmt::Debug,
+    DelayToTicksError: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SetDirection(err) => {
+                write!(f, "Failed to set direction: {}", err)
+            }
+            Self::Step(err) => write!(f, "Failed to step: {}", err),
+            Self::TimeConversion(err) => write!(f, "{}", err),
+            Self::StepDelay(err) => {
+                write!(f, "Timer error during step delay: {:?}", err)
+            }
+            Self::DelayTooShort => {
+                write!(f, "Step delay shorter than STEP pulse")
+            }
+            Self::ZeroDelay => write!(f, "Step delay of zero timer ticks"),
+            Self::SpeedExceedsTimerResolution => {
+                write!(f, "Speed exceeds timer resolution")
+            }
+            Self::InvalidState => {
+                write!(f, "Invalid internal state, caused by a previous panic")
+            }
+            Self::Timeout => write!(f, "Move timed out"),
+            Self::OutOfBounds => {
+                write!(f, "Target position outside of soft limits")
+            }
+            Self::Busy => write!(f, "Motion control is busy with a motion"),
+            Self::CancelTimer(err) => {
+                write!(f, "Failed to cancel timer: {:?}", err)
+            }
+        }
+    }
+}
+
+/// An error that can occur while using [`PwmMotionControl`]
+///
+/// [`PwmMotionControl`]: super::PwmMotionControl
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PwmError<
+    SetDirectionPinUnavailable,
+    SetDirectionError,
+    PwmStepError,
+    DelayToTicksError,
+> {
+    /// The DIR pin was not accessible
+    SetDirectionPinUnavailable(SetDirectionPinUnavailable),
+
+    /// Error while setting direction
+    SetDirection(SetDirectionError),
+
+    /// Error while generating STEP pulses
+    PwmStep(PwmStepError),
+
+    /// Error while converting a delay into a PWM period
+    DelayToTicks(DelayToTicksError),
 }
 
 /// An error occurred while converting between time formats
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum TimeConversionError<NanosecondsToTicksError, DelayToTicksError> {
     /// Error converting from nanoseconds to timer ticks
-    NanosecondsToTicks(NanosecondsToTicksError),
+    NanosecondsToTicks {
+        /// The value that could not be converted, in nanoseconds
+        nanoseconds: u32,
+
+        /// The error returned by the conversion
+        error: NanosecondsToTicksError,
+    },
 
     /// Error converting from RampMaker delay value to timer ticks
     DelayToTicks(DelayToTicksError),
 }
 
+impl<NanosecondsToTicksError, DelayToTicksError> fmt::Display
+    for TimeConversionError<NanosecondsToTicksError, DelayToTicksError>
+where
+    NanosecondsToTicksError: fmt::Debug,
+    DelayToTicksError: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NanosecondsToTicks { nanoseconds, error } => write!(
+                f,
+                "Failed to convert {} ns to timer ticks: {:?}",
+                nanoseconds, error
+            ),
+            Self::DelayToTicks(err) => {
+                write!(f, "Failed to convert delay to timer ticks: {:?}", err)
+            }
+        }
+    }
+}
+
+/// The move queue of [`SoftwareMotionControl`] is full
+///
+/// See [`SoftwareMotionControl::queue_move`].
+///
+/// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+/// [`SoftwareMotionControl::queue_move`]: super::SoftwareMotionControl::queue_move
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueueFullError;
+
+impl fmt::Display for QueueFullError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Move queue is full")
+    }
+}
+
 /// The software motion control was busy, or another generic error occurred
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum BusyError<T> {
     /// The software motion control was busy
     ///
@@ -62,3 +260,69 @@ pub enum BusyError<T> {
     /// Another error has occurred
     Other(T),
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString as _;
+
+    use crate::SignalError;
+
+    use super::{Error, TimeConversionError};
+
+    #[test]
+    fn error_should_display_message() {
+        type TestError = Error<u8, u8, u8, u8, u8, u8, u8>;
+
+        let cases = [
+            (
+                TestError::SetDirection(SignalError::Pin(1)),
+                "Failed to set direction: Pin error: 1",
+            ),
+            (
+                TestError::Step(SignalError::Timer(2)),
+                "Failed to step: Timer error: 2",
+            ),
+            (
+                TestError::TimeConversion(TimeConversionError::DelayToTicks(3)),
+                "Failed to convert delay to timer ticks: 3",
+            ),
+            (TestError::StepDelay(4), "Timer error during step delay: 4"),
+            (
+                TestError::DelayTooShort,
+                "Step delay shorter than STEP pulse",
+            ),
+            (TestError::ZeroDelay, "Step delay of zero timer ticks"),
+            (
+                TestError::SpeedExceedsTimerResolution,
+                "Speed exceeds timer resolution",
+            ),
+            (
+                TestError::InvalidState,
+                "Invalid internal state, caused by a previous panic",
+            ),
+            (TestError::CancelTimer(5), "Failed to cancel timer: 5"),
+        ];
+
+        for (error, message) in IntoIterator::into_iter(cases) {
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn time_conversion_error_should_display_message() {
+        type TestError = TimeConversionError<u8, u8>;
+
+        assert_eq!(
+            TestError::NanosecondsToTicks {
+                nanoseconds: 500,
+                error: 1,
+            }
+            .to_string(),
+            "Failed to convert 500 ns to timer ticks: 1"
+        );
+        assert_eq!(
+            TestError::DelayToTicks(2).to_string(),
+            "Failed to convert delay to timer ticks: 2"
+        );
+    }
+}
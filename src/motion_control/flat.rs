@@ -0,0 +1,222 @@
+//! Constant-speed motion, using integer math only
+//!
+//! The motion profiles from RampMaker are generic over the numeric type they
+//! use, but that type has to support division. On microcontrollers without an
+//! FPU, that usually means pulling in a fixed-point library, just to move a
+//! motor at a constant speed.
+//!
+//! [`ConstantSpeed`] is a motion profile that doesn't need any of that. Speeds
+//! are given in whole steps per second, and the delays it produces are already
+//! timer ticks, so [`DelayToTicksConst`] can pass them on to the timer as they
+//! are. [`FlatMotionControl`] puts both together.
+//!
+//! Like [`ramp_maker::Flat`], this doesn't accelerate or decelerate, so the
+//! speed needs to be low enough for the motor to start and stop without losing
+//! steps.
+//!
+//! # Example
+//!
+//! ``` rust
+//! # fn main() -> Result<(), stepper::motion_control::Error<
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! #     core::convert::Infallible,
+//! #     stepper::embedded_time::ConversionError,
+//! #     core::convert::Infallible,
+//! # >> {
+//! use stepper::{
+//!     compat::Ticks,
+//!     drivers::generic::GenericDriver,
+//!     motion_control::{
+//!         flat::ConstantSpeed, DelayToTicksConst, SoftwareMotionControl,
+//!     },
+//!     Stepper,
+//! };
+//!
+//! # struct Pin;
+//! # impl stepper::embedded_hal::digital::blocking::OutputPin for Pin {
+//! #     type Error = core::convert::Infallible;
+//! #     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+//! #     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+//! # }
+//! #
+//! # struct Timer;
+//! # impl stepper::embedded_hal::timer::nb::CountDown for Timer {
+//! #     type Error = core::convert::Infallible;
+//! #     type Time = Ticks<u32, 1_000_000>;
+//! #     fn start<T>(&mut self, count: T) -> Result<(), Self::Error>
+//! #         where T: Into<Self::Time>
+//! #     {
+//! #         Ok(())
+//! #     }
+//! #     fn wait(&mut self) -> nb::Result<(), Self::Error> {
+//! #         Ok(())
+//! #     }
+//! # }
+//! #
+//! // The STEP and DIR pins of an A4988 driver, and a 1 MHz timer. How you
+//! // acquire those depends on the platform you run on.
+//! let driver = GenericDriver::<_, _, 1000, 200>::new(Pin, Pin);
+//! let timer = Timer;
+//!
+//! let mut stepper = Stepper::from_driver(SoftwareMotionControl::new(
+//!     driver,
+//!     timer,
+//!     ConstantSpeed::<1_000_000>::new(),
+//!     DelayToTicksConst::<1_000_000>,
+//! ));
+//!
+//! // Move 200 steps forward, at 400 steps per second.
+//! stepper.move_to_position(400, 200).wait()?;
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use core::convert::Infallible;
+
+use ramp_maker::MotionProfile;
+
+use crate::compat::Ticks;
+
+use super::{DelayToTicks, DelayToTicksConst, SoftwareMotionControl};
+
+/// [`SoftwareMotionControl`] with a constant speed
+///
+/// `TIMER_HZ` is the frequency of the timer, in Hz. See the
+/// [module documentation](self) for details.
+pub type FlatMotionControl<Driver, Timer, const TIMER_HZ: u32> =
+    SoftwareMotionControl<
+        Driver,
+        Timer,
+        ConstantSpeed<TIMER_HZ>,
+        DelayToTicksConst<TIMER_HZ>,
+    >;
+
+/// A motion profile that moves at a constant speed
+///
+/// The speed of a motion (its maximum velocity, as far as [`MotionProfile`] is
+/// concerned) is given in steps per second. The delays between steps are
+/// [`Ticks`] of a timer with the frequency `TIMER_HZ`, in Hz. They are rounded
+/// up to the next whole tick, so the motor never moves faster than requested.
+///
+/// A speed of zero produces no delays, just like [`ramp_maker::Flat`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ConstantSpeed<const TIMER_HZ: u32> {
+    delay: Option<Ticks<u32, TIMER_HZ>>,
+    steps_left: u32,
+}
+
+impl<const TIMER_HZ: u32> ConstantSpeed<TIMER_HZ> {
+    /// Create a new instance of `ConstantSpeed`
+    pub fn new() -> Self {
+        Self {
+            delay: None,
+            steps_left: 0,
+        }
+    }
+}
+
+impl<const TIMER_HZ: u32> MotionProfile for ConstantSpeed<TIMER_HZ> {
+    type Velocity = u32;
+    type Delay = Ticks<u32, TIMER_HZ>;
+
+    fn enter_position_mode(
+        &mut self,
+        max_velocity: Self::Velocity,
+        num_steps: u32,
+    ) {
+        self.delay = if max_velocity == 0 {
+            None
+        } else {
+            Some(Ticks(TIMER_HZ.div_ceil(max_velocity)))
+        };
+        self.steps_left = num_steps;
+    }
+
+    fn next_delay(&mut self) -> Option<Self::Delay> {
+        if self.steps_left == 0 {
+            return None;
+        }
+
+        self.steps_left -= 1;
+        self.delay
+    }
+}
+
+impl<const HZ: u32> DelayToTicks<Ticks<u32, HZ>> for DelayToTicksConst<HZ> {
+    type Ticks = Ticks<u32, HZ>;
+    type Error = Infallible;
+
+    fn delay_to_ticks(
+        &self,
+        delay: Ticks<u32, HZ>,
+    ) -> Result<Self::Ticks, Self::Error> {
+        Ok(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use embedded_hal::digital::PinState::Low;
+
+    use crate::{
+        compat::Ticks,
+        motion_control::{profile_delays, DelayToTicksConst},
+        test_util::{Event, Log, MockDriver, MockTimer},
+        traits::MotionControl as _,
+    };
+
+    use super::{ConstantSpeed, FlatMotionControl};
+
+    #[test]
+    fn constant_speed_should_produce_identical_delays() {
+        let delays: Vec<_> =
+            profile_delays(ConstantSpeed::<1_000_000>::new(), 400, 5).collect();
+        assert_eq!(delays, [Ticks(2_500); 5]);
+
+        // Fractional ticks are rounded up.
+        let delays: Vec<_> =
+            profile_delays(ConstantSpeed::<1_000_000>::new(), 3, 2).collect();
+        assert_eq!(delays, [Ticks(333_334); 2]);
+
+        assert_eq!(
+            profile_delays(ConstantSpeed::<1_000_000>::new(), 0, 3).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn flat_motion_control_should_step_at_constant_speed() {
+        let log = Log::new();
+        let mut motion_control: FlatMotionControl<_, _, 1_000_000> =
+            FlatMotionControl::new(
+                MockDriver::new(&log),
+                MockTimer::new(&log),
+                ConstantSpeed::new(),
+                DelayToTicksConst,
+            );
+
+        motion_control.move_to_position(1_000, 4).unwrap();
+        while motion_control.update().unwrap() {}
+
+        let events = log.events();
+        let delays: Vec<_> = events
+            .windows(2)
+            .filter_map(|events| match events {
+                [Event::Pin("step", Low), Event::TimerStart(ticks)] => {
+                    Some(*ticks)
+                }
+                _ => None,
+            })
+            .collect();
+
+        // The pulse length of 2 ticks is part of the delay.
+        assert_eq!(delays, [998; 4]);
+        assert_eq!(motion_control.current_step(), 4);
+    }
+}
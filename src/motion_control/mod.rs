@@ -2,34 +2,77 @@
 //!
 //! See [`SoftwareMotionControl`] for more information.
 
+mod boost;
 mod conversion;
 mod error;
+mod on_step;
+mod outcome;
+mod profile_delays;
+mod pwm;
+mod queue;
+mod resonance;
+mod retarget;
 mod state;
+mod step_counter;
+mod velocity;
+
+pub mod flat;
+
+#[cfg(feature = "motion-stats")]
+mod motion_stats;
+
+#[cfg(feature = "timing-stats")]
+mod timing_stats;
 
 pub use self::{
-    conversion::DelayToTicks,
-    error::{BusyError, Error, TimeConversionError},
+    boost::BoostProfile,
+    conversion::{DelayToTicks, DelayToTicksConst, DelayToTicksDithered},
+    error::{BusyError, Error, PwmError, QueueFullError, TimeConversionError},
+    on_step::OnStep,
+    outcome::MotionOutcome,
+    profile_delays::{profile_delays, ProfileDelays},
+    pwm::PwmMotionControl,
+    queue::MOVE_QUEUE_CAPACITY,
+    resonance::ResonanceAvoidance,
+    step_counter::StepCounter,
+    velocity::{Acceleration, Speed, VelocityConverter},
 };
 
+#[cfg(feature = "motion-stats")]
+pub use self::motion_stats::MotionStats;
+
+#[cfg(feature = "timing-stats")]
+pub use self::timing_stats::TimingStats;
+
 use core::{
+    cmp,
     convert::{Infallible, TryFrom},
-    ops,
+    mem, ops,
 };
 
-use embedded_hal::{digital::blocking::OutputPin, timer::nb as timer};
+use embedded_hal::{
+    digital::{blocking::OutputPin, PinState},
+    timer::nb as timer,
+};
 use embedded_time::duration::Nanoseconds;
 use ramp_maker::MotionProfile;
-use replace_with::replace_with_and_return;
+use replace_with::{replace_with, replace_with_and_return};
 
 use crate::{
     traits::{
-        EnableMotionControl, MotionControl, SetDirection, SetStepMode, Step,
+        Capabilities, ChopperMode, EnableMotionControl, MotionControl,
+        SetDirection, SetEnable, SetStepMode, StallDetection, Step,
     },
     util::ref_mut::RefMut,
-    Direction, SetDirectionFuture, SetStepModeFuture, StepFuture,
+    Direction, EnableFuture, SetDirectionFuture, SetStepModeFuture,
+    SignalError, StepFuture, StepNFuture,
 };
 
-use self::state::State;
+use self::{
+    queue::MoveQueue,
+    retarget::Reversal,
+    state::{RotaryWrap, State},
+};
 
 /// Software implementation of motion control capability
 ///
@@ -41,19 +84,57 @@ use self::state::State;
 /// You can use `SoftwareMotionControl` directly, but like a driver, it is
 /// designed to be used through the [`Stepper`] API.
 ///
+/// Steps are counted using `i32` by default. See [`StepCounter`] for the
+/// overflow behavior and how to use `i64` instead.
+///
+/// `Ticks` is the tick type of the timer, and should be left at its default.
+/// It's only a separate parameter, so the struct doesn't require `Timer` to
+/// implement [`CountDown`].
+///
 /// [`Stepper`]: crate::Stepper
-pub struct SoftwareMotionControl<Driver, Timer, Profile: MotionProfile, Convert>
-{
-    state: State<Driver, Timer, Profile>,
+/// [`CountDown`]: timer::CountDown
+pub struct SoftwareMotionControl<
+    Driver,
+    Timer,
+    Profile: MotionProfile,
+    Convert,
+    Counter = i32,
+    Hook = (),
+    Ticks = <Timer as timer::CountDown>::Time,
+> {
+    state: State<Driver, Timer, Profile, Ticks>,
     new_motion: Option<Direction>,
     profile: Profile,
-    current_step: i32,
+    next_delay: Option<Profile::Delay>,
+    max_velocity: Option<Profile::Velocity>,
+    rotating: bool,
+    current_step: Counter,
+    target_step: Counter,
     current_direction: Direction,
+    invert_direction: bool,
+    pulse_length: Option<Nanoseconds>,
     convert: Convert,
+    on_step: Hook,
+    queue: MoveQueue<Profile::Velocity>,
+    move_ticks: Option<Ticks>,
+    paused: Option<Paused<Profile::Velocity, Counter>>,
+    steps_per_revolution: Option<Counter>,
+    timeout: Option<u32>,
+    move_polls: u32,
+    soft_limits: Option<(Counter, Counter)>,
+    in_motion: bool,
+    zero_distance: bool,
+    outcome: Option<MotionOutcome>,
+
+    #[cfg(feature = "motion-stats")]
+    stats: MotionStats<Ticks>,
+
+    #[cfg(feature = "timing-stats")]
+    timing_stats: TimingStats<Ticks>,
 }
 
-impl<Driver, Timer, Profile, Convert>
-    SoftwareMotionControl<Driver, Timer, Profile, Convert>
+impl<Driver, Timer, Profile, Convert, Ticks>
+    SoftwareMotionControl<Driver, Timer, Profile, Convert, i32, (), Ticks>
 where
     Profile: MotionProfile,
 {
@@ -65,30 +146,234 @@ where
     /// This module provides a blanket implementation of [`EnableMotionControl`]
     /// to make this work.
     ///
+    /// Steps are counted using `i32`. Use [`Self::with_step_counter`] to
+    /// choose a different [`StepCounter`].
+    ///
     /// [`Stepper::enable_motion_control`]: crate::Stepper::enable_motion_control
     pub fn new(
         driver: Driver,
         timer: Timer,
         profile: Profile,
         convert: Convert,
+    ) -> Self {
+        Self::with_step_counter(driver, timer, profile, convert)
+    }
+}
+
+impl<Driver, Timer, Profile, Convert, Counter, Ticks>
+    SoftwareMotionControl<Driver, Timer, Profile, Convert, Counter, (), Ticks>
+where
+    Profile: MotionProfile,
+    Counter: StepCounter,
+{
+    /// Construct a new instance of `SoftwareMotionControl` with the given
+    /// [`StepCounter`]
+    ///
+    /// Works like [`SoftwareMotionControl::new`], except that the type used to
+    /// count steps can be chosen. Use `i64` to prevent the position from
+    /// overflowing during long rotations.
+    pub fn with_step_counter(
+        driver: Driver,
+        timer: Timer,
+        profile: Profile,
+        convert: Convert,
     ) -> Self {
         Self {
             state: State::Idle { driver, timer },
             new_motion: None,
             profile,
-            current_step: 0,
+            next_delay: None,
+            max_velocity: None,
+            rotating: false,
+            current_step: Counter::from(0),
+            target_step: Counter::from(0),
             // Doesn't matter what we initialize it with. We're only using it
             // during an ongoing movement, and it will have been overridden at
             // that point.
             current_direction: Direction::Forward,
+            invert_direction: false,
+            pulse_length: None,
             convert,
+            on_step: (),
+            queue: MoveQueue::new(),
+            move_ticks: None,
+            paused: None,
+            steps_per_revolution: None,
+            timeout: None,
+            move_polls: 0,
+            soft_limits: None,
+            in_motion: false,
+            zero_distance: false,
+            outcome: None,
+
+            #[cfg(feature = "motion-stats")]
+            stats: MotionStats::default(),
+
+            #[cfg(feature = "timing-stats")]
+            timing_stats: TimingStats::default(),
+        }
+    }
+
+    /// Set a hook that is called after every completed step
+    ///
+    /// Consumes this instance of `SoftwareMotionControl` and returns a new one
+    /// that calls `on_step` after every step. See [`OnStep`] for details.
+    ///
+    /// ``` rust
+    /// # use core::convert::Infallible;
+    /// #
+    /// # use stepper::{
+    /// #     compat::Ticks,
+    /// #     embedded_hal::{digital::blocking::OutputPin, timer::nb as timer},
+    /// #     embedded_time::duration::Nanoseconds,
+    /// #     traits::{SetDirection, Step},
+    /// # };
+    /// #
+    /// # struct Pin;
+    /// # impl OutputPin for Pin {
+    /// #     type Error = Infallible;
+    /// #     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// #
+    /// # struct Driver(Pin, Pin);
+    /// # impl SetDirection for Driver {
+    /// #     const SETUP_TIME: Nanoseconds = Nanoseconds(0);
+    /// #     type Dir = Pin;
+    /// #     type Error = Infallible;
+    /// #     fn dir(&mut self) -> Result<&mut Pin, Infallible> { Ok(&mut self.0) }
+    /// # }
+    /// # impl Step for Driver {
+    /// #     const PULSE_LENGTH: Nanoseconds = Nanoseconds(0);
+    /// #     type Step = Pin;
+    /// #     type Error = Infallible;
+    /// #     fn step(&mut self) -> Result<&mut Pin, Infallible> { Ok(&mut self.1) }
+    /// # }
+    /// #
+    /// # struct Timer;
+    /// # impl timer::CountDown for Timer {
+    /// #     type Error = Infallible;
+    /// #     type Time = Ticks<u32, 1_000_000>;
+    /// #     fn start<T>(&mut self, _: T) -> Result<(), Self::Error>
+    /// #         where T: Into<Self::Time>
+    /// #     {
+    /// #         Ok(())
+    /// #     }
+    /// #     fn wait(&mut self) -> nb::Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// #
+    /// # struct DelayToTicks;
+    /// # impl stepper::motion_control::DelayToTicks<f32> for DelayToTicks {
+    /// #     type Ticks = Ticks<u32, 1_000_000>;
+    /// #     type Error = Infallible;
+    /// #     fn delay_to_ticks(&self, delay: f32) -> Result<Self::Ticks, Infallible> {
+    /// #         Ok(Ticks(delay as u32))
+    /// #     }
+    /// # }
+    /// #
+    /// use stepper::{
+    ///     motion_control::SoftwareMotionControl, ramp_maker::Flat,
+    ///     traits::MotionControl as _,
+    /// };
+    ///
+    /// let mut steps_seen = 0;
+    ///
+    /// let mut motion_control = SoftwareMotionControl::new(
+    ///     Driver(Pin, Pin),
+    ///     Timer,
+    ///     Flat::<f32>::new(),
+    ///     DelayToTicks,
+    /// )
+    /// .with_on_step(|_step: i32, _direction| {
+    ///     // This is where you could compare the step against an encoder.
+    ///     steps_seen += 1;
+    /// });
+    ///
+    /// motion_control.move_to_position(0.01, 10).unwrap();
+    /// while motion_control.update().unwrap() {}
+    ///
+    /// drop(motion_control);
+    /// assert_eq!(steps_seen, 10);
+    /// ```
+    pub fn with_on_step<NewHook>(
+        self,
+        on_step: NewHook,
+    ) -> SoftwareMotionControl<
+        Driver,
+        Timer,
+        Profile,
+        Convert,
+        Counter,
+        NewHook,
+        Ticks,
+    >
+    where
+        NewHook: OnStep<Counter>,
+    {
+        SoftwareMotionControl {
+            state: self.state,
+            new_motion: self.new_motion,
+            profile: self.profile,
+            next_delay: self.next_delay,
+            max_velocity: self.max_velocity,
+            rotating: self.rotating,
+            current_step: self.current_step,
+            target_step: self.target_step,
+            current_direction: self.current_direction,
+            invert_direction: self.invert_direction,
+            pulse_length: self.pulse_length,
+            convert: self.convert,
+            on_step,
+            queue: self.queue,
+            move_ticks: self.move_ticks,
+            paused: self.paused,
+            steps_per_revolution: self.steps_per_revolution,
+            timeout: self.timeout,
+            move_polls: self.move_polls,
+            soft_limits: self.soft_limits,
+            in_motion: self.in_motion,
+            zero_distance: self.zero_distance,
+            outcome: self.outcome,
+
+            #[cfg(feature = "motion-stats")]
+            stats: self.stats,
+
+            #[cfg(feature = "timing-stats")]
+            timing_stats: self.timing_stats,
+        }
+    }
+}
+
+impl<Driver, Timer, Profile, Convert, Counter, Hook, Ticks>
+    SoftwareMotionControl<Driver, Timer, Profile, Convert, Counter, Hook, Ticks>
+where
+    Profile: MotionProfile,
+    Counter: StepCounter,
+{
+    /// The position, including a step that has been started, but not counted
+    ///
+    /// If we're in the middle of a step, it's going to be counted once it's
+    /// finished, in the direction it was started in. Anything that computes a
+    /// number of steps for the motion profile needs to take that into account.
+    fn position(&self) -> Counter {
+        match self.state {
+            State::Step { .. } => {
+                self.current_step.wrapping_step(self.current_direction)
+            }
+            _ => self.current_step,
         }
     }
 
     /// Access a reference to the wrapped driver
     ///
-    /// This is only possible if there is no ongoing movement.
+    /// This is only possible if there is no ongoing or pending motion (see
+    /// [`Self::is_idle`]). Between two steps of a motion, the driver and timer
+    /// are not in use, but the motion isn't over yet.
     pub fn driver(&self) -> Option<&Driver> {
+        if !self.is_idle() {
+            return None;
+        }
+
         if let State::Idle { driver, .. } = &self.state {
             return Some(driver);
         }
@@ -98,8 +383,14 @@ where
 
     /// Access a mutable reference to the wrapped driver
     ///
-    /// This is only possible if there is no ongoing movement.
+    /// This is only possible if there is no ongoing or pending motion (see
+    /// [`Self::is_idle`]). Between two steps of a motion, the driver and timer
+    /// are not in use, but the motion isn't over yet.
     pub fn driver_mut(&mut self) -> Option<&mut Driver> {
+        if !self.is_idle() {
+            return None;
+        }
+
         if let State::Idle { driver, .. } = &mut self.state {
             return Some(driver);
         }
@@ -109,8 +400,14 @@ where
 
     /// Access a reference to the wrapped timer
     ///
-    /// This is only possible if there is no ongoing movement.
+    /// This is only possible if there is no ongoing or pending motion (see
+    /// [`Self::is_idle`]). Between two steps of a motion, the driver and timer
+    /// are not in use, but the motion isn't over yet.
     pub fn timer(&self) -> Option<&Timer> {
+        if !self.is_idle() {
+            return None;
+        }
+
         if let State::Idle { timer, .. } = &self.state {
             return Some(timer);
         }
@@ -120,8 +417,14 @@ where
 
     /// Access a mutable reference to the wrapped timer
     ///
-    /// This is only possible if there is no ongoing movement.
+    /// This is only possible if there is no ongoing or pending motion (see
+    /// [`Self::is_idle`]). Between two steps of a motion, the driver and timer
+    /// are not in use, but the motion isn't over yet.
     pub fn timer_mut(&mut self) -> Option<&mut Timer> {
+        if !self.is_idle() {
+            return None;
+        }
+
         if let State::Idle { timer, .. } = &mut self.state {
             return Some(timer);
         }
@@ -129,6 +432,162 @@ where
         None
     }
 
+    /// Invert the direction signal
+    ///
+    /// Depending on the wiring, [`Direction::Forward`] might make one motor
+    /// turn clockwise, and another one counter-clockwise. If `invert` is
+    /// `true`, the DIR signal is inverted, making the motor turn the other
+    /// way. This only affects the signal. Positions and directions, as
+    /// returned by this API or passed to an [`OnStep`] hook, stay the same.
+    /// Homing (see [`Stepper::home`]) takes a direction in the same sense, so
+    /// after inverting the direction, pass the opposite direction to keep
+    /// homing towards the same limit switch.
+    ///
+    /// Takes effect when the next motion starts.
+    ///
+    /// [`Stepper::home`]: crate::Stepper::home
+    pub fn set_invert_direction(&mut self, invert: bool) {
+        self.invert_direction = invert;
+    }
+
+    /// Indicate whether the direction signal is inverted
+    ///
+    /// See [`Self::set_invert_direction`].
+    pub fn is_direction_inverted(&self) -> bool {
+        self.invert_direction
+    }
+
+    /// Wrap the position once per revolution, for a rotary axis
+    ///
+    /// By default, the position grows without bounds, as the motor keeps
+    /// rotating in one direction. Passing `Some` wraps it to the range
+    /// `0..steps_per_revolution` after every step, which keeps it meaningful
+    /// as an angle over long runs. Passing `None` restores the default.
+    ///
+    /// When the position wraps during a move, the target wraps along with it.
+    /// This means a relative move (see [`Stepper::move_by`]) can cross the
+    /// wrapping point, and takes the expected number of steps. A move to an
+    /// absolute position within the range never crosses it.
+    ///
+    /// Takes effect with the next step. A position outside the range is only
+    /// wrapped once it crosses the wrapping point, so call
+    /// [`MotionControl::reset_position`] afterwards, if that matters.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `steps_per_revolution` is zero or larger than `i32::MAX`.
+    ///
+    /// [`Stepper::move_by`]: crate::Stepper::move_by
+    pub fn set_rotary_wrap(&mut self, steps_per_revolution: Option<u32>) {
+        self.steps_per_revolution = steps_per_revolution.map(|steps| {
+            assert!(steps != 0, "Steps per revolution must not be zero");
+            let steps = i32::try_from(steps)
+                .expect("Steps per revolution must fit into `i32`");
+            Counter::from(steps)
+        });
+    }
+
+    /// Fail a move that takes too many calls to finish
+    ///
+    /// Counts the calls to [`MotionControl::update`] (or
+    /// [`Self::update_bounded`]) since the current move started, for which the
+    /// move was still in progress afterwards. Once that count exceeds
+    /// `timeout`, the move is aborted (see [`MotionControl::abort`]), which
+    /// drives the STEP pin to its idle state, and the call returns
+    /// [`Error::Timeout`]. Passing `None` disables the timeout, which is the
+    /// default.
+    ///
+    /// This is meant as a safety net for unattended operation, for example in
+    /// case the timer never finishes. Since this counts calls and not time,
+    /// the timeout has to account for how often you call `update`, and for
+    /// the longest legitimate move. A slow move with long step delays takes
+    /// many calls to finish, if `update` is called in a busy loop, so choose
+    /// a generous limit, or call `update` from a timer interrupt, where the
+    /// number of calls per step is predictable.
+    ///
+    /// Every move has its own count, including queued moves (see
+    /// [`Self::queue_move`]) and resumed ones (see [`Self::resume`]).
+    /// Rotations (see [`MotionControl::rotate`]) go on indefinitely, so they
+    /// never time out. Takes effect with the next call to `update`.
+    pub fn set_timeout(&mut self, timeout: Option<u32>) {
+        self.timeout = timeout;
+    }
+
+    /// Reject moves to positions outside of the given range
+    ///
+    /// Once set, [`MotionControl::move_to_position`] (and with it,
+    /// [`Stepper::move_by`]) checks the target against `min` and `max`, before
+    /// the move starts. Both limits are part of the allowed range. Targets
+    /// outside of it are rejected with [`Error::OutOfBounds`], and the ongoing
+    /// motion is left as it is. Queued moves (see [`Self::queue_move`]) are
+    /// checked when they start, in which case [`MotionControl::update`]
+    /// returns the error.
+    ///
+    /// A rotation (see [`MotionControl::rotate`]) becomes a move to the limit
+    /// in its direction, which means the motion profile decelerates and the
+    /// motor stops there. If the motor already is at or beyond that limit, it
+    /// doesn't move.
+    ///
+    /// The limits are compared to the tracked position, so they only protect
+    /// the machine, if that position is accurate, for example after homing.
+    /// Takes effect with the next motion. Use [`Self::clear_soft_limits`] to
+    /// remove the limits again.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `min` is greater than `max`.
+    ///
+    /// [`Stepper::move_by`]: crate::Stepper::move_by
+    pub fn set_soft_limits(&mut self, min: i32, max: i32) {
+        assert!(min <= max, "Minimum must not be greater than maximum");
+        self.soft_limits = Some((Counter::from(min), Counter::from(max)));
+    }
+
+    /// Remove the limits set by [`Self::set_soft_limits`]
+    pub fn clear_soft_limits(&mut self) {
+        self.soft_limits = None;
+    }
+
+    /// Override the length of the STEP pulse
+    ///
+    /// By default, STEP pulses last for [`Step::PULSE_LENGTH`]. Passing
+    /// `Some` makes them last for the given length instead, which allows
+    /// tuning the timing at runtime. For example, after measuring the jitter
+    /// of a system. Passing `None` restores the default. The length of the
+    /// pulse is also subtracted from the step delay, as it would be otherwise.
+    ///
+    /// Please note that a pulse length shorter than the one required by the
+    /// driver might cause steps to be missed.
+    ///
+    /// Takes effect when the next step starts.
+    pub fn set_pulse_length(&mut self, pulse_length: Option<Nanoseconds>) {
+        self.pulse_length = pulse_length;
+    }
+
+    /// Release the wrapped driver and timer
+    ///
+    /// Drops this instance of `SoftwareMotionControl`, along with the motion
+    /// profile and delay converter, and returns the wrapped driver and timer.
+    /// This is useful, if the timer is needed for something else, once motion
+    /// control is no longer required.
+    ///
+    /// This is only possible if there is no ongoing, pending, queued, or paused
+    /// motion (see [`Self::is_idle`] and [`Self::is_paused`]), as those would
+    /// be lost. Returns `self` unchanged as the error, if there is.
+    pub fn release(self) -> Result<(Driver, Timer), Self> {
+        let releasable = matches!(self.state, State::Idle { .. })
+            && self.is_idle()
+            && self.paused.is_none();
+        if !releasable {
+            return Err(self);
+        }
+
+        match self.state {
+            State::Idle { driver, timer } => Ok((driver, timer)),
+            _ => unreachable!(),
+        }
+    }
+
     /// Access a reference to the wrapped motion profile
     pub fn profile(&self) -> &Profile {
         &self.profile
@@ -139,8 +598,36 @@ where
         &mut self.profile
     }
 
+    /// Preview the delay the motion profile will produce for the next step
+    ///
+    /// Returns `None`, if the motion profile has no more steps to make.
+    ///
+    /// Motion profiles can't be asked for their next delay without advancing
+    /// their internal state. This method therefore takes the next delay from
+    /// the motion profile and caches it. The next step made by
+    /// [`MotionControl::update`] uses the cached delay, instead of asking the
+    /// motion profile again. Calling this method repeatedly returns the same
+    /// delay, until that step has been started.
+    ///
+    /// Starting a new motion, changing the maximum velocity, or aborting
+    /// discards the cached delay, as the motion profile computes a new one
+    /// under the new conditions.
+    pub fn peek_next_delay(&mut self) -> Option<Profile::Delay>
+    where
+        Profile::Delay: Copy,
+    {
+        if self.next_delay.is_none() {
+            self.next_delay = self.profile.next_delay();
+        }
+
+        self.next_delay
+    }
+
     /// Access the current step
-    pub fn current_step(&self) -> i32 {
+    ///
+    /// Unlike [`MotionControl::current_step`], this returns the full value of
+    /// the [`StepCounter`].
+    pub fn current_step(&self) -> Counter {
         self.current_step
     }
 
@@ -149,6 +636,200 @@ where
         self.current_direction
     }
 
+    /// Access the statistics about how long each phase of a motion takes
+    ///
+    /// Statistics accumulate across motions, until they are cleared using
+    /// [`Self::reset_timing_stats`]. See [`TimingStats`] for details.
+    ///
+    /// Only available with the `timing-stats` feature.
+    #[cfg(feature = "timing-stats")]
+    pub fn timing_stats(&self) -> TimingStats<Ticks>
+    where
+        Ticks: Copy,
+    {
+        self.timing_stats
+    }
+
+    /// Clear the statistics returned by [`Self::timing_stats`]
+    ///
+    /// Only available with the `timing-stats` feature.
+    #[cfg(feature = "timing-stats")]
+    pub fn reset_timing_stats(&mut self) {
+        self.timing_stats = TimingStats::default();
+    }
+
+    /// Access the totals of all motions made so far
+    ///
+    /// Totals accumulate across motions, until they are cleared using
+    /// [`Self::reset_stats`]. See [`MotionStats`] for details.
+    ///
+    /// Only available with the `motion-stats` feature.
+    #[cfg(feature = "motion-stats")]
+    pub fn stats(&self) -> MotionStats<Ticks>
+    where
+        Ticks: Copy,
+    {
+        self.stats
+    }
+
+    /// Clear the totals returned by [`Self::stats`]
+    ///
+    /// Only available with the `motion-stats` feature.
+    #[cfg(feature = "motion-stats")]
+    pub fn reset_stats(&mut self) {
+        self.stats = MotionStats::default();
+    }
+
+    /// Indicate whether there is no ongoing or pending motion
+    ///
+    /// This only inspects the internal state and doesn't poll any hardware, so
+    /// it's cheap to call. A motion that has been started, but not yet picked
+    /// up by [`MotionControl::update`], counts as pending. A paused motion
+    /// (see [`Self::pause`]) doesn't, and neither do the moves queued after
+    /// it.
+    ///
+    /// Returns `false`, if a previous panic left the internal state invalid, as
+    /// the driver can't be used anymore. See [`Self::reset`].
+    pub fn is_idle(&self) -> bool {
+        match self.state {
+            State::Idle { .. } => {
+                // Between two steps of a motion, the state can be idle too
+                // (see `Self::update_bounded`).
+                !self.in_motion
+                    && self.new_motion.is_none()
+                    && (self.queue.is_empty() || self.paused.is_some())
+            }
+            State::SetDirection(_)
+            | State::Step { .. }
+            | State::StartStepDelay { .. }
+            | State::StepDelay { .. }
+            | State::Invalid => false,
+        }
+    }
+
+    /// Queue a move to the given position
+    ///
+    /// Works like [`MotionControl::move_to_position`], except that the move
+    /// doesn't replace the ongoing motion. Instead, [`MotionControl::update`]
+    /// starts it as soon as the ongoing motion (and any move queued before it)
+    /// has finished, without returning `Ok(false)` in between. This is useful
+    /// for executing a sequence of moves, like G-code blocks, without having
+    /// to start each of them from the main loop.
+    ///
+    /// Up to [`MOVE_QUEUE_CAPACITY`] moves can be queued. Returns
+    /// [`QueueFullError`], if the queue is full.
+    ///
+    /// Moves are not blended. Each move is a separate motion, meaning the
+    /// motion profile decelerates to a stop at the end of each move, even if
+    /// the next move continues in the same direction. Queued moves count as
+    /// pending motions (see [`Self::is_idle`]). Aborting (see
+    /// [`MotionControl::abort`]) clears the queue. Starting a motion directly,
+    /// using [`MotionControl::move_to_position`] or [`MotionControl::rotate`],
+    /// only replaces the ongoing motion, and leaves the queue as it is.
+    pub fn queue_move(
+        &mut self,
+        max_velocity: Profile::Velocity,
+        target_step: i32,
+    ) -> Result<(), QueueFullError> {
+        self.queue
+            .push(max_velocity, target_step)
+            .map_err(|_| QueueFullError)
+    }
+
+    /// Report how the last motion ended
+    ///
+    /// Returns the outcome of the last motion that has ended since this method
+    /// was last called, then forgets it, so every outcome is reported once.
+    /// Returns `None`, if no motion has ended since. A good time to call this
+    /// is after [`MotionControl::update`] has returned `Ok(false)`.
+    ///
+    /// Each queued move (see [`Self::queue_move`]) is a motion of its own, and
+    /// only the outcome of the last one that ended is kept. Pausing a motion
+    /// (see [`Self::pause`]) doesn't end it.
+    pub fn take_outcome(&mut self) -> Option<MotionOutcome> {
+        self.outcome.take()
+    }
+
+    /// Returns the number of queued moves
+    ///
+    /// This doesn't include the ongoing motion. See [`Self::queue_move`].
+    pub fn queued_moves(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Indicate whether there is an ongoing or pending motion
+    ///
+    /// This is the inverse of [`Self::is_idle`]. See its documentation for
+    /// details.
+    pub fn is_moving(&self) -> bool {
+        !self.is_idle()
+    }
+
+    /// The timer ticks spent on the last move
+    ///
+    /// Sums up the length of every STEP pulse of the last move started by
+    /// [`MotionControl::move_to_position`], and the delays waited for after
+    /// each of them. Comparing this to the expected duration of the move shows
+    /// whether the timer's resolution (or a driver's [`Step::LOW_TIME`]) limits
+    /// the speed. The time spent setting the direction is not included.
+    ///
+    /// The sum is reset when the next move starts. Returns `None`, while a
+    /// motion is ongoing, during and after a rotation, and after an abort.
+    ///
+    /// The sum is not protected against overflow, which might happen during
+    /// very long moves, depending on the timer's tick type.
+    pub fn last_move_ticks(&self) -> Option<Ticks>
+    where
+        Ticks: Copy,
+    {
+        if self.rotating || !self.is_idle() {
+            return None;
+        }
+
+        self.move_ticks
+    }
+
+    /// Enable the wrapped driver
+    ///
+    /// This method is a more convenient alternative to [`Stepper::enable`],
+    /// which requires a timer, while this methods reuses the timer that
+    /// `SoftwareMotionControl` already owns.
+    ///
+    /// However, while [`Stepper::enable`] is part of the generic API, this
+    /// method is only available, if you statically know that you're working
+    /// with a driver wrapped by `SoftwareMotionControl`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BusyError::Busy`], if a motion is ongoing or pending (see
+    /// [`Self::is_idle`]).
+    ///
+    /// [`Stepper::enable`]: crate::Stepper::enable
+    pub fn enable(
+        &mut self,
+    ) -> Result<
+        EnableFuture<RefMut<'_, Driver>, RefMut<'_, Timer>>,
+        BusyError<Infallible>,
+    >
+    where
+        Driver: SetEnable,
+        Timer: timer::CountDown,
+        Timer::Time: TryFrom<Nanoseconds>,
+    {
+        if !self.is_idle() {
+            return Err(BusyError::Busy);
+        }
+
+        let future = match &mut self.state {
+            State::Idle { driver, timer } => {
+                EnableFuture::new(RefMut(driver), RefMut(timer))
+            }
+            _ => return Err(BusyError::Busy),
+        };
+
+        Ok(future)
+    }
+
     /// Set step mode of the wrapped driver
     ///
     /// This method is a more convenient alternative to
@@ -159,16 +840,23 @@ where
     /// this method is only available, if you statically know that you're
     /// working with a driver wrapped by `SoftwareMotionControl`.
     ///
+    /// Changing the step mode during a motion would change the distance each
+    /// step covers, leaving the tracked position meaningless. This is why
+    /// this method rejects any attempt to do so, including while a motion has
+    /// been started, but not picked up by [`MotionControl::update`] yet, while
+    /// moves are queued, and while a motion is paused (see [`Self::pause`]).
+    ///
     /// # Errors
     ///
-    /// Returns [`BusyError::Busy`], if a motion is ongoing.
+    /// Returns [`BusyError::Busy`], if a motion is ongoing, pending, queued, or
+    /// paused.
     ///
     /// [`Stepper::set_step_mode`]: crate::Stepper::set_step_mode
     pub fn set_step_mode(
         &mut self,
         step_mode: Driver::StepMode,
     ) -> Result<
-        SetStepModeFuture<RefMut<Driver>, RefMut<Timer>>,
+        SetStepModeFuture<RefMut<'_, Driver>, RefMut<'_, Timer>>,
         BusyError<Infallible>,
     >
     where
@@ -176,6 +864,12 @@ where
         Timer: timer::CountDown,
         Timer::Time: TryFrom<Nanoseconds>,
     {
+        // A paused motion would be resumed with the new step mode, as would
+        // the moves queued after it.
+        if !self.is_idle() || self.paused.is_some() {
+            return Err(BusyError::Busy);
+        }
+
         let future = match &mut self.state {
             State::Idle { driver, timer } => {
                 SetStepModeFuture::new(step_mode, RefMut(driver), RefMut(timer))
@@ -198,14 +892,15 @@ where
     ///
     /// # Errors
     ///
-    /// Returns [`BusyError::Busy`], if a motion is ongoing.
+    /// Returns [`BusyError::Busy`], if a motion is ongoing or pending (see
+    /// [`Self::is_idle`]).
     ///
     /// [`Stepper::set_direction`]: crate::Stepper::set_direction
     pub fn set_direction(
         &mut self,
         direction: Direction,
     ) -> Result<
-        SetDirectionFuture<RefMut<Driver>, RefMut<Timer>>,
+        SetDirectionFuture<RefMut<'_, Driver>, RefMut<'_, Timer>>,
         BusyError<Infallible>,
     >
     where
@@ -213,6 +908,10 @@ where
         Timer: timer::CountDown,
         Timer::Time: TryFrom<Nanoseconds>,
     {
+        if !self.is_idle() {
+            return Err(BusyError::Busy);
+        }
+
         let future = match &mut self.state {
             State::Idle { driver, timer } => SetDirectionFuture::new(
                 direction,
@@ -237,20 +936,34 @@ where
     ///
     /// # Errors
     ///
-    /// Returns [`BusyError::Busy`], if a motion is ongoing.
+    /// Returns [`BusyError::Busy`], if a motion is ongoing or pending (see
+    /// [`Self::is_idle`]).
     ///
     /// [`Stepper::step`]: crate::Stepper::step
     pub fn step(
         &mut self,
-    ) -> Result<StepFuture<RefMut<Driver>, RefMut<Timer>>, BusyError<Infallible>>
+    ) -> Result<
+        StepFuture<RefMut<'_, Driver>, RefMut<'_, Timer>>,
+        BusyError<Infallible>,
+    >
     where
         Driver: Step,
         Timer: timer::CountDown,
         Timer::Time: TryFrom<Nanoseconds>,
     {
+        if !self.is_idle() {
+            return Err(BusyError::Busy);
+        }
+
         let future = match &mut self.state {
             State::Idle { driver, timer } => {
-                StepFuture::new(RefMut(driver), RefMut(timer))
+                let future = StepFuture::new(RefMut(driver), RefMut(timer));
+                match self.pulse_length {
+                    Some(pulse_length) => {
+                        future.with_pulse_length(pulse_length)
+                    }
+                    None => future,
+                }
             }
             _ => return Err(BusyError::Busy),
         };
@@ -259,15 +972,21 @@ where
     }
 }
 
-impl<Driver, Timer, Profile, Convert> MotionControl
-    for SoftwareMotionControl<Driver, Timer, Profile, Convert>
+impl<Driver, Timer, Profile, Convert, Counter, Hook> MotionControl
+    for SoftwareMotionControl<Driver, Timer, Profile, Convert, Counter, Hook>
 where
+    Counter: StepCounter,
     Driver: SetDirection + Step,
     Profile: MotionProfile,
     Timer: timer::CountDown,
     Profile::Velocity: Copy,
     Convert: DelayToTicks<Profile::Delay, Ticks = Timer::Time>,
-    Convert::Ticks: TryFrom<Nanoseconds> + ops::Sub<Output = Convert::Ticks>,
+    Convert::Ticks: TryFrom<Nanoseconds>
+        + ops::Add<Output = Convert::Ticks>
+        + ops::Sub<Output = Convert::Ticks>
+        + PartialOrd
+        + Copy,
+    Hook: OnStep<Counter>,
 {
     type Velocity = Profile::Velocity;
     type Error = Error<
@@ -285,147 +1004,2927 @@ where
         max_velocity: Self::Velocity,
         target_step: i32,
     ) -> Result<(), Self::Error> {
-        let steps_from_here = target_step - self.current_step;
+        let target_step = Counter::from(target_step);
 
-        self.profile
-            .enter_position_mode(max_velocity, steps_from_here.abs() as u32);
+        if let Some((min, max)) = self.soft_limits {
+            if target_step < min || target_step > max {
+                return Err(Error::OutOfBounds);
+            }
+        }
 
-        let direction = if steps_from_here > 0 {
-            Direction::Forward
-        } else {
-            Direction::Backward
-        };
+        self.start_move(max_velocity, target_step);
+        Ok(())
+    }
+
+    /// Rotate the motor in the given direction, at the given velocity
+    ///
+    /// If soft limits are set (see [`SoftwareMotionControl::set_soft_limits`]),
+    /// this moves to the limit in the given direction instead.
+    fn rotate(
+        &mut self,
+        direction: Direction,
+        velocity: Self::Velocity,
+    ) -> Result<(), Self::Error> {
+        if let Some((min, max)) = self.soft_limits {
+            // Never move away from the limit, if we're already beyond it.
+            let position = self.position();
+            let target_step = match direction {
+                Direction::Forward => cmp::max(position, max),
+                Direction::Backward => cmp::min(position, min),
+            };
+
+            self.start_move(velocity, target_step);
+            // The move might be empty, but still goes in this direction.
+            self.new_motion = Some(direction);
+            return Ok(());
+        }
+
+        retarget::enter_rotation(&mut self.profile, velocity);
+        self.next_delay = None;
+
+        self.max_velocity = Some(velocity);
+        self.rotating = true;
         self.new_motion = Some(direction);
+        self.move_ticks = None;
+        self.in_motion = true;
+        self.zero_distance = false;
+        self.paused = None;
+
+        Ok(())
+    }
+
+    /// Change the maximum velocity of the ongoing motion
+    ///
+    /// This re-enters position mode of the motion profile with the new maximum
+    /// velocity and the steps that are left. This relies on the motion profile
+    /// keeping its internal state (like the current velocity) when doing so,
+    /// which is the case for all of RampMaker's motion profiles ([`Flat`] and
+    /// [`Trapezoidal`]). Motion profiles that don't behave like that might
+    /// cause an abrupt change in velocity.
+    ///
+    /// [`Flat`]: ramp_maker::Flat
+    /// [`Trapezoidal`]: ramp_maker::Trapezoidal
+    fn set_max_speed(
+        &mut self,
+        max_velocity: Self::Velocity,
+    ) -> Result<(), Self::Error> {
+        let position = self.position();
+        if retarget::set_max_speed(
+            &mut self.profile,
+            &mut self.max_velocity,
+            max_velocity,
+            self.rotating,
+            position,
+            self.target_step,
+        ) {
+            self.next_delay = None;
+        }
 
         Ok(())
     }
 
+    /// Reset the tracked position to the given value
+    ///
+    /// Any steps made afterwards are counted from the new position. This is
+    /// intended to be used while idle, for example after homing.
+    ///
+    /// Returns [`Error::Busy`], if a motion is ongoing, pending, queued, or
+    /// paused (see [`SoftwareMotionControl::is_idle`]), as its steps would be
+    /// counted from the new position, but its target wouldn't change.
     fn reset_position(&mut self, step: i32) -> Result<(), Self::Error> {
+        if !self.is_idle() || self.paused.is_some() {
+            return Err(Error::Busy);
+        }
+
+        let step = Counter::from(step);
         self.current_step = step;
+        self.target_step = step;
         Ok(())
     }
 
-    fn update(&mut self) -> Result<bool, Self::Error> {
-        // Otherwise the closure will borrow all of `self`.
-        let new_motion = &mut self.new_motion;
-        let profile = &mut self.profile;
-        let current_step = &mut self.current_step;
-        let current_direction = &mut self.current_direction;
-        let convert = &self.convert;
+    /// Access the current step
+    ///
+    /// If the [`StepCounter`] is wider than `i32`, this wraps around, once the
+    /// position no longer fits into `i32`. Use
+    /// [`SoftwareMotionControl::current_step`] to get the full value.
+    fn current_step(&mut self) -> Result<i32, Self::Error> {
+        Ok(self.current_step.wrapping_to_i32())
+    }
 
-        replace_with_and_return(
-            &mut self.state,
-            || State::Invalid,
-            |state| {
-                state::update(
-                    state,
-                    new_motion,
-                    profile,
-                    current_step,
-                    current_direction,
-                    convert,
-                )
-            },
-        )
+    fn current_direction(&mut self) -> Result<Direction, Self::Error> {
+        Ok(self.current_direction)
     }
-}
 
-// We could also implement the various "enable" traits here, but those
-// implementations can only work while we have access to the driver, which
-// mostly means we'd have to be idle. Since the "enable" traits are infallible,
-// we'd have to panic, and I don't know if that would be worth it.
-
-impl<Driver, Timer, Profile, Convert> SetStepMode
-    for SoftwareMotionControl<Driver, Timer, Profile, Convert>
-where
-    Driver: SetStepMode,
-    Profile: MotionProfile,
-{
-    const SETUP_TIME: Nanoseconds = Driver::SETUP_TIME;
-    const HOLD_TIME: Nanoseconds = Driver::HOLD_TIME;
+    /// Reverse the ongoing motion
+    ///
+    /// This starts a new motion in the opposite direction, which means the
+    /// direction is changed as usual, respecting the driver's
+    /// [`SetDirection::SETUP_TIME`]. The motion profile is not reset, meaning
+    /// the motor doesn't decelerate before changing direction.
+    fn reverse(&mut self) -> Result<(), Self::Error> {
+        let reversal = retarget::reversal(
+            self.max_velocity,
+            self.rotating,
+            self.current_direction,
+            self.position(),
+            self.target_step,
+        );
 
-    type Error = BusyError<Driver::Error>;
-    type StepMode = Driver::StepMode;
+        match reversal {
+            Reversal::Nothing => Ok(()),
+            Reversal::Rotate(direction, velocity) => {
+                self.rotate(direction, velocity)
+            }
+            Reversal::MoveTo(max_velocity, target_step) => {
+                if let Some((min, max)) = self.soft_limits {
+                    if target_step < min || target_step > max {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
 
-    fn apply_mode_config(
-        &mut self,
-        step_mode: Self::StepMode,
-    ) -> Result<(), Self::Error> {
-        match self.driver_mut() {
-            Some(driver) => driver
-                .apply_mode_config(step_mode)
-                .map_err(|err| BusyError::Other(err)),
-            None => Err(BusyError::Busy),
+                self.start_move(max_velocity, target_step);
+                Ok(())
+            }
         }
     }
 
-    fn enable_driver(&mut self) -> Result<(), Self::Error> {
-        match self.driver_mut() {
-            Some(driver) => {
-                driver.enable_driver().map_err(|err| BusyError::Other(err))
-            }
-            None => Err(BusyError::Busy),
+    fn remaining_steps(&mut self) -> Result<Option<u32>, Self::Error> {
+        if self.rotating {
+            return Ok(None);
         }
-    }
-}
 
-impl<Driver, Timer, Profile, Convert> SetDirection
-    for SoftwareMotionControl<Driver, Timer, Profile, Convert>
-where
-    Driver: SetDirection,
-    Profile: MotionProfile,
-{
-    const SETUP_TIME: Nanoseconds = Driver::SETUP_TIME;
+        // RampMaker's motion profiles don't expose the number of steps they
+        // have left, but we know where the motion is supposed to end.
+        Ok(Some(self.current_step.distance(self.target_step)))
+    }
 
-    type Dir = Driver::Dir;
-    type Error = BusyError<Driver::Error>;
+    /// Abort any ongoing motion immediately
+    ///
+    /// Returns the motion control state to idle and drives the STEP pin to its
+    /// idle state (see [`Step::pulse_polarity`]), in case a step was
+    /// interrupted. An interrupted step is never counted, but since it might
+    /// or might not have been registered by the driver,
+    /// [`SoftwareMotionControl::current_step`] might be off by one afterwards.
+    ///
+    /// Returns [`Error::InvalidState`], if a previous panic left the internal
+    /// state invalid. See [`SoftwareMotionControl::reset`].
+    fn abort(&mut self) -> Result<(), Self::Error> {
+        self.stop_motion()?;
 
-    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
-        match self.driver_mut() {
-            Some(driver) => driver.dir().map_err(|err| BusyError::Other(err)),
-            None => Err(BusyError::Busy),
+        if let State::Idle { driver, .. } = &mut self.state {
+            let idle = !driver.pulse_polarity();
+            driver
+                .step()
+                .map_err(|err| Error::Step(SignalError::PinUnavailable(err)))?
+                .set_state(idle)
+                .map_err(|err| Error::Step(SignalError::Pin(err)))?;
         }
+
+        Ok(())
     }
-}
 
-impl<Driver, Timer, Profile, Convert> Step
-    for SoftwareMotionControl<Driver, Timer, Profile, Convert>
-where
-    Driver: Step,
-    Profile: MotionProfile,
-{
-    const PULSE_LENGTH: Nanoseconds = Driver::PULSE_LENGTH;
+    /// Make progress on the ongoing motion
+    ///
+    /// Once a motion has finished, the next queued move is started, if any
+    /// (see [`SoftwareMotionControl::queue_move`]).
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        loop {
+            if self.update_state(usize::MAX)? {
+                return Ok(true);
+            }
 
-    type Step = Driver::Step;
-    type Error = BusyError<Driver::Error>;
+            if self.paused.is_some() {
+                return Ok(false);
+            }
 
-    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
-        match self.driver_mut() {
-            Some(driver) => driver.step().map_err(|err| BusyError::Other(err)),
-            None => Err(BusyError::Busy),
+            match self.queue.pop() {
+                Some((max_velocity, target_step)) => {
+                    self.move_to_position(max_velocity, target_step)?
+                }
+                None => return Ok(false),
+            }
         }
     }
 }
 
-// Blanket implementation of `EnableMotionControl` for all STEP/DIR stepper
-// drivers.
-impl<Driver, Timer, Profile, Convert>
-    EnableMotionControl<(Timer, Profile, Convert)> for Driver
+impl<Driver, Timer, Profile, Convert, Counter, Hook>
+    SoftwareMotionControl<Driver, Timer, Profile, Convert, Counter, Hook>
 where
+    Counter: StepCounter,
     Driver: SetDirection + Step,
     Profile: MotionProfile,
     Timer: timer::CountDown,
     Profile::Velocity: Copy,
     Convert: DelayToTicks<Profile::Delay, Ticks = Timer::Time>,
-    Convert::Ticks: TryFrom<Nanoseconds> + ops::Sub<Output = Convert::Ticks>,
+    Convert::Ticks: TryFrom<Nanoseconds>
+        + ops::Add<Output = Convert::Ticks>
+        + ops::Sub<Output = Convert::Ticks>
+        + PartialOrd
+        + Copy,
+    Hook: OnStep<Counter>,
 {
-    type WithMotionControl =
-        SoftwareMotionControl<Driver, Timer, Profile, Convert>;
+    /// Make progress on the ongoing motion, doing a bounded amount of work
+    ///
+    /// Works like [`MotionControl::update`], except that it returns after at
+    /// most `max_transitions` transitions of the internal state machine (for
+    /// example, from setting the direction to making a step). A single call to
+    /// [`MotionControl::update`] can go through several of those. Limiting
+    /// them bounds the time spent per call, which can be useful in a
+    /// hard-real-time loop.
+    ///
+    /// Returns `Ok(true)`, if the limit has been reached, even if the motion
+    /// turns out to be finished on the next call. The state is preserved, and
+    /// the next call continues where this one left off. If `max_transitions`
+    /// is `0`, no progress is made.
+    ///
+    /// The motion still counts as ongoing between two such calls (see
+    /// [`Self::is_idle`]), so the driver and timer stay unavailable (see
+    /// [`Self::driver_mut`]), even if the limit was reached between two steps.
+    pub fn update_bounded(
+        &mut self,
+        max_transitions: usize,
+    ) -> Result<bool, <Self as MotionControl>::Error> {
+        if self.update_state(max_transitions)? {
+            return Ok(true);
+        }
 
-    fn enable_motion_control(
-        self,
-        (timer, profile, convert): (Timer, Profile, Convert),
-    ) -> Self::WithMotionControl {
-        SoftwareMotionControl::new(self, timer, profile, convert)
+        if self.paused.is_some() {
+            return Ok(false);
+        }
+
+        // Starting a queued move doesn't make a transition. The next call
+        // picks it up.
+        match self.queue.pop() {
+            Some((max_velocity, target_step)) => {
+                self.move_to_position(max_velocity, target_step)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Make a single step in the current direction, and block until it's done
+    ///
+    /// Sets the DIR signal to [`Self::current_direction`] (which is the
+    /// direction of the last motion), waits for the driver's setup time, makes
+    /// one STEP pulse of [`Step::PULSE_LENGTH`], then waits for the driver's
+    /// [`Step::LOW_TIME`], so another step can follow right away. The position
+    /// is updated and the [`OnStep`] hook is called, just like for a step made
+    /// during a motion. A pulse length set with [`Self::set_pulse_length`]
+    /// doesn't apply.
+    ///
+    /// This doesn't use the motion profile and is meant for jogging the motor
+    /// by hand, or for calibration. Calling it at a high rate doesn't
+    /// accelerate the motor, so the rate must be low enough for the motor to
+    /// start at that speed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BusyError::Busy`], if a motion is ongoing, pending, queued, or
+    /// paused (see [`Self::is_idle`]). Returns [`Error::OutOfBounds`], if the
+    /// step would leave the soft limits (see [`Self::set_soft_limits`]).
+    pub fn step_once(
+        &mut self,
+    ) -> Result<(), BusyError<<Self as MotionControl>::Error>> {
+        // A delay of zero is extended to the driver's LOW_TIME.
+        self.step_n(1, Nanoseconds(0))
+    }
+
+    /// Make `count` steps at a constant rate, and block until they're done
+    ///
+    /// Like [`Self::step_once`], but makes `count` steps in
+    /// [`Self::current_direction`], without a motion profile. `delay` is the
+    /// time from the start of one step to the start of the next, but at least
+    /// the driver's [`Step::PULSE_LENGTH`] plus [`Step::LOW_TIME`]. There's no
+    /// acceleration or deceleration, so `delay` must be long enough for the
+    /// motor to start at that speed.
+    ///
+    /// Unlike [`Stepper::step_n`], which works on the bare driver, every
+    /// completed STEP pulse is counted into [`Self::current_step`], calls the
+    /// [`OnStep`] hook, and is included in the motion statistics, just like a
+    /// step made during a motion. If an error occurs part of the way, the
+    /// position reflects the steps made up to that point.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BusyError::Busy`], if a motion is ongoing, pending, queued, or
+    /// paused (see [`Self::is_idle`]). Returns [`Error::OutOfBounds`], before
+    /// making the first step that would leave the soft limits (see
+    /// [`Self::set_soft_limits`]).
+    ///
+    /// [`Stepper::step_n`]: crate::Stepper::step_n
+    pub fn step_n(
+        &mut self,
+        count: u32,
+        delay: Nanoseconds,
+    ) -> Result<(), BusyError<<Self as MotionControl>::Error>> {
+        // Between two steps of a motion, the state is idle too, and the steps
+        // would cut the motion short, as they reset the target.
+        if !self.is_idle() || self.paused.is_some() {
+            return Err(BusyError::Busy);
+        }
+        if count == 0 {
+            return Ok(());
+        }
+
+        let direction = self.current_direction;
+        let soft_limits = self.soft_limits;
+        let within_limits = |step: Counter| match soft_limits {
+            Some((min, max)) => step >= min && step <= max,
+            None => true,
+        };
+        // Check the first step before setting the DIR signal, so nothing
+        // happens, if it's out of bounds.
+        if !within_limits(self.current_step.wrapping_step(direction)) {
+            return Err(BusyError::Other(Error::OutOfBounds));
+        }
+
+        let (driver, timer) = match &mut self.state {
+            State::Idle { driver, timer } => (driver, timer),
+            _ => return Err(BusyError::Busy),
+        };
+
+        // Only the signal is inverted, same as during a motion.
+        let signal = if self.invert_direction {
+            direction.opposite()
+        } else {
+            direction
+        };
+        SetDirectionFuture::new(
+            signal,
+            RefMut(&mut *driver),
+            RefMut(&mut *timer),
+        )
+        .wait()
+        .map_err(|err| BusyError::Other(Error::SetDirection(err)))?;
+
+        let mut rotary_wrap =
+            self.steps_per_revolution
+                .map(|steps_per_revolution| RotaryWrap {
+                    steps_per_revolution,
+                    target_step: None,
+                });
+
+        for i in 0..count {
+            if i > 0
+                && !within_limits(self.current_step.wrapping_step(direction))
+            {
+                return Err(BusyError::Other(Error::OutOfBounds));
+            }
+
+            // Each step is made on its own, so it can be counted as soon as
+            // its pulse has completed.
+            StepNFuture::new(
+                RefMut(&mut *driver),
+                RefMut(&mut *timer),
+                1,
+                delay,
+            )
+            .wait()
+            .map_err(|err| BusyError::Other(Error::Step(err)))?;
+
+            state::count_step(
+                &mut self.current_step,
+                direction,
+                rotary_wrap.as_mut(),
+            );
+            self.target_step = self.current_step;
+            self.on_step.on_step(self.current_step, direction);
+
+            #[cfg(feature = "motion-stats")]
+            {
+                self.stats.total_steps += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pause the ongoing motion
+    ///
+    /// Completes the current STEP pulse, if one is in progress, then stops
+    /// stepping immediately, like [`Self::shutdown`]. Unlike aborting, the
+    /// motion is remembered, and [`Self::resume`] picks it up where it stopped.
+    /// Moves queued after it (see [`Self::queue_move`]) are kept, but not
+    /// started while paused.
+    ///
+    /// The motor isn't decelerated, so pausing at high speed might cause it
+    /// to overshoot or lose steps. What happens to the coils is up to the
+    /// caller. For example, they can be de-energized by disabling the driver,
+    /// as long as it's enabled again before resuming.
+    ///
+    /// Does nothing, if there is no ongoing motion, or if it's already paused.
+    pub fn pause(&mut self) -> Result<(), <Self as MotionControl>::Error> {
+        if self.paused.is_some() {
+            return Ok(());
+        }
+
+        let paused = match self.max_velocity {
+            Some(max_velocity) if self.rotating => Paused::Rotation {
+                velocity: max_velocity,
+                direction: self.current_direction,
+            },
+            Some(max_velocity) if self.position() != self.target_step => {
+                Paused::Move {
+                    max_velocity,
+                    target_step: self.target_step,
+                }
+            }
+            // There's no motion to pause.
+            _ => return Ok(()),
+        };
+
+        // Aborting clears the queue, but a paused motion keeps it. It hasn't
+        // ended either, so it doesn't have an outcome yet.
+        let queue = mem::replace(&mut self.queue, MoveQueue::new());
+        let outcome = self.outcome;
+        let result = self.shutdown();
+        self.queue = queue;
+        self.outcome = outcome;
+        result?;
+
+        self.paused = Some(paused);
+
+        Ok(())
+    }
+
+    /// Resume a motion paused by [`Self::pause`]
+    ///
+    /// The motion starts again from a standstill, accelerating as the motion
+    /// profile defines, just like a new motion would. It ends at the original
+    /// target. Does nothing, if no motion is paused.
+    pub fn resume(&mut self) -> Result<(), <Self as MotionControl>::Error> {
+        match self.paused.take() {
+            Some(Paused::Move {
+                max_velocity,
+                target_step,
+            }) => self.start_move(max_velocity, target_step),
+            Some(Paused::Rotation {
+                velocity,
+                direction,
+            }) => self.rotate(direction, velocity)?,
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    /// Indicate whether a motion has been paused
+    ///
+    /// See [`Self::pause`].
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// Stop stepping, but leave the driver as it is
+    ///
+    /// Returns the motion control state to idle and discards the ongoing
+    /// motion, any queued moves, and any paused motion, like
+    /// [`MotionControl::abort`]. Unlike aborting, this doesn't touch any pins:
+    /// DIR keeps its level, and the ENABLE signal is left alone, so an enabled
+    /// driver keeps the coils energized and the motor holds its position.
+    /// Disabling the driver (see [`SetEnable::disable`]) is what de-energizes
+    /// the coils.
+    ///
+    /// Since the STEP pin is left alone too, it stays in its active state, if
+    /// a STEP pulse was in progress. Use [`Self::shutdown`] to complete that
+    /// pulse first, or call this only after [`MotionControl::update`] has
+    /// returned.
+    ///
+    /// Returns [`Error::InvalidState`], if a previous panic left the internal
+    /// state invalid. See [`Self::reset`].
+    pub fn hold(&mut self) -> Result<(), <Self as MotionControl>::Error> {
+        self.stop_motion()
+    }
+
+    fn stop_motion(&mut self) -> Result<(), <Self as MotionControl>::Error> {
+        if let State::Invalid = self.state {
+            return Err(Error::InvalidState);
+        }
+
+        // A paused motion hasn't ended yet, so discarding it aborts it.
+        if self.in_motion || self.paused.is_some() {
+            self.outcome = Some(MotionOutcome::Aborted);
+        }
+        self.in_motion = false;
+
+        self.new_motion = None;
+        self.rotating = false;
+        self.target_step = self.current_step;
+        self.queue.clear();
+        self.move_ticks = None;
+        self.move_polls = 0;
+        self.paused = None;
+
+        // RampMaker has no way to reset a motion profile, but a motion profile
+        // that is told to go nowhere will come to a stop. It's not moving the
+        // motor, so we can just run it until it does.
+        if let Some(max_velocity) = self.max_velocity.take() {
+            self.profile.enter_position_mode(max_velocity, 0);
+            while self.profile.next_delay().is_some() {}
+        }
+        self.next_delay = None;
+
+        replace_with(&mut self.state, || State::Invalid, state::abort);
+
+        Ok(())
+    }
+
+    fn start_move(
+        &mut self,
+        max_velocity: Profile::Velocity,
+        target_step: Counter,
+    ) {
+        let position = self.position();
+
+        self.profile
+            .enter_position_mode(max_velocity, position.distance(target_step));
+        self.next_delay = None;
+        self.max_velocity = Some(max_velocity);
+        self.rotating = false;
+        self.target_step = target_step;
+        self.move_ticks = None;
+        self.move_polls = 0;
+        self.in_motion = true;
+        self.zero_distance = target_step == position;
+        self.paused = None;
+
+        let direction = if target_step > position {
+            Direction::Forward
+        } else {
+            Direction::Backward
+        };
+        self.new_motion = Some(direction);
+    }
+
+    /// Return to a safe, idle state
+    ///
+    /// Aborts any ongoing motion (see [`MotionControl::abort`]), then drives
+    /// the STEP pin to its idle state and the DIR pin to its level for
+    /// [`Direction::Backward`] (low, unless the driver inverts it, see
+    /// [`SetDirection::dir_polarity`]). This is intended to be used when
+    /// entering a safe state, for example after an error.
+    ///
+    /// If setting either pin fails, the other one is still set, and the first
+    /// error is returned.
+    ///
+    /// # Recovering from a panic
+    ///
+    /// If a previous call to [`MotionControl::update`] panicked, and the panic
+    /// was caught, the internal state is invalid. This method returns
+    /// [`Error::InvalidState`] then, as do [`MotionControl::update`],
+    /// [`MotionControl::abort`], [`Self::hold`], and the other methods that
+    /// need the driver, so that code that handles the first panic doesn't
+    /// cause another one. Getters that can't fail return a conservative value
+    /// instead (see [`Self::is_idle`], for example).
+    ///
+    /// The driver and timer are owned by the internal state, and a panic while
+    /// updating that state drops them. Once that has happened, they can't be
+    /// recovered, and neither can this instance of `SoftwareMotionControl`. The
+    /// STEP and DIR pins are left as they were, and it's up to the caller to
+    /// bring the hardware into a safe state by other means (for example by
+    /// disabling the driver, if it was never moved in here).
+    pub fn reset(&mut self) -> Result<(), <Self as MotionControl>::Error> {
+        self.stop_and_drive_pins(|driver| {
+            (!driver.pulse_polarity(), !driver.dir_polarity())
+        })
+    }
+
+    /// Force all outputs low and return to idle, for use in a fault handler
+    ///
+    /// Discards any ongoing motion, like [`Self::hold`], and returns to the
+    /// idle state. Then drives the STEP and DIR pins low, regardless of the
+    /// driver's polarity (see [`Step::pulse_polarity`] and
+    /// [`SetDirection::dir_polarity`]), and cancels the timer, if a motion was
+    /// using it. A STEP pulse that is cut short isn't counted, so
+    /// [`Self::current_step`] might be off by one afterwards.
+    ///
+    /// This is best-effort: If any of these fail, the rest is still done, and
+    /// the first error is returned. Please note that [`timer::Cancel`] also
+    /// reports an error, if the timer has already finished counting down,
+    /// which is harmless here.
+    ///
+    /// Unlike [`Self::reset`], which drives the pins to their idle levels,
+    /// this is meant for hardware where low is known to be safe. Like
+    /// [`Self::reset`], this returns [`Error::InvalidState`] without touching
+    /// anything, if a previous panic left the internal state invalid.
+    pub fn safe_state(&mut self) -> Result<(), <Self as MotionControl>::Error>
+    where
+        Timer: timer::Cancel,
+    {
+        let timer_running = match self.state {
+            State::Idle { .. } => false,
+            State::Invalid => return Err(Error::InvalidState),
+            _ => true,
+        };
+
+        let mut result =
+            self.stop_and_drive_pins(|_| (PinState::Low, PinState::Low));
+
+        if let State::Idle { timer, .. } = &mut self.state {
+            if timer_running {
+                result = result.and(timer.cancel().map_err(Error::CancelTimer));
+            }
+        }
+
+        result
+    }
+
+    /// Discard any motion, then drive STEP and DIR to the levels from `levels`
+    ///
+    /// This is the common part of [`Self::reset`] and [`Self::safe_state`].
+    /// `levels` gets access to the driver, for its polarities, and returns the
+    /// levels for the STEP and DIR pins. If either pin fails, the other one is
+    /// still set, and the first error is returned.
+    fn stop_and_drive_pins(
+        &mut self,
+        levels: impl FnOnce(&Driver) -> (PinState, PinState),
+    ) -> Result<(), <Self as MotionControl>::Error> {
+        self.stop_motion()?;
+
+        let mut result = Ok(());
+        if let State::Idle { driver, .. } = &mut self.state {
+            let (step_level, dir_level) = levels(driver);
+
+            let step = match driver.step() {
+                Ok(pin) => pin
+                    .set_state(step_level)
+                    .map_err(|err| Error::Step(SignalError::Pin(err))),
+                Err(err) => Err(Error::Step(SignalError::PinUnavailable(err))),
+            };
+            result = result.and(step);
+
+            let dir = match driver.dir() {
+                Ok(pin) => pin
+                    .set_state(dir_level)
+                    .map_err(|err| Error::SetDirection(SignalError::Pin(err))),
+                Err(err) => {
+                    Err(Error::SetDirection(SignalError::PinUnavailable(err)))
+                }
+            };
+            if dir.is_ok() {
+                self.current_direction = direction_of_dir_level(
+                    driver,
+                    dir_level,
+                    self.invert_direction,
+                );
+            }
+            result = result.and(dir);
+        }
+
+        result
+    }
+
+    /// Complete the current STEP pulse, then abort
+    ///
+    /// If a STEP pulse is in progress, waits until it has lasted for
+    /// [`Step::PULSE_LENGTH`], ends it, and counts the step, the same way
+    /// [`MotionControl::update`] would. Then aborts the rest of the motion
+    /// (see [`MotionControl::abort`]), which leaves the STEP pin in its idle
+    /// state. Call this before dropping `SoftwareMotionControl`, or before
+    /// releasing the driver.
+    ///
+    /// `SoftwareMotionControl` doesn't do this on drop, as it can't require
+    /// the driver to implement [`Step`] there. Dropping it while a STEP pulse
+    /// is in progress leaves the STEP pin in its active state. Use
+    /// [`Self::finish`], if errors are to be ignored, like a drop would.
+    ///
+    /// If an error occurs while completing the STEP pulse, the motion is not
+    /// aborted, and calling this method again will retry.
+    pub fn shutdown(&mut self) -> Result<(), <Self as MotionControl>::Error> {
+        let current_step = &mut self.current_step;
+        let current_direction = self.current_direction;
+        let pulse_length = self.pulse_length;
+        let convert = &self.convert;
+        let on_step = &mut self.on_step;
+        #[cfg(feature = "motion-stats")]
+        let ticks_before = self.move_ticks;
+        #[cfg(feature = "motion-stats")]
+        let on_step = &mut motion_stats::CountSteps {
+            hook: on_step,
+            steps: &mut self.stats.total_steps,
+        };
+        let move_ticks = if self.rotating {
+            None
+        } else {
+            Some(&mut self.move_ticks)
+        };
+        #[cfg(feature = "timing-stats")]
+        let timing = &mut self.timing_stats;
+        #[cfg(not(feature = "timing-stats"))]
+        let timing = &mut ();
+        // The motion is aborted right after, so the target doesn't matter.
+        let rotary_wrap =
+            self.steps_per_revolution
+                .map(|steps_per_revolution| RotaryWrap {
+                    steps_per_revolution,
+                    target_step: None,
+                });
+
+        let result = replace_with_and_return(
+            &mut self.state,
+            || State::Invalid,
+            |state| {
+                state::finish_step(
+                    state,
+                    current_step,
+                    current_direction,
+                    pulse_length,
+                    convert,
+                    on_step,
+                    move_ticks,
+                    rotary_wrap,
+                    timing,
+                )
+            },
+        );
+
+        #[cfg(feature = "motion-stats")]
+        self.stats.record(
+            ticks_before,
+            self.move_ticks,
+            current_direction,
+            self.current_direction,
+        );
+
+        result?;
+
+        self.abort()
+    }
+
+    /// Complete or end the current STEP pulse, then abort, ignoring errors
+    ///
+    /// This is what dropping `SoftwareMotionControl` would do, if it could
+    /// (see [`Self::shutdown`]). It works like [`Self::shutdown`], except that
+    /// it can't fail. If completing the STEP pulse fails, the pulse is cut
+    /// short instead, like [`MotionControl::abort`] does, and isn't counted.
+    /// Then the STEP pin is driven to its idle state, unless that fails too.
+    ///
+    /// Does nothing, if a previous panic left the internal state invalid (see
+    /// [`Self::reset`]).
+    pub fn finish(&mut self) {
+        if let State::Invalid = self.state {
+            return;
+        }
+
+        if self.shutdown().is_err() {
+            // There's nobody to report this to. Abort anyway, which releases
+            // the STEP pulse, if that's where it failed.
+            let _ = self.abort();
+        }
+    }
+
+    fn update_state(
+        &mut self,
+        max_transitions: usize,
+    ) -> Result<bool, <Self as MotionControl>::Error> {
+        if self.rotating {
+            if let Some(max_velocity) = self.max_velocity {
+                retarget::enter_rotation(&mut self.profile, max_velocity);
+            }
+        }
+
+        // Otherwise the closure will borrow all of `self`.
+        let new_motion = &mut self.new_motion;
+        let profile = &mut self.profile;
+        let next_delay = &mut self.next_delay;
+        let current_step = &mut self.current_step;
+        let current_direction = &mut self.current_direction;
+        let invert_direction = self.invert_direction;
+        let pulse_length = self.pulse_length;
+        let convert = &self.convert;
+        let on_step = &mut self.on_step;
+        #[cfg(feature = "motion-stats")]
+        let (ticks_before, direction_before) =
+            (self.move_ticks, *current_direction);
+        #[cfg(feature = "motion-stats")]
+        let on_step = &mut motion_stats::CountSteps {
+            hook: on_step,
+            steps: &mut self.stats.total_steps,
+        };
+        #[cfg(feature = "timing-stats")]
+        let timing = &mut self.timing_stats;
+        #[cfg(not(feature = "timing-stats"))]
+        let timing = &mut ();
+        // A rotation might go on long enough to overflow the sum.
+        let move_ticks = if self.rotating {
+            None
+        } else {
+            Some(&mut self.move_ticks)
+        };
+        // The target of a rotation doesn't matter, and wrapping it each
+        // revolution could overflow it.
+        let rotary_wrap = match self.steps_per_revolution {
+            Some(steps_per_revolution) => Some(RotaryWrap {
+                steps_per_revolution,
+                target_step: if self.rotating {
+                    None
+                } else {
+                    Some(&mut self.target_step)
+                },
+            }),
+            None => None,
+        };
+
+        let result = replace_with_and_return(
+            &mut self.state,
+            || State::Invalid,
+            |state| {
+                state::update(
+                    state,
+                    new_motion,
+                    profile,
+                    next_delay,
+                    current_step,
+                    current_direction,
+                    invert_direction,
+                    pulse_length,
+                    convert,
+                    on_step,
+                    move_ticks,
+                    rotary_wrap,
+                    timing,
+                    max_transitions,
+                )
+            },
+        );
+
+        #[cfg(feature = "motion-stats")]
+        self.stats.record(
+            ticks_before,
+            self.move_ticks,
+            direction_before,
+            self.current_direction,
+        );
+
+        let still_moving = result?;
+
+        if self.in_motion && !still_moving {
+            // The motion profile has run out of steps either way. Only the
+            // distance tells whether it had any to begin with.
+            self.in_motion = false;
+            self.outcome = Some(if self.zero_distance {
+                MotionOutcome::NoOp
+            } else {
+                MotionOutcome::Complete
+            });
+        }
+
+        if let Some(timeout) = self.timeout {
+            if still_moving && !self.rotating {
+                self.move_polls = self.move_polls.saturating_add(1);
+                if self.move_polls > timeout {
+                    self.abort()?;
+                    return Err(Error::Timeout);
+                }
+            }
+        }
+
+        Ok(still_moving)
+    }
+}
+
+// We could also implement the various "enable" traits here, but those
+// implementations can only work while we have access to the driver, which
+// mostly means we'd have to be idle. Since the "enable" traits are infallible,
+// we'd have to panic, and I don't know if that would be worth it.
+
+impl<Driver, Timer, Profile, Convert, Counter, Hook, Ticks> SetEnable
+    for SoftwareMotionControl<
+        Driver,
+        Timer,
+        Profile,
+        Convert,
+        Counter,
+        Hook,
+        Ticks,
+    >
+where
+    Counter: StepCounter,
+    Driver: SetEnable,
+    Profile: MotionProfile,
+{
+    const HOLD_TIME: Nanoseconds = Driver::HOLD_TIME;
+
+    type Error = BusyError<Driver::Error>;
+
+    fn enable(&mut self) -> Result<(), Self::Error> {
+        match self.driver_mut() {
+            Some(driver) => driver.enable().map_err(BusyError::Other),
+            None => Err(BusyError::Busy),
+        }
+    }
+
+    fn disable(&mut self) -> Result<(), Self::Error> {
+        match self.driver_mut() {
+            Some(driver) => driver.disable().map_err(BusyError::Other),
+            None => Err(BusyError::Busy),
+        }
+    }
+
+    /// Indicate whether the driver is enabled
+    ///
+    /// Unlike the other methods of this trait, this also works during a
+    /// motion. Returns `None`, if the internal state is invalid (see
+    /// [`Self::reset`]).
+    fn is_enabled(&self) -> Option<bool> {
+        self.state.driver().and_then(|driver| driver.is_enabled())
+    }
+}
+
+impl<Driver, Timer, Profile, Convert, Counter, Hook, Ticks> Capabilities
+    for SoftwareMotionControl<
+        Driver,
+        Timer,
+        Profile,
+        Convert,
+        Counter,
+        Hook,
+        Ticks,
+    >
+where
+    Driver: Capabilities,
+    Profile: MotionProfile,
+{
+    const SUPPORTS_STEP_MODE_CONTROL: bool = Driver::SUPPORTS_STEP_MODE_CONTROL;
+    const SUPPORTS_DRIVER_CONTROL: bool = Driver::SUPPORTS_DRIVER_CONTROL;
+    const SUPPORTS_CHOPPER_MODE: bool = Driver::SUPPORTS_CHOPPER_MODE;
+    const SUPPORTS_CURRENT_CONTROL: bool = Driver::SUPPORTS_CURRENT_CONTROL;
+    const SUPPORTS_STALL_DETECTION: bool = Driver::SUPPORTS_STALL_DETECTION;
+    const SUPPORTS_UART_CONFIG: bool = Driver::SUPPORTS_UART_CONFIG;
+    const SUPPORTS_SPI_CONFIG: bool = Driver::SUPPORTS_SPI_CONFIG;
+}
+
+impl<Driver, Timer, Profile, Convert, Counter, Hook, Ticks> ChopperMode
+    for SoftwareMotionControl<
+        Driver,
+        Timer,
+        Profile,
+        Convert,
+        Counter,
+        Hook,
+        Ticks,
+    >
+where
+    Counter: StepCounter,
+    Driver: ChopperMode,
+    Profile: MotionProfile,
+{
+    type Error = BusyError<Driver::Error>;
+
+    fn set_quiet(&mut self, quiet: bool) -> Result<(), Self::Error> {
+        match self.driver_mut() {
+            Some(driver) => driver.set_quiet(quiet).map_err(BusyError::Other),
+            None => Err(BusyError::Busy),
+        }
+    }
+}
+
+impl<Driver, Timer, Profile, Convert, Counter, Hook, Ticks> StallDetection
+    for SoftwareMotionControl<
+        Driver,
+        Timer,
+        Profile,
+        Convert,
+        Counter,
+        Hook,
+        Ticks,
+    >
+where
+    Counter: StepCounter,
+    Driver: StallDetection,
+    Profile: MotionProfile,
+{
+    type Diag = Driver::Diag;
+    type Error = BusyError<Driver::Error>;
+
+    /// Provides access to the DIAG pin of the wrapped driver
+    ///
+    /// Unlike most other methods that access the wrapped driver, this also
+    /// works during a motion, which is when a stall is going to happen. It
+    /// only touches the DIAG pin, not the STEP and DIR pins used by the motion.
+    ///
+    /// Returns [`BusyError::Busy`], if the internal state is invalid (see
+    /// [`Self::reset`]).
+    fn diag(&mut self) -> Result<&mut Self::Diag, Self::Error> {
+        match self.state.driver_mut() {
+            Some(driver) => driver.diag().map_err(BusyError::Other),
+            None => Err(BusyError::Busy),
+        }
+    }
+
+    /// Indicates whether the wrapped driver has detected a stall
+    ///
+    /// Works during a motion, like [`Self::diag`].
+    fn is_stalled(&mut self) -> Result<bool, Self::Error> {
+        match self.state.driver_mut() {
+            Some(driver) => driver.is_stalled().map_err(BusyError::Other),
+            None => Err(BusyError::Busy),
+        }
+    }
+}
+
+impl<Driver, Timer, Profile, Convert, Counter, Hook, Ticks> SetStepMode
+    for SoftwareMotionControl<
+        Driver,
+        Timer,
+        Profile,
+        Convert,
+        Counter,
+        Hook,
+        Ticks,
+    >
+where
+    Counter: StepCounter,
+    Driver: SetStepMode,
+    Profile: MotionProfile,
+{
+    const SETUP_TIME: Nanoseconds = Driver::SETUP_TIME;
+    const HOLD_TIME: Nanoseconds = Driver::HOLD_TIME;
+    const RESET_TIME: Nanoseconds = Driver::RESET_TIME;
+    const MODE_PIN_SETTLE_TIME: Nanoseconds = Driver::MODE_PIN_SETTLE_TIME;
+
+    type Error = BusyError<Driver::Error>;
+    type StepMode = Driver::StepMode;
+
+    /// Apply the step mode configuration to the wrapped driver
+    ///
+    /// Returns [`BusyError::Busy`], if a motion is ongoing, pending, queued, or
+    /// paused. See [`SoftwareMotionControl::set_step_mode`].
+    fn apply_mode_config(
+        &mut self,
+        step_mode: Self::StepMode,
+    ) -> Result<(), Self::Error> {
+        // See `SoftwareMotionControl::set_step_mode`.
+        if !self.is_idle() || self.paused.is_some() {
+            return Err(BusyError::Busy);
+        }
+
+        match self.driver_mut() {
+            Some(driver) => driver
+                .apply_mode_config(step_mode)
+                .map_err(BusyError::Other),
+            None => Err(BusyError::Busy),
+        }
+    }
+
+    /// Apply one mode pin of the step mode configuration to the wrapped driver
+    ///
+    /// Returns [`BusyError::Busy`] under the same conditions as
+    /// [`SetStepMode::apply_mode_config`].
+    fn apply_mode_pin(
+        &mut self,
+        step_mode: Self::StepMode,
+        index: usize,
+    ) -> Result<bool, Self::Error> {
+        if !self.is_idle() || self.paused.is_some() {
+            return Err(BusyError::Busy);
+        }
+
+        match self.driver_mut() {
+            Some(driver) => driver
+                .apply_mode_pin(step_mode, index)
+                .map_err(BusyError::Other),
+            None => Err(BusyError::Busy),
+        }
+    }
+
+    fn enable_driver(&mut self) -> Result<(), Self::Error> {
+        match self.driver_mut() {
+            Some(driver) => driver.enable_driver().map_err(BusyError::Other),
+            None => Err(BusyError::Busy),
+        }
+    }
+
+    /// Returns the step mode that was last applied to the wrapped driver
+    ///
+    /// Unlike the other methods of this trait, this also works during a
+    /// motion. Returns `None`, if the internal state is invalid (see
+    /// [`Self::reset`]).
+    fn step_mode(&self) -> Option<Self::StepMode> {
+        self.state.driver().and_then(|driver| driver.step_mode())
+    }
+}
+
+impl<Driver, Timer, Profile, Convert, Counter, Hook, Ticks> SetDirection
+    for SoftwareMotionControl<
+        Driver,
+        Timer,
+        Profile,
+        Convert,
+        Counter,
+        Hook,
+        Ticks,
+    >
+where
+    Counter: StepCounter,
+    Driver: SetDirection,
+    Profile: MotionProfile,
+{
+    const SETUP_TIME: Nanoseconds = Driver::SETUP_TIME;
+
+    type Dir = Driver::Dir;
+    type Error = BusyError<Driver::Error>;
+
+    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+        match self.driver_mut() {
+            Some(driver) => driver.dir().map_err(BusyError::Other),
+            None => Err(BusyError::Busy),
+        }
+    }
+
+    /// Returns the setup time of the wrapped driver
+    ///
+    /// Unlike [`SetDirection::dir`], this also works during a motion.
+    ///
+    /// Returns [`SetDirection::SETUP_TIME`], if the internal state is invalid (see
+    /// [`Self::reset`]).
+    fn setup_time(&self) -> Nanoseconds {
+        self.state
+            .driver()
+            .map_or(Driver::SETUP_TIME, |driver| driver.setup_time())
+    }
+
+    /// Returns the DIR polarity of the wrapped driver
+    ///
+    /// Unlike [`SetDirection::dir`], this also works during a motion.
+    ///
+    /// Returns [`PinState::High`], like the trait's default, if the internal state is invalid (see
+    /// [`Self::reset`]).
+    fn dir_polarity(&self) -> PinState {
+        self.state
+            .driver()
+            .map_or(PinState::High, |driver| driver.dir_polarity())
+    }
+}
+
+impl<Driver, Timer, Profile, Convert, Counter, Hook, Ticks> Step
+    for SoftwareMotionControl<
+        Driver,
+        Timer,
+        Profile,
+        Convert,
+        Counter,
+        Hook,
+        Ticks,
+    >
+where
+    Counter: StepCounter,
+    Driver: Step,
+    Profile: MotionProfile,
+{
+    const PULSE_LENGTH: Nanoseconds = Driver::PULSE_LENGTH;
+    const PULSE_POLARITY: PinState = Driver::PULSE_POLARITY;
+    const LOW_TIME: Nanoseconds = Driver::LOW_TIME;
+
+    type Step = Driver::Step;
+    type Error = BusyError<Driver::Error>;
+
+    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+        match self.driver_mut() {
+            Some(driver) => driver.step().map_err(BusyError::Other),
+            None => Err(BusyError::Busy),
+        }
+    }
+
+    /// Returns the pulse polarity of the wrapped driver
+    ///
+    /// Unlike [`Step::step`], this also works during a motion.
+    ///
+    /// Returns [`Step::PULSE_POLARITY`], if the internal state is invalid (see
+    /// [`Self::reset`]).
+    fn pulse_polarity(&self) -> PinState {
+        self.state
+            .driver()
+            .map_or(Driver::PULSE_POLARITY, |driver| driver.pulse_polarity())
+    }
+
+    /// Returns the pulse length of the wrapped driver
+    ///
+    /// This ignores any override set using [`Self::set_pulse_length`]. Unlike
+    /// [`Step::step`], this also works during a motion.
+    ///
+    /// Returns [`Step::PULSE_LENGTH`], if the internal state is invalid (see
+    /// [`Self::reset`]).
+    fn pulse_length(&self) -> Nanoseconds {
+        self.state
+            .driver()
+            .map_or(Driver::PULSE_LENGTH, |driver| driver.pulse_length())
+    }
+
+    /// Returns the low time of the wrapped driver
+    ///
+    /// Unlike [`Step::step`], this also works during a motion.
+    ///
+    /// Returns [`Step::LOW_TIME`], if the internal state is invalid (see
+    /// [`Self::reset`]).
+    fn low_time(&self) -> Nanoseconds {
+        self.state
+            .driver()
+            .map_or(Driver::LOW_TIME, |driver| driver.low_time())
+    }
+}
+
+// Blanket implementation of `EnableMotionControl` for all STEP/DIR stepper
+// drivers.
+impl<Driver, Timer, Profile, Convert>
+    EnableMotionControl<(Timer, Profile, Convert)> for Driver
+where
+    Driver: SetDirection + Step,
+    Profile: MotionProfile,
+    Timer: timer::CountDown,
+    Profile::Velocity: Copy,
+    Convert: DelayToTicks<Profile::Delay, Ticks = Timer::Time>,
+    Convert::Ticks: TryFrom<Nanoseconds>
+        + ops::Add<Output = Convert::Ticks>
+        + ops::Sub<Output = Convert::Ticks>
+        + PartialOrd
+        + Copy,
+{
+    type WithMotionControl =
+        SoftwareMotionControl<Driver, Timer, Profile, Convert>;
+
+    fn enable_motion_control(
+        self,
+        (timer, profile, convert): (Timer, Profile, Convert),
+    ) -> Self::WithMotionControl {
+        SoftwareMotionControl::new(self, timer, profile, convert)
+    }
+}
+
+/// A motion that has been paused by [`SoftwareMotionControl::pause`]
+enum Paused<Velocity, Counter> {
+    Move {
+        max_velocity: Velocity,
+        target_step: Counter,
+    },
+    Rotation {
+        velocity: Velocity,
+        direction: Direction,
+    },
+}
+
+/// The direction steps are counted in, while the DIR pin is at `level`
+///
+/// Steps are counted in the direction that was requested, before the signal
+/// is inverted (see [`SoftwareMotionControl::set_invert_direction`]).
+fn direction_of_dir_level<Driver: SetDirection>(
+    driver: &Driver,
+    level: PinState,
+    invert_direction: bool,
+) -> Direction {
+    let signal = if level == driver.dir_polarity() {
+        Direction::Forward
+    } else {
+        Direction::Backward
+    };
+
+    if invert_direction {
+        signal.opposite()
+    } else {
+        signal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use embedded_hal::digital::PinState::{High, Low};
+    use embedded_time::duration::Nanoseconds;
+    use ramp_maker::{Flat, Trapezoidal};
+
+    use crate::{
+        compat::Ticks,
+        step_mode::StepMode256,
+        test_util::{
+            mock_motion_control, mock_motion_control_with, BusyMockTimer,
+            Event, Log, MockDelayToTicks, MockDriver, MockMotionControl,
+            MockPin, MockTimer, Num, NumToTicks,
+        },
+        traits::{MotionControl as _, SetDirection, Step},
+        Direction,
+    };
+
+    use super::{
+        profile_delays, state::State, BusyError, Error, MotionOutcome,
+        SoftwareMotionControl,
+    };
+
+    fn run(motion_control: &mut MockMotionControl) {
+        while motion_control.update().unwrap() {}
+    }
+
+    #[test]
+    fn update_should_share_timer_between_direction_setup_and_steps() {
+        let log = Log::new();
+        // The timer takes a few polls to finish, so every state that uses it
+        // has to hand it over while it's still running.
+        let mut motion_control = mock_motion_control_with(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(1),
+        );
+
+        motion_control.move_to_position(0.01, 2).unwrap();
+        for _ in 0..3 {
+            assert!(motion_control.update().unwrap());
+        }
+        // The first step has been made and its delay is running. Change
+        // direction in the middle of that.
+        motion_control.move_to_position(0.01, -1).unwrap();
+        while motion_control.update().unwrap() {}
+
+        assert_eq!(motion_control.current_step(), -1);
+        assert_eq!(log.count(Event::Pin("step", High)), 3);
+        assert_eq!(log.count(Event::Pin("dir", Low)), 1);
+
+        // The timer is never started again before it has finished, and no pin
+        // changes while it's running.
+        let mut running = false;
+        for event in log.events() {
+            match event {
+                Event::TimerStart(_) => {
+                    assert!(!running, "Timer restarted: {:?}", log.events());
+                    running = true;
+                }
+                Event::TimerFinished => running = false,
+                _ => assert!(!running, "Pin changed: {:?}", log.events()),
+            }
+        }
+        assert!(!running);
+    }
+
+    #[test]
+    fn update_should_retry_starting_step_delay() {
+        let log = Log::new();
+        // Starts are: DIR setup time, STEP pulse, step delay.
+        let mut motion_control = mock_motion_control_with(
+            MockDriver::new(&log),
+            BusyMockTimer::new(&log, 2),
+        );
+
+        motion_control.move_to_position(0.01, 2).unwrap();
+        let result = loop {
+            match motion_control.update() {
+                Ok(true) => continue,
+                result => break result,
+            }
+        };
+        assert!(matches!(result, Err(Error::StepDelay(()))));
+        assert_eq!(motion_control.current_step(), 1);
+
+        while motion_control.update().unwrap() {}
+
+        // The step isn't repeated, and the delay isn't skipped.
+        assert_eq!(motion_control.current_step(), 2);
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("dir", High),
+                Event::TimerStart(1),
+                Event::TimerFinished,
+                Event::Pin("step", High),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+                Event::TimerStart(98),
+                Event::TimerFinished,
+                Event::Pin("step", High),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+                Event::TimerStart(98),
+                Event::TimerFinished,
+            ]
+        );
+    }
+
+    #[test]
+    fn safe_state_should_force_pins_low_and_return_to_idle() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control_with(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(3),
+        );
+
+        motion_control.move_to_position(0.01, 5).unwrap();
+        while !matches!(motion_control.state, State::Step { .. }) {
+            assert!(motion_control.update().unwrap());
+        }
+        assert_eq!(log.events().last(), Some(&Event::TimerStart(2)));
+
+        log.clear();
+        motion_control.safe_state().unwrap();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("step", Low),
+                Event::Pin("dir", Low),
+                Event::TimerCanceled,
+            ]
+        );
+        assert!(matches!(motion_control.state, State::Idle { .. }));
+        assert!(motion_control.is_idle());
+        assert_eq!(motion_control.take_outcome(), Some(MotionOutcome::Aborted));
+        assert_eq!(motion_control.current_step(), 0);
+        assert_eq!(motion_control.current_direction(), Direction::Backward);
+
+        // Nothing is left to do.
+        assert!(!motion_control.update().unwrap());
+        assert_eq!(log.events().len(), 3);
+    }
+
+    #[test]
+    fn reset_position_should_count_subsequent_steps_from_new_position() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 5).unwrap();
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), 5);
+
+        motion_control.reset_position(0).unwrap();
+        assert_eq!(motion_control.current_step(), 0);
+
+        log.clear();
+        motion_control.move_to_position(0.01, 3).unwrap();
+        run(&mut motion_control);
+
+        assert_eq!(log.count(Event::Pin("step", High)), 3);
+        assert_eq!(motion_control.current_step(), 3);
+    }
+
+    #[test]
+    fn reset_position_should_handle_extremes() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, -5).unwrap();
+        run(&mut motion_control);
+
+        motion_control.reset_position(i32::MAX).unwrap();
+        assert_eq!(motion_control.current_step(), i32::MAX);
+        assert_eq!(motion_control.remaining_steps().unwrap(), Some(0));
+
+        motion_control.move_to_position(0.01, i32::MAX - 2).unwrap();
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), i32::MAX - 2);
+
+        motion_control.reset_position(i32::MIN).unwrap();
+        assert_eq!(motion_control.current_step(), i32::MIN);
+        assert_eq!(motion_control.remaining_steps().unwrap(), Some(0));
+    }
+
+    #[test]
+    fn reset_position_should_be_rejected_during_motion() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, -5).unwrap();
+        assert!(matches!(
+            motion_control.reset_position(i32::MAX),
+            Err(Error::Busy)
+        ));
+
+        assert!(motion_control.update().unwrap());
+        assert!(motion_control.update().unwrap());
+        motion_control.pause().unwrap();
+        assert!(matches!(
+            motion_control.reset_position(i32::MAX),
+            Err(Error::Busy)
+        ));
+
+        motion_control.resume().unwrap();
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), -5);
+    }
+
+    #[test]
+    fn update_should_fail_if_delay_is_shorter_than_step_pulse() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        // A velocity of 1 step per tick results in a delay of 1 tick, which is
+        // shorter than the 2 ticks of the mock driver's STEP pulse.
+        motion_control.move_to_position(1.0, 5).unwrap();
+
+        let result = loop {
+            match motion_control.update() {
+                Ok(true) => continue,
+                result => break result,
+            }
+        };
+        assert!(matches!(result, Err(Error::DelayTooShort)));
+    }
+
+    #[test]
+    fn update_should_fail_if_timer_is_too_coarse_for_speed() {
+        let log = Log::new();
+
+        // At 500 kHz, the mock driver's STEP pulse is 1 tick long.
+        let mut motion_control = SoftwareMotionControl::new(
+            MockDriver::new(&log),
+            MockTimer::<500_000>::with_frequency(&log),
+            Flat::new(),
+            MockDelayToTicks::<500_000>,
+        );
+
+        // A velocity of 1 step per tick results in a delay of 1 tick, which
+        // leaves no time between the STEP pulses.
+        motion_control.move_to_position(1.0, 5).unwrap();
+
+        let result = loop {
+            match motion_control.update() {
+                Ok(true) => continue,
+                result => break result,
+            }
+        };
+        assert!(matches!(result, Err(Error::SpeedExceedsTimerResolution)));
+        assert_eq!(motion_control.current_step(), 1);
+    }
+
+    #[test]
+    fn update_should_fail_if_delay_is_zero() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        // An infinite velocity results in a delay of zero.
+        motion_control.move_to_position(f32::INFINITY, 5).unwrap();
+
+        let result = loop {
+            match motion_control.update() {
+                Ok(true) => continue,
+                result => break result,
+            }
+        };
+        assert!(matches!(result, Err(Error::ZeroDelay)));
+
+        // No step delay has been started.
+        assert_eq!(
+            log.events().last(),
+            Some(&Event::Pin("step", Low)),
+            "{:?}",
+            log.events()
+        );
+    }
+
+    #[test]
+    fn abort_should_stop_motion_and_drive_step_pin_low() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 5).unwrap();
+
+        // Set direction, then start the first step. The STEP pin is now high.
+        assert!(motion_control.update().unwrap());
+        assert!(motion_control.update().unwrap());
+        assert_eq!(log.count(Event::Pin("step", High)), 1);
+        assert_eq!(log.count(Event::Pin("step", Low)), 0);
+
+        motion_control.abort().unwrap();
+        assert_eq!(log.count(Event::Pin("step", Low)), 1);
+
+        // The rest of the motion has been discarded.
+        assert!(!motion_control.update().unwrap());
+        assert_eq!(log.count(Event::Pin("step", High)), 1);
+        assert!(motion_control.driver().is_some());
+    }
+
+    #[test]
+    fn abort_should_not_count_interrupted_step() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control_with(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(3),
+        );
+
+        motion_control.move_to_position(0.01, 5).unwrap();
+
+        // Make the first step, then start the second one.
+        while log.count(Event::Pin("step", High)) < 2 {
+            assert!(motion_control.update().unwrap());
+        }
+        assert!(matches!(motion_control.state, State::Step { .. }));
+        assert_eq!(motion_control.current_step(), 1);
+
+        motion_control.abort().unwrap();
+        assert_eq!(log.events().last(), Some(&Event::Pin("step", Low)));
+        assert_eq!(motion_control.current_step(), 1);
+
+        assert!(!motion_control.update().unwrap());
+        assert_eq!(motion_control.current_step(), 1);
+    }
+
+    #[test]
+    fn update_bounded_should_not_release_driver_between_steps() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 2).unwrap();
+
+        // Idle -> SetDirection, poll the timer, then SetDirection -> Idle. The
+        // motion is parked in the idle state, but not over.
+        for _ in 0..3 {
+            assert!(motion_control.update_bounded(1).unwrap());
+        }
+        assert!(matches!(motion_control.state, State::Idle { .. }));
+
+        assert!(motion_control.driver_mut().is_none());
+        assert!(motion_control.timer_mut().is_none());
+        assert!(matches!(
+            motion_control.set_direction(Direction::Backward),
+            Err(BusyError::Busy)
+        ));
+        assert!(matches!(motion_control.step(), Err(BusyError::Busy)));
+
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), 2);
+        assert!(motion_control.driver_mut().is_some());
+    }
+
+    #[test]
+    fn update_bounded_should_stop_after_max_transitions() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 2).unwrap();
+
+        // Idle -> SetDirection. The direction isn't set until the next call.
+        assert!(motion_control.update_bounded(1).unwrap());
+        assert_eq!(log.events(), []);
+
+        // No transition, as the timer hasn't been polled yet.
+        assert!(motion_control.update_bounded(1).unwrap());
+        assert_eq!(
+            log.events(),
+            [Event::Pin("dir", High), Event::TimerStart(1)]
+        );
+        log.clear();
+
+        // The direction has been set. SetDirection -> Idle.
+        assert!(motion_control.update_bounded(1).unwrap());
+        assert_eq!(log.events(), [Event::TimerFinished]);
+        log.clear();
+
+        // Idle -> Step. The step isn't started until the next call.
+        assert!(motion_control.update_bounded(1).unwrap());
+        assert_eq!(log.events(), []);
+
+        assert!(motion_control.update_bounded(1).unwrap());
+        assert_eq!(
+            log.events(),
+            [Event::Pin("step", High), Event::TimerStart(2)]
+        );
+
+        // Without a limit, the rest of the motion completes as usual.
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), 2);
+        assert_eq!(log.count(Event::Pin("step", High)), 2);
+    }
+
+    #[test]
+    fn shutdown_should_complete_step_pulse_in_progress() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 5).unwrap();
+
+        // Set direction, then start the first step. The STEP pin is now high.
+        assert!(motion_control.update().unwrap());
+        assert!(motion_control.update().unwrap());
+        log.clear();
+
+        motion_control.shutdown().unwrap();
+
+        // The pulse has been waited out, not cut short, and was counted.
+        assert_eq!(
+            log.events(),
+            [
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+                Event::Pin("step", Low),
+            ]
+        );
+        assert_eq!(motion_control.current_step(), 1);
+        assert!(!motion_control.update().unwrap());
+        assert!(motion_control.driver().is_some());
+    }
+
+    #[test]
+    fn shutdown_should_only_drive_step_pin_low_if_idle() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.shutdown().unwrap();
+
+        assert_eq!(log.events(), [Event::Pin("step", Low)]);
+        assert_eq!(motion_control.current_step(), 0);
+    }
+
+    #[cfg(feature = "motion-stats")]
+    #[test]
+    fn shutdown_should_count_step_in_stats() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 5).unwrap();
+        assert!(motion_control.update().unwrap());
+        assert!(motion_control.update().unwrap());
+
+        motion_control.shutdown().unwrap();
+
+        // The step is tallied, like `update` would have, including its ticks.
+        let stats = motion_control.stats();
+        assert_eq!(stats.total_steps, 1);
+        assert_eq!(stats.total_ticks, Some(Ticks(100)));
+    }
+
+    #[test]
+    fn finish_should_complete_step_pulse_in_progress() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 5).unwrap();
+        assert!(motion_control.update().unwrap());
+        assert!(motion_control.update().unwrap());
+        log.clear();
+
+        motion_control.finish();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+                Event::Pin("step", Low),
+            ]
+        );
+        assert_eq!(motion_control.current_step(), 1);
+        assert!(!motion_control.update().unwrap());
+    }
+
+    #[test]
+    fn finish_should_abort_even_if_shutdown_fails() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        // The delay is too short for the STEP pulse, which is only detected
+        // once the pulse has ended. See
+        // `update_should_fail_if_delay_is_shorter_than_step_pulse`.
+        motion_control.move_to_position(1.0, 5).unwrap();
+        assert!(motion_control.update().unwrap());
+        assert!(motion_control.update().unwrap());
+        log.clear();
+
+        motion_control.finish();
+
+        // Shutting down failed after ending the pulse, and the motion was
+        // aborted anyway, leaving the STEP pin in its idle state.
+        assert_eq!(
+            log.events(),
+            [
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+                Event::Pin("step", Low),
+            ]
+        );
+        assert!(motion_control.is_idle());
+        assert!(!motion_control.update().unwrap());
+    }
+
+    #[test]
+    fn remaining_steps_should_decrease_with_each_step() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        assert_eq!(motion_control.remaining_steps().unwrap(), Some(0));
+
+        motion_control.move_to_position(0.01, 3).unwrap();
+        assert_eq!(motion_control.remaining_steps().unwrap(), Some(3));
+
+        let mut remaining_steps = [3, 2, 1, 0].iter();
+        let mut expected = remaining_steps.next();
+        let mut steps = log.count(Event::Pin("step", Low));
+
+        loop {
+            let running = motion_control.update().unwrap();
+
+            let steps_after_update = log.count(Event::Pin("step", Low));
+            if steps_after_update > steps {
+                expected = remaining_steps.next();
+                steps = steps_after_update;
+            }
+
+            assert_eq!(
+                motion_control.remaining_steps().unwrap(),
+                expected.copied()
+            );
+
+            if !running {
+                break;
+            }
+        }
+
+        assert_eq!(remaining_steps.next(), None);
+        assert_eq!(motion_control.remaining_steps().unwrap(), Some(0));
+    }
+
+    #[test]
+    fn rotate_should_keep_stepping_until_aborted() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.rotate(Direction::Backward, 0.01).unwrap();
+
+        while log.count(Event::Pin("step", Low)) < 1000 {
+            assert!(motion_control.update().unwrap());
+        }
+        assert_eq!(log.count(Event::Pin("dir", Low)), 1);
+        assert_eq!(motion_control.current_step(), -1000);
+        assert_eq!(motion_control.remaining_steps().unwrap(), None);
+
+        motion_control.abort().unwrap();
+        assert!(!motion_control.update().unwrap());
+        assert_eq!(motion_control.current_step(), -1000);
+    }
+
+    #[test]
+    fn set_max_speed_should_change_velocity_of_ongoing_motion() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        // 100 ticks per step, minus the 2 ticks of the STEP pulse.
+        motion_control.move_to_position(0.01, 6).unwrap();
+        while log.count(Event::TimerStart(98)) < 3 {
+            motion_control.update().unwrap();
+        }
+
+        // 50 ticks per step, minus the 2 ticks of the STEP pulse.
+        motion_control.set_max_speed(0.02).unwrap();
+        run(&mut motion_control);
+
+        // The delay for the 4th step had already been taken from the profile,
+        // when the velocity was changed.
+        assert_eq!(log.count(Event::TimerStart(98)), 4);
+        assert_eq!(log.count(Event::TimerStart(48)), 2);
+        assert_eq!(motion_control.current_step(), 6);
+    }
+
+    #[test]
+    fn set_max_speed_should_continue_the_current_ramp() {
+        let log = Log::new();
+        let mut motion_control = SoftwareMotionControl::new(
+            MockDriver::new(&log),
+            MockTimer::new(&log),
+            Trapezoidal::new(Num::from_num(0.000_000_1)),
+            NumToTicks,
+        );
+        let delays = || {
+            let delays: Vec<_> = log
+                .events()
+                .windows(2)
+                .filter_map(|events| match events {
+                    [Event::Pin("step", Low), Event::TimerStart(ticks)] => {
+                        Some(*ticks)
+                    }
+                    _ => None,
+                })
+                .collect();
+            log.clear();
+            delays
+        };
+
+        // Cruise at about 2000 ticks per step for a while.
+        motion_control
+            .move_to_position(Num::from_num(0.000_5), 40)
+            .unwrap();
+        while motion_control.current_step() < 10 {
+            motion_control.update().unwrap();
+        }
+        let before = delays();
+        let cruise = *before.last().unwrap();
+        assert!(before.iter().all(|&delay| delay == cruise), "{:?}", before);
+
+        motion_control.set_max_speed(Num::from_num(0.001)).unwrap();
+        while motion_control.update().unwrap() {}
+        let after = delays();
+
+        // The motor accelerates from the velocity it already had, instead of
+        // starting a new ramp from standstill, which would begin with delays
+        // longer than the cruising delay (about 2236 ticks).
+        let new_cruise = *after.iter().min().unwrap();
+        let accel: Vec<_> = after
+            .iter()
+            .copied()
+            .take_while(|&delay| delay != new_cruise)
+            .collect();
+        assert!(accel.len() < after.len(), "{:?}", after);
+        assert!(accel.iter().all(|&delay| delay <= cruise), "{:?}", after);
+        assert!(accel.windows(2).all(|w| w[0] >= w[1]), "{:?}", after);
+        assert_eq!(motion_control.current_step(), 40);
+    }
+
+    #[test]
+    fn reverse_should_flip_direction_of_subsequent_steps() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 10).unwrap();
+        while motion_control.current_step() < 4 {
+            motion_control.update().unwrap();
+        }
+        assert_eq!(motion_control.current_direction(), Direction::Forward);
+
+        motion_control.reverse().unwrap();
+
+        let mut previous_step = motion_control.current_step();
+        let mut reversed = false;
+        while motion_control.update().unwrap() {
+            let current_step = motion_control.current_step();
+            if current_step < previous_step {
+                reversed = true;
+            }
+            if reversed {
+                assert!(current_step <= previous_step);
+            }
+            previous_step = current_step;
+        }
+
+        assert!(reversed);
+        assert_eq!(motion_control.current_direction(), Direction::Backward);
+        assert_eq!(log.count(Event::Pin("dir", Low)), 1);
+
+        // The step in progress when reversing was completed, then the
+        // remaining 5 steps were made backwards.
+        assert_eq!(motion_control.current_step(), 0);
+    }
+
+    #[test]
+    fn invert_direction_should_only_invert_dir_signal() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+        motion_control.set_invert_direction(true);
+        assert!(motion_control.is_direction_inverted());
+
+        motion_control.move_to_position(0.01, 3).unwrap();
+        run(&mut motion_control);
+
+        assert_eq!(log.events()[0], Event::Pin("dir", Low));
+        assert_eq!(log.count(Event::Pin("dir", High)), 0);
+        assert_eq!(motion_control.current_direction(), Direction::Forward);
+        assert_eq!(motion_control.current_step(), 3);
+
+        log.clear();
+        motion_control.move_to_position(0.01, 1).unwrap();
+        run(&mut motion_control);
+
+        assert_eq!(log.events()[0], Event::Pin("dir", High));
+        assert_eq!(motion_control.current_direction(), Direction::Backward);
+        assert_eq!(motion_control.current_step(), 1);
+    }
+
+    #[test]
+    fn set_pulse_length_should_override_driver_pulse_length() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+        motion_control.set_pulse_length(Some(Nanoseconds(5_000)));
+
+        motion_control.move_to_position(0.01, 1).unwrap();
+        run(&mut motion_control);
+
+        // The mock driver's pulse length would be 2 ticks. The step delay of
+        // 100 ticks is shortened by the length of the pulse.
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("dir", High),
+                Event::TimerStart(1),
+                Event::TimerFinished,
+                Event::Pin("step", High),
+                Event::TimerStart(5),
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+                Event::TimerStart(95),
+                Event::TimerFinished,
+            ]
+        );
+    }
+
+    #[test]
+    fn update_should_wait_for_direction_setup_time_before_stepping() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control_with(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(2),
+        );
+
+        motion_control.move_to_position(0.01, 1).unwrap();
+
+        // Set direction and start waiting for the setup time. The timer is
+        // still running after each of these updates.
+        for _ in 0..3 {
+            assert!(motion_control.update().unwrap());
+            assert_eq!(log.count(Event::Pin("step", High)), 0);
+        }
+
+        run(&mut motion_control);
+
+        assert_eq!(
+            log.events()[..4],
+            [
+                Event::Pin("dir", High),
+                // `SETUP_TIME` of the mock driver at 1 MHz.
+                Event::TimerStart(1),
+                Event::TimerFinished,
+                Event::Pin("step", High),
+            ]
+        );
+    }
+
+    #[test]
+    fn release_should_only_succeed_while_idle() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 2).unwrap();
+        motion_control.update().unwrap();
+
+        let mut motion_control = match motion_control.release() {
+            Ok(_) => panic!("Released resources during motion"),
+            Err(motion_control) => motion_control,
+        };
+
+        // Between two steps of the motion, the state is idle too.
+        while !matches!(motion_control.state, State::Idle { .. }) {
+            motion_control.update_bounded(1).unwrap();
+        }
+        let mut motion_control = match motion_control.release() {
+            Ok(_) => panic!("Released resources between steps"),
+            Err(motion_control) => motion_control,
+        };
+
+        run(&mut motion_control);
+
+        // A pending motion would be lost.
+        motion_control.move_to_position(0.01, 3).unwrap();
+        let mut motion_control = match motion_control.release() {
+            Ok(_) => panic!("Released resources with pending motion"),
+            Err(motion_control) => motion_control,
+        };
+
+        // So would a paused one.
+        motion_control.update().unwrap();
+        motion_control.pause().unwrap();
+        let mut motion_control = match motion_control.release() {
+            Ok(_) => panic!("Released resources with paused motion"),
+            Err(motion_control) => motion_control,
+        };
+
+        motion_control.resume().unwrap();
+        run(&mut motion_control);
+        assert!(motion_control.release().is_ok());
+    }
+
+    #[test]
+    fn i32_step_counter_should_wrap_around_on_overflow() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.reset_position(i32::MAX - 2).unwrap();
+        motion_control.rotate(Direction::Forward, 0.01).unwrap();
+        while log.count(Event::Pin("step", Low)) < 5 {
+            motion_control.update().unwrap();
+        }
+
+        assert_eq!(motion_control.current_step(), i32::MIN + 2);
+    }
+
+    #[test]
+    fn reverse_should_mirror_target_beyond_i32_max() {
+        let log = Log::new();
+        let mut motion_control: SoftwareMotionControl<_, _, _, _, i64> =
+            SoftwareMotionControl::with_step_counter(
+                MockDriver::new(&log),
+                MockTimer::new(&log),
+                Flat::<f32>::new(),
+                MockDelayToTicks,
+            );
+
+        motion_control.reset_position(i32::MAX - 2).unwrap();
+        motion_control.rotate(Direction::Forward, 0.01).unwrap();
+        while log.count(Event::Pin("step", Low)) < 5 {
+            motion_control.update().unwrap();
+        }
+        // The 6th step has already been started, so the position is
+        // `i32::MAX + 4`. Mirroring the target at that position puts it beyond
+        // `i32::MAX` too.
+        assert_eq!(motion_control.position(), i32::MAX as i64 + 4);
+        motion_control.move_to_position(0.01, i32::MAX).unwrap();
+        motion_control.reverse().unwrap();
+        while motion_control.update().unwrap() {}
+
+        assert_eq!(motion_control.current_step(), i32::MAX as i64 + 8);
+    }
+
+    #[test]
+    fn i64_step_counter_should_count_past_i32_max() {
+        let log = Log::new();
+        let mut motion_control: SoftwareMotionControl<_, _, _, _, i64> =
+            SoftwareMotionControl::with_step_counter(
+                MockDriver::new(&log),
+                MockTimer::new(&log),
+                Flat::<f32>::new(),
+                MockDelayToTicks,
+            );
+
+        motion_control.reset_position(i32::MAX - 2).unwrap();
+        motion_control.rotate(Direction::Forward, 0.01).unwrap();
+        while log.count(Event::Pin("step", Low)) < 5 {
+            motion_control.update().unwrap();
+        }
+
+        assert_eq!(motion_control.current_step(), i32::MAX as i64 + 3);
+
+        // The generic API is limited to `i32`.
+        assert_eq!(
+            crate::traits::MotionControl::current_step(&mut motion_control)
+                .unwrap(),
+            i32::MIN + 2
+        );
+    }
+
+    #[test]
+    fn set_step_mode_should_be_rejected_during_motion() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control_with(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(1),
+        );
+
+        // Pending, but not picked up by `update` yet.
+        motion_control.move_to_position(0.01, 2).unwrap();
+        assert!(matches!(
+            motion_control.set_step_mode(StepMode256::M2),
+            Err(BusyError::Busy)
+        ));
+
+        while !matches!(motion_control.state, State::Step { .. }) {
+            motion_control.update().unwrap();
+        }
+        assert!(matches!(
+            motion_control.set_step_mode(StepMode256::M2),
+            Err(BusyError::Busy)
+        ));
+        assert_eq!(
+            crate::traits::SetStepMode::apply_mode_config(
+                &mut motion_control,
+                StepMode256::M2
+            ),
+            Err(BusyError::Busy)
+        );
+
+        run(&mut motion_control);
+        assert_eq!(log.count(Event::ApplyModeConfig(2)), 0);
+
+        motion_control
+            .set_step_mode(StepMode256::M2)
+            .unwrap()
+            .wait()
+            .unwrap();
+        assert_eq!(log.count(Event::ApplyModeConfig(2)), 1);
+    }
+
+    #[test]
+    fn set_step_mode_should_be_rejected_while_paused() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 5).unwrap();
+        assert!(motion_control.update().unwrap());
+        assert!(motion_control.update().unwrap());
+        motion_control.pause().unwrap();
+        assert!(motion_control.is_idle());
+
+        // Resuming would finish the move with the new step mode.
+        assert!(matches!(
+            motion_control.set_step_mode(StepMode256::M2),
+            Err(BusyError::Busy)
+        ));
+        assert_eq!(
+            crate::traits::SetStepMode::apply_mode_config(
+                &mut motion_control,
+                StepMode256::M2
+            ),
+            Err(BusyError::Busy)
+        );
+        assert_eq!(log.count(Event::ApplyModeConfig(2)), 0);
+    }
+
+    #[test]
+    fn set_step_mode_should_be_rejected_with_queued_moves() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 2).unwrap();
+        motion_control.queue_move(0.01, 4).unwrap();
+
+        // Park the motion in the idle state, between two steps.
+        for _ in 0..3 {
+            assert!(motion_control.update_bounded(1).unwrap());
+        }
+        assert!(matches!(motion_control.state, State::Idle { .. }));
+
+        assert!(matches!(
+            motion_control.set_step_mode(StepMode256::M2),
+            Err(BusyError::Busy)
+        ));
+        assert_eq!(
+            crate::traits::SetStepMode::apply_mode_pin(
+                &mut motion_control,
+                StepMode256::M2,
+                0
+            ),
+            Err(BusyError::Busy)
+        );
+
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), 4);
+        assert_eq!(log.count(Event::ApplyModeConfig(2)), 0);
+    }
+
+    #[test]
+    fn is_idle_and_is_moving_should_reflect_state() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control_with(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(1),
+        );
+
+        assert!(motion_control.is_idle());
+        assert!(!motion_control.is_moving());
+
+        // The motion is pending, before it's picked up by `update`.
+        motion_control.move_to_position(0.01, 1).unwrap();
+        assert!(!motion_control.is_idle());
+        assert!(motion_control.is_moving());
+
+        let mut set_direction = false;
+        let mut step = false;
+        let mut step_delay = false;
+        while motion_control.update().unwrap() {
+            match motion_control.state {
+                State::SetDirection(_) => set_direction = true,
+                State::Step { .. } => step = true,
+                State::StartStepDelay { .. } | State::StepDelay { .. } => {
+                    step_delay = true
+                }
+                State::Idle { .. } | State::Invalid => {
+                    panic!("Unexpected state during motion")
+                }
+            }
+
+            assert!(!motion_control.is_idle());
+            assert!(motion_control.is_moving());
+        }
+        assert!(set_direction && step && step_delay);
+
+        assert!(matches!(motion_control.state, State::Idle { .. }));
+        assert!(motion_control.is_idle());
+        assert!(!motion_control.is_moving());
+    }
+
+    #[test]
+    fn getters_should_not_panic_on_invalid_state() {
+        let mut motion_control = mock_motion_control(&Log::new());
+        motion_control.state = State::Invalid;
+
+        assert!(!motion_control.is_idle());
+        assert_eq!(motion_control.pulse_polarity(), MockDriver::PULSE_POLARITY);
+        assert_eq!(motion_control.pulse_length(), MockDriver::PULSE_LENGTH);
+        assert!(matches!(motion_control.update(), Err(Error::InvalidState)));
+    }
+
+    #[test]
+    fn on_step_should_be_called_after_every_step() {
+        let log = Log::new();
+        let mut steps = Vec::new();
+
+        let mut motion_control =
+            mock_motion_control(&log).with_on_step(|step, direction| {
+                steps.push((step, direction));
+            });
+
+        motion_control.move_to_position(0.01, -2).unwrap();
+        while motion_control.update().unwrap() {}
+        drop(motion_control);
+
+        assert_eq!(
+            steps,
+            [(-1, Direction::Backward), (-2, Direction::Backward)]
+        );
+    }
+
+    #[test]
+    fn rotary_wrap_should_wrap_position_in_both_directions() {
+        let log = Log::new();
+        let mut positions = Vec::new();
+        let mut motion_control = mock_motion_control(&log)
+            .with_on_step(|step: i32, _| positions.push(step));
+        motion_control.set_rotary_wrap(Some(10));
+
+        // Crosses the wrapping point going forward. The target wraps along with
+        // the position.
+        motion_control.move_to_position(0.01, 12).unwrap();
+        while motion_control.update().unwrap() {}
+        assert_eq!(motion_control.current_step(), 2);
+
+        // And going backward.
+        motion_control.move_to_position(0.01, -1).unwrap();
+        while motion_control.update().unwrap() {}
+        assert_eq!(motion_control.current_step(), 9);
+        assert_eq!(motion_control.remaining_steps().unwrap(), Some(0));
+
+        drop(motion_control);
+        assert_eq!(positions, [1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 2, 1, 0, 9]);
+    }
+
+    #[test]
+    fn reset_should_stop_motion_and_drive_pins_low() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 5).unwrap();
+
+        // Set direction, then start the first step. Both pins are now high.
+        motion_control.update().unwrap();
+        motion_control.update().unwrap();
+        assert_eq!(log.count(Event::Pin("step", High)), 1);
+        assert_eq!(log.count(Event::Pin("step", Low)), 0);
+
+        log.clear();
+        motion_control.reset().unwrap();
+
+        assert_eq!(
+            log.events(),
+            [Event::Pin("step", Low), Event::Pin("dir", Low)]
+        );
+        assert!(motion_control.is_idle());
+        assert!(!motion_control.update().unwrap());
+    }
+
+    #[test]
+    fn reset_should_track_direction_with_inverted_dir_signal() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+        motion_control.set_invert_direction(true);
+
+        motion_control.reset().unwrap();
+
+        // The DIR pin is low, which means forward, if the signal is inverted.
+        assert_eq!(log.events().last(), Some(&Event::Pin("dir", Low)));
+        assert_eq!(motion_control.current_direction(), Direction::Forward);
+    }
+
+    #[test]
+    fn reset_should_fail_without_panicking_on_invalid_state() {
+        let mut motion_control = mock_motion_control(&Log::new());
+        motion_control.state = State::Invalid;
+
+        assert!(matches!(motion_control.reset(), Err(Error::InvalidState)));
+    }
+
+    #[test]
+    fn abort_and_hold_should_fail_without_panicking_on_invalid_state() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+        motion_control.state = State::Invalid;
+
+        assert!(matches!(motion_control.abort(), Err(Error::InvalidState)));
+        assert!(matches!(motion_control.hold(), Err(Error::InvalidState)));
+        assert!(matches!(motion_control.state, State::Invalid));
+        assert_eq!(log.events(), []);
+    }
+
+    #[test]
+    fn update_should_fail_without_panicking_on_invalid_state() {
+        let mut motion_control = mock_motion_control(&Log::new());
+        motion_control.state = State::Invalid;
+
+        assert!(matches!(motion_control.update(), Err(Error::InvalidState)));
+        assert!(matches!(motion_control.state, State::Invalid));
+    }
+
+    #[test]
+    fn trapezoidal_profile_should_accelerate_cruise_and_decelerate() {
+        let log = Log::new();
+        let mut motion_control = SoftwareMotionControl::new(
+            MockDriver::new(&log),
+            MockTimer::new(&log),
+            Trapezoidal::new(Num::from_num(0.000_000_1)),
+            NumToTicks,
+        );
+
+        // Reaching the maximum velocity takes about 5 steps, so this leaves
+        // plenty of steps for cruising.
+        motion_control
+            .move_to_position(Num::from_num(0.001), 30)
+            .unwrap();
+        while motion_control.update().unwrap() {}
+
+        // The timer is also used for the STEP pulse, so only look at the
+        // delays that start after the pulse has ended.
+        let events = log.events();
+        let delays: Vec<_> = events
+            .windows(2)
+            .filter_map(|events| match events {
+                [Event::Pin("step", Low), Event::TimerStart(ticks)] => {
+                    Some(*ticks)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(delays.len(), 30);
+
+        // 1000 ticks per step at maximum velocity, minus the STEP pulse.
+        let cruise = 1000 - 2;
+        let accel: Vec<_> =
+            delays.iter().copied().take_while(|&d| d > cruise).collect();
+        let decel: Vec<_> = delays
+            .iter()
+            .copied()
+            .rev()
+            .take_while(|&d| d > cruise)
+            .collect();
+
+        // Delays get shorter during acceleration, stay at the minimum while
+        // cruising, then get longer again during deceleration.
+        assert!(accel.windows(2).all(|w| w[0] > w[1]), "{:?}", delays);
+        assert!(decel.windows(2).all(|w| w[0] >= w[1]), "{:?}", delays);
+        assert!(
+            delays[accel.len()..delays.len() - decel.len()]
+                .iter()
+                .all(|&d| d == cruise),
+            "{:?}",
+            delays
+        );
+
+        // The ramps are roughly symmetric. RampMaker's approximation doesn't
+        // make them exactly so.
+        assert!(!accel.is_empty());
+        assert!((accel.len() as i32 - decel.len() as i32).abs() <= 1);
+        assert!(accel[0].abs_diff(decel[0]) < accel[0] / 5, "{:?}", delays);
+    }
+
+    #[test]
+    fn resume_should_reaccelerate_and_complete_paused_move() {
+        let log = Log::new();
+        let mut motion_control = SoftwareMotionControl::new(
+            MockDriver::new(&log),
+            MockTimer::new(&log),
+            Trapezoidal::new(Num::from_num(0.000_000_1)),
+            NumToTicks,
+        );
+        let delays = || {
+            let delays: Vec<_> = log
+                .events()
+                .windows(2)
+                .filter_map(|events| match events {
+                    [Event::Pin("step", Low), Event::TimerStart(ticks)] => {
+                        Some(*ticks)
+                    }
+                    _ => None,
+                })
+                .collect();
+            log.clear();
+            delays
+        };
+
+        motion_control.queue_move(Num::from_num(0.001), 0).unwrap();
+        motion_control
+            .move_to_position(Num::from_num(0.001), 30)
+            .unwrap();
+
+        // Pause in the middle of the acceleration ramp.
+        while motion_control.current_step() < 3 {
+            motion_control.update().unwrap();
+        }
+        motion_control.update().unwrap();
+        motion_control.pause().unwrap();
+        let before = delays();
+
+        let paused_at = motion_control.current_step();
+        assert!(motion_control.is_paused());
+        assert!(motion_control.is_idle());
+        assert!(!motion_control.update().unwrap());
+        assert_eq!(motion_control.current_step(), paused_at);
+        assert_eq!(motion_control.queued_moves(), 1);
+
+        motion_control.resume().unwrap();
+        assert!(!motion_control.is_paused());
+        while motion_control.current_step() != 30 {
+            motion_control.update().unwrap();
+        }
+        let after = delays();
+
+        // The motion starts from a standstill again.
+        assert_eq!(after[0], before[0], "{:?}, {:?}", before, after);
+        assert_eq!(after.len() as i32, 30 - paused_at);
+
+        // The queued move is started after the resumed one.
+        while motion_control.update().unwrap() {}
+        assert_eq!(motion_control.current_step(), 0);
+    }
+
+    #[test]
+    fn update_should_fail_move_that_exceeds_timeout() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control_with(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(2),
+        );
+
+        // Takes 18 calls that leave the move in progress, which is just
+        // within the limit.
+        motion_control.set_timeout(Some(18));
+        motion_control.move_to_position(0.01, 3).unwrap();
+        while motion_control.update().unwrap() {}
+        assert_eq!(motion_control.current_step(), 3);
+
+        motion_control.set_timeout(Some(10));
+        motion_control.move_to_position(0.01, 6).unwrap();
+        let result = loop {
+            match motion_control.update() {
+                Ok(true) => {}
+                result => break result,
+            }
+        };
+
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert!(motion_control.is_idle());
+        assert!(motion_control.current_step() < 6);
+        assert_eq!(log.events().last(), Some(&Event::Pin("step", Low)));
+        assert!(!motion_control.update().unwrap());
+    }
+
+    #[test]
+    fn move_to_position_should_reject_targets_beyond_soft_limits() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+        motion_control.set_soft_limits(-5, 5);
+
+        // Inside and exactly at the limits.
+        for &target in &[3, 5, -5] {
+            motion_control.move_to_position(0.01, target).unwrap();
+            run(&mut motion_control);
+            assert_eq!(motion_control.current_step(), target);
+        }
+
+        // Beyond the limits.
+        log.clear();
+        for &target in &[6, -6, i32::MAX] {
+            assert!(matches!(
+                motion_control.move_to_position(0.01, target),
+                Err(Error::OutOfBounds)
+            ));
+        }
+        assert!(!motion_control.update().unwrap());
+        assert_eq!(motion_control.current_step(), -5);
+        assert_eq!(log.events(), []);
+
+        // A rejected target doesn't affect the ongoing motion.
+        motion_control.move_to_position(0.01, 0).unwrap();
+        motion_control.update().unwrap();
+        assert!(motion_control.move_to_position(0.01, 10).is_err());
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), 0);
+
+        motion_control.clear_soft_limits();
+        motion_control.move_to_position(0.01, 10).unwrap();
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), 10);
+    }
+
+    #[test]
+    fn rotate_should_stop_at_soft_limit() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+        motion_control.set_soft_limits(-3, 3);
+
+        motion_control.rotate(Direction::Forward, 0.01).unwrap();
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), 3);
+        assert_eq!(
+            motion_control.take_outcome(),
+            Some(MotionOutcome::Complete)
+        );
+
+        // Already at the limit, so there's nowhere to go.
+        log.clear();
+        motion_control.rotate(Direction::Forward, 0.01).unwrap();
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), 3);
+        assert!(!log.events().contains(&Event::Pin("step", High)));
+
+        motion_control.rotate(Direction::Backward, 0.01).unwrap();
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), -3);
+
+        // Beyond the limit, rotating toward it doesn't move back.
+        motion_control.clear_soft_limits();
+        motion_control.move_to_position(0.01, -5).unwrap();
+        run(&mut motion_control);
+        motion_control.set_soft_limits(-3, 3);
+        motion_control.rotate(Direction::Backward, 0.01).unwrap();
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), -5);
+    }
+
+    #[test]
+    fn take_outcome_should_report_completed_move_once() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        assert_eq!(motion_control.take_outcome(), None);
+
+        motion_control.move_to_position(0.01, 3).unwrap();
+        motion_control.update().unwrap();
+        assert_eq!(motion_control.take_outcome(), None);
+
+        run(&mut motion_control);
+        assert_eq!(
+            motion_control.take_outcome(),
+            Some(MotionOutcome::Complete)
+        );
+        assert_eq!(motion_control.take_outcome(), None);
+
+        // Aborting while idle doesn't interrupt anything.
+        motion_control.abort().unwrap();
+        assert_eq!(motion_control.take_outcome(), None);
+    }
+
+    #[test]
+    fn take_outcome_should_report_zero_distance_move_as_no_op() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 0).unwrap();
+        run(&mut motion_control);
+        assert_eq!(log.count(Event::Pin("step", High)), 0);
+        assert_eq!(motion_control.take_outcome(), Some(MotionOutcome::NoOp));
+
+        // A real move still completes as usual, even to the position of the
+        // previous move.
+        motion_control.move_to_position(0.01, 2).unwrap();
+        run(&mut motion_control);
+        assert_eq!(
+            motion_control.take_outcome(),
+            Some(MotionOutcome::Complete)
+        );
+
+        motion_control.move_to_position(0.01, 2).unwrap();
+        run(&mut motion_control);
+        assert_eq!(motion_control.take_outcome(), Some(MotionOutcome::NoOp));
+        assert_eq!(motion_control.current_step(), 2);
+    }
+
+    #[test]
+    fn take_outcome_should_report_aborted_motion() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 10).unwrap();
+        motion_control.update().unwrap();
+        motion_control.abort().unwrap();
+        assert!(!motion_control.update().unwrap());
+        assert_eq!(motion_control.take_outcome(), Some(MotionOutcome::Aborted));
+
+        // Pausing doesn't end a motion, but discarding it while paused does.
+        motion_control.move_to_position(0.01, 10).unwrap();
+        motion_control.update().unwrap();
+        motion_control.pause().unwrap();
+        assert_eq!(motion_control.take_outcome(), None);
+        motion_control.abort().unwrap();
+        assert_eq!(motion_control.take_outcome(), Some(MotionOutcome::Aborted));
+
+        // A rotation never completes.
+        motion_control.rotate(Direction::Forward, 0.01).unwrap();
+        motion_control.update().unwrap();
+        motion_control.hold().unwrap();
+        assert_eq!(motion_control.take_outcome(), Some(MotionOutcome::Aborted));
+    }
+
+    #[test]
+    fn last_move_ticks_should_sum_up_profile_delays() {
+        let profile = || Trapezoidal::new(Num::from_num(0.000_000_1));
+        let expected: u32 = profile_delays(profile(), Num::from_num(0.001), 20)
+            .map(|delay| delay.to_num::<u32>())
+            .sum();
+
+        let log = Log::new();
+        let mut motion_control = SoftwareMotionControl::new(
+            MockDriver::new(&log),
+            MockTimer::new(&log),
+            profile(),
+            NumToTicks,
+        );
+        assert_eq!(motion_control.last_move_ticks(), None);
+
+        motion_control
+            .move_to_position(Num::from_num(0.001), 20)
+            .unwrap();
+        motion_control.update().unwrap();
+        assert_eq!(motion_control.last_move_ticks(), None);
+
+        while motion_control.update().unwrap() {}
+        assert_eq!(motion_control.last_move_ticks(), Some(Ticks(expected)));
+
+        // The sum is reset for the next move.
+        motion_control
+            .move_to_position(Num::from_num(0.001), 0)
+            .unwrap();
+        while motion_control.update().unwrap() {}
+        assert_eq!(motion_control.last_move_ticks(), Some(Ticks(expected)));
+    }
+
+    /// Wraps the mock driver, to require an idle time between STEP pulses
+    struct LowTimeMockDriver(MockDriver);
+
+    impl Step for LowTimeMockDriver {
+        const PULSE_LENGTH: Nanoseconds = MockDriver::PULSE_LENGTH;
+        const LOW_TIME: Nanoseconds = Nanoseconds(5_000);
+
+        type Step = MockPin;
+        type Error = <MockDriver as Step>::Error;
+
+        fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+            self.0.step()
+        }
+    }
+
+    impl SetDirection for LowTimeMockDriver {
+        const SETUP_TIME: Nanoseconds = MockDriver::SETUP_TIME;
+
+        type Dir = MockPin;
+        type Error = <MockDriver as SetDirection>::Error;
+
+        fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+            self.0.dir()
+        }
+    }
+
+    #[test]
+    fn update_should_wait_at_least_low_time_between_steps() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control_with(
+            LowTimeMockDriver(MockDriver::new(&log)),
+            MockTimer::new(&log),
+        );
+
+        // The profile asks for 10 ticks per step at first, which leaves 8 ticks
+        // after the 2-tick STEP pulse. That is more than the low time. 4 ticks
+        // per step would leave 2 ticks, so the 5-tick low time is enforced
+        // instead.
+        motion_control.move_to_position(0.1, 1).unwrap();
+        while motion_control.update().unwrap() {}
+        motion_control.move_to_position(0.25, 3).unwrap();
+        while motion_control.update().unwrap() {}
+
+        let events = log.events();
+        let delays: Vec<_> = events
+            .windows(2)
+            .filter_map(|events| match events {
+                [Event::Pin("step", Low), Event::TimerStart(ticks)] => {
+                    Some(*ticks)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(delays, [8, 5, 5]);
+        assert_eq!(motion_control.current_step(), 3);
+    }
+
+    #[test]
+    fn update_should_fail_if_timer_is_too_coarse_despite_low_time() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control_with(
+            LowTimeMockDriver(MockDriver::new(&log)),
+            MockTimer::new(&log),
+        );
+
+        // 2 ticks per step, which is exactly the STEP pulse. That must not be
+        // extended to the low time silently.
+        motion_control.move_to_position(0.5, 5).unwrap();
+
+        let result = loop {
+            match motion_control.update() {
+                Ok(true) => continue,
+                result => break result,
+            }
+        };
+        assert!(matches!(result, Err(Error::SpeedExceedsTimerResolution)));
+        assert_eq!(motion_control.current_step(), 1);
+    }
+
+    #[test]
+    fn peek_next_delay_should_not_skip_steps() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        assert_eq!(motion_control.peek_next_delay(), None);
+
+        motion_control.move_to_position(0.01, 3).unwrap();
+        assert_eq!(motion_control.peek_next_delay(), Some(100.0));
+        assert_eq!(motion_control.peek_next_delay(), Some(100.0));
+
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), 3);
+        assert_eq!(log.count(Event::Pin("step", High)), 3);
+        assert_eq!(motion_control.peek_next_delay(), None);
+    }
+
+    #[test]
+    fn peek_next_delay_should_be_discarded_by_new_motion() {
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        motion_control.move_to_position(0.01, 3).unwrap();
+        assert_eq!(motion_control.peek_next_delay(), Some(100.0));
+
+        motion_control.move_to_position(0.02, 2).unwrap();
+        assert_eq!(motion_control.peek_next_delay(), Some(50.0));
+
+        run(&mut motion_control);
+        assert_eq!(motion_control.current_step(), 2);
+        assert_eq!(log.count(Event::Pin("step", High)), 2);
+    }
+
+    #[cfg(feature = "timing-stats")]
+    #[test]
+    fn timing_stats_should_accumulate_across_steps() {
+        use super::TimingStats;
+
+        let log = Log::new();
+        let mut motion_control = mock_motion_control_with(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(2),
+        );
+
+        motion_control.move_to_position(0.01, 3).unwrap();
+        run(&mut motion_control);
+
+        // The DIR setup time is 1 tick, and every step is made of a STEP pulse
+        // of 2 ticks, followed by the 98 ticks left of the step delay. Blocking
+        // timers don't make a difference.
+        let expected = TimingStats {
+            set_direction: Some(Ticks(1)),
+            step: Some(Ticks(3 * 2)),
+            step_delay: Some(Ticks(3 * 98)),
+        };
+        assert_eq!(motion_control.timing_stats(), expected);
+
+        motion_control.move_to_position(0.01, 5).unwrap();
+        run(&mut motion_control);
+        assert_eq!(
+            motion_control.timing_stats(),
+            TimingStats {
+                set_direction: Some(Ticks(2)),
+                step: Some(Ticks(5 * 2)),
+                step_delay: Some(Ticks(5 * 98)),
+            }
+        );
+
+        motion_control.reset_timing_stats();
+        assert_eq!(motion_control.timing_stats(), TimingStats::default());
+    }
+
+    #[cfg(feature = "motion-stats")]
+    #[test]
+    fn stats_should_tally_steps_ticks_and_direction_changes() {
+        use super::MotionStats;
+
+        let log = Log::new();
+        let mut motion_control = mock_motion_control(&log);
+
+        let mut move_ticks = Vec::new();
+        for &target in &[3, 0, 2, 4] {
+            motion_control.move_to_position(0.01, target).unwrap();
+            run(&mut motion_control);
+            move_ticks.push(motion_control.last_move_ticks().unwrap().0);
+        }
+
+        // Each step takes 100 ticks, including the STEP pulse.
+        assert_eq!(move_ticks, [300, 300, 200, 200]);
+        assert_eq!(
+            motion_control.stats(),
+            MotionStats {
+                total_steps: 10,
+                total_ticks: Some(Ticks(1000)),
+                direction_changes: 2,
+            }
+        );
+
+        motion_control.reset_stats();
+        assert_eq!(motion_control.stats(), MotionStats::default());
     }
 }
@@ -0,0 +1,99 @@
+use core::ops;
+
+use crate::Direction;
+
+use super::OnStep;
+
+/// Totals of all motions made by [`SoftwareMotionControl`]
+///
+/// Accumulated by [`MotionControl::update`], across any number of motions.
+/// This is meant for endurance testing, where a machine makes thousands of
+/// moves, and the totals show how much work it has done.
+///
+/// Only available with the `motion-stats` feature. See
+/// [`SoftwareMotionControl::stats`].
+///
+/// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+/// [`SoftwareMotionControl::stats`]: super::SoftwareMotionControl::stats
+/// [`MotionControl::update`]: crate::traits::MotionControl::update
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MotionStats<Ticks> {
+    /// The number of steps made, in either direction
+    pub total_steps: u64,
+
+    /// The timer ticks taken by the steps of all moves
+    ///
+    /// Adds up the time from the start of each step to the start of the next,
+    /// like [`SoftwareMotionControl::last_move_ticks`] does for a single move.
+    /// Like there, rotations are not included, and the sum is not protected
+    /// against overflow, which can happen after a while, depending on the
+    /// timer's tick type. `None`, if no step of a move has been made.
+    ///
+    /// [`SoftwareMotionControl::last_move_ticks`]:
+    ///     super::SoftwareMotionControl::last_move_ticks
+    pub total_ticks: Option<Ticks>,
+
+    /// How often a motion went in the opposite direction of the previous one
+    ///
+    /// Before the first motion, the direction is [`Direction::Forward`].
+    pub direction_changes: u32,
+}
+
+impl<Ticks> MotionStats<Ticks>
+where
+    Ticks: Copy + ops::Add<Output = Ticks> + ops::Sub<Output = Ticks>,
+{
+    /// Record what happened during a call to `update`
+    ///
+    /// Steps are counted separately, by [`CountSteps`].
+    pub(super) fn record(
+        &mut self,
+        ticks_before: Option<Ticks>,
+        ticks_after: Option<Ticks>,
+        direction_before: Direction,
+        direction_after: Direction,
+    ) {
+        if direction_before != direction_after {
+            self.direction_changes = self.direction_changes.saturating_add(1);
+        }
+
+        // The sum for a move only grows, until the next move starts over.
+        let ticks = match (ticks_before, ticks_after) {
+            (Some(before), Some(after)) => after - before,
+            (None, Some(after)) => after,
+            (_, None) => return,
+        };
+
+        self.total_ticks = Some(match self.total_ticks {
+            Some(total_ticks) => total_ticks + ticks,
+            None => ticks,
+        });
+    }
+}
+
+impl<Ticks> Default for MotionStats<Ticks> {
+    fn default() -> Self {
+        Self {
+            total_steps: 0,
+            total_ticks: None,
+            direction_changes: 0,
+        }
+    }
+}
+
+/// Counts steps, before passing them on to the user's hook
+pub(super) struct CountSteps<'r, Hook> {
+    pub hook: &'r mut Hook,
+    pub steps: &'r mut u64,
+}
+
+impl<'r, Counter, Hook> OnStep<Counter> for CountSteps<'r, Hook>
+where
+    Hook: OnStep<Counter>,
+{
+    fn on_step(&mut self, step: Counter, direction: Direction) {
+        *self.steps = self.steps.saturating_add(1);
+        self.hook.on_step(step, direction);
+    }
+}
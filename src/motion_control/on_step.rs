@@ -0,0 +1,34 @@
+use crate::Direction;
+
+/// A hook that [`SoftwareMotionControl`] calls after every completed step
+///
+/// This can be used to keep track of steps outside of this crate, for example
+/// to cross-check them against an external encoder and detect missed steps.
+///
+/// This trait is implemented for `()`, which does nothing and is the default,
+/// and for all closures that take the new position and the direction of the
+/// step. See [`SoftwareMotionControl::with_on_step`].
+///
+/// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+/// [`SoftwareMotionControl::with_on_step`]:
+///     super::SoftwareMotionControl::with_on_step
+pub trait OnStep<Counter> {
+    /// Called after a step has been completed
+    ///
+    /// `step` is the position after the step was made, `direction` is the
+    /// direction the step was made in.
+    fn on_step(&mut self, step: Counter, direction: Direction);
+}
+
+impl<Counter> OnStep<Counter> for () {
+    fn on_step(&mut self, _: Counter, _: Direction) {}
+}
+
+impl<Counter, F> OnStep<Counter> for F
+where
+    F: FnMut(Counter, Direction),
+{
+    fn on_step(&mut self, step: Counter, direction: Direction) {
+        self(step, direction)
+    }
+}
@@ -0,0 +1,31 @@
+/// How a motion ended
+///
+/// Reported by [`SoftwareMotionControl::take_outcome`], once a motion has
+/// ended. This makes it possible to tell a move that has reached its target
+/// apart from one that was interrupted, which [`MotionControl::update`] alone
+/// doesn't, as it returns `Ok(false)` in both cases.
+///
+/// [`SoftwareMotionControl::take_outcome`]:
+///     super::SoftwareMotionControl::take_outcome
+/// [`MotionControl::update`]: crate::traits::MotionControl::update
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MotionOutcome {
+    /// The motion has reached its target
+    Complete,
+
+    /// The move's target was the position the motor was already at
+    ///
+    /// No steps were made. This is reported instead of [`Self::Complete`],
+    /// which would otherwise look the same.
+    NoOp,
+
+    /// The motion was stopped before reaching its target
+    ///
+    /// This happens, if the motion was aborted (see
+    /// [`MotionControl::abort`]), shut down, held, or timed out. A rotation
+    /// never reaches a target, so it always ends like this.
+    ///
+    /// [`MotionControl::abort`]: crate::traits::MotionControl::abort
+    Aborted,
+}
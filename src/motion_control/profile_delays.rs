@@ -0,0 +1,81 @@
+use ramp_maker::MotionProfile;
+
+/// Compute the delays of a motion, without a driver or timer
+///
+/// Enters position mode of `profile`, as [`SoftwareMotionControl`] would for a
+/// motion of `num_steps` steps at `max_velocity`, and returns an iterator over
+/// the delays between the steps. This is useful for analyzing or plotting a
+/// motion profile, and for tuning its parameters.
+///
+/// Takes ownership of `profile`, since the motion profile's state changes
+/// while iterating. Use [`ProfileDelays::release`] to get it back.
+///
+/// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+pub fn profile_delays<Profile>(
+    mut profile: Profile,
+    max_velocity: Profile::Velocity,
+    num_steps: u32,
+) -> ProfileDelays<Profile>
+where
+    Profile: MotionProfile,
+{
+    profile.enter_position_mode(max_velocity, num_steps);
+    ProfileDelays { profile }
+}
+
+/// An iterator over the delays of a motion
+///
+/// Returned by [`profile_delays`]. Calls [`MotionProfile::next_delay`] until
+/// the motion has finished.
+pub struct ProfileDelays<Profile> {
+    profile: Profile,
+}
+
+impl<Profile> ProfileDelays<Profile> {
+    /// Release the motion profile
+    pub fn release(self) -> Profile {
+        self.profile
+    }
+}
+
+impl<Profile> Iterator for ProfileDelays<Profile>
+where
+    Profile: MotionProfile,
+{
+    type Item = Profile::Delay;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.profile.next_delay()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use ramp_maker::Trapezoidal;
+
+    use super::profile_delays;
+
+    #[test]
+    fn profile_delays_should_accelerate_then_decelerate() {
+        // RampMaker only implements the math `Trapezoidal` needs for `f32` with
+        // its `std` or `libm` features, so use a fixed-point number instead.
+        type Num = fixed::FixedI64<typenum::U32>;
+
+        // Too short to reach the maximum velocity, so there's no cruising.
+        let delays: Vec<u32> = profile_delays(
+            Trapezoidal::new(Num::from_num(0.000_000_1)),
+            Num::from_num(0.001),
+            6,
+        )
+        .map(|delay| delay.to_num())
+        .collect();
+
+        assert_eq!(delays.len(), 6);
+
+        let (accel, decel) = delays.split_at(3);
+        assert!(accel.windows(2).all(|delays| delays[0] > delays[1]));
+        assert!(decel.windows(2).all(|delays| delays[0] <= delays[1]));
+    }
+}
@@ -0,0 +1,423 @@
+use embedded_hal::digital::blocking::OutputPin;
+use ramp_maker::MotionProfile;
+
+use crate::{
+    traits::{MotionControl, PwmStep, SetDirection},
+    Direction,
+};
+
+use super::{
+    retarget::{self, Reversal},
+    DelayToTicks, PwmError, StepCounter as _,
+};
+
+/// Motion control that generates STEP pulses in hardware
+///
+/// Works like [`SoftwareMotionControl`], except that STEP pulses are generated
+/// by a PWM or output compare channel (see [`PwmStep`]), instead of toggling
+/// the STEP pin in software. Every delay of the motion profile becomes the
+/// period of one STEP pulse. Since the STEP pulses don't depend on software
+/// timing, this supports higher step rates and doesn't jitter under interrupt
+/// load.
+///
+/// [`MotionControl::update`] counts the steps that have been made since it was
+/// last called, and programs the period of the next step. It needs to be
+/// called at least once per step (for example, from the interrupt handler of
+/// the PWM channel), or some steps are made with an outdated period. If the
+/// last step of a motion has been made before `update` is called, additional
+/// steps might be made before the pulses are stopped. Those are still counted.
+///
+/// The DIR signal is set at the start of every motion, without waiting for
+/// [`SetDirection::SETUP_TIME`]. This relies on the next STEP pulse being at
+/// least one period away, which means the delays of the motion profile must
+/// always be longer than the setup time.
+///
+/// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+pub struct PwmMotionControl<Driver, Profile: MotionProfile, Convert> {
+    driver: Driver,
+    profile: Profile,
+    convert: Convert,
+    max_velocity: Option<Profile::Velocity>,
+    moving: bool,
+    rotating: bool,
+    current_step: i32,
+    target_step: i32,
+    current_direction: Direction,
+}
+
+impl<Driver, Profile, Convert> PwmMotionControl<Driver, Profile, Convert>
+where
+    Profile: MotionProfile,
+{
+    /// Construct a new instance of `PwmMotionControl`
+    ///
+    /// `convert` converts the delays of the motion profile into the period type
+    /// of the driver (see [`PwmStep::Period`]).
+    pub fn new(driver: Driver, profile: Profile, convert: Convert) -> Self {
+        Self {
+            driver,
+            profile,
+            convert,
+            max_velocity: None,
+            moving: false,
+            rotating: false,
+            current_step: 0,
+            target_step: 0,
+            // Doesn't matter what we initialize it with. It will have been
+            // overridden, once a motion has been started.
+            current_direction: Direction::Forward,
+        }
+    }
+
+    /// Access a reference to the wrapped driver
+    pub fn driver(&self) -> &Driver {
+        &self.driver
+    }
+
+    /// Access a mutable reference to the wrapped driver
+    pub fn driver_mut(&mut self) -> &mut Driver {
+        &mut self.driver
+    }
+
+    /// Release the wrapped driver
+    ///
+    /// Drops this instance of `PwmMotionControl`, along with the motion
+    /// profile and delay converter. STEP pulses are not stopped. Only release
+    /// the driver while idle, unless you take care of that yourself.
+    pub fn release(self) -> Driver {
+        self.driver
+    }
+}
+
+impl<Driver, Profile, Convert> PwmMotionControl<Driver, Profile, Convert>
+where
+    Driver: SetDirection + PwmStep,
+    Profile: MotionProfile,
+    Profile::Velocity: Copy,
+    Convert: DelayToTicks<Profile::Delay, Ticks = Driver::Period>,
+{
+    /// Count the steps that have been made since the last call
+    fn count_steps(&mut self) -> Result<u32, <Self as MotionControl>::Error> {
+        let steps = self.driver.take_steps().map_err(PwmError::PwmStep)?;
+        self.current_step = self
+            .current_step
+            .wrapping_add(self.current_direction.apply(steps));
+
+        Ok(steps)
+    }
+
+    /// Program the next period, or stop, if the motion profile has finished
+    fn next_period(
+        &mut self,
+        delay: Option<Profile::Delay>,
+    ) -> Result<(), <Self as MotionControl>::Error> {
+        match delay {
+            Some(delay) => {
+                let period = self
+                    .convert
+                    .delay_to_ticks(delay)
+                    .map_err(PwmError::DelayToTicks)?;
+                self.driver.set_period(period).map_err(PwmError::PwmStep)?;
+                self.moving = true;
+            }
+            None => {
+                self.driver.stop().map_err(PwmError::PwmStep)?;
+                self.moving = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set the direction and start the first STEP pulse of a new motion
+    fn start_motion(
+        &mut self,
+        direction: Direction,
+    ) -> Result<(), <Self as MotionControl>::Error> {
+        let forward = self.driver.dir_polarity();
+        let level = match direction {
+            Direction::Forward => forward,
+            Direction::Backward => !forward,
+        };
+        self.driver
+            .dir()
+            .map_err(PwmError::SetDirectionPinUnavailable)?
+            .set_state(level)
+            .map_err(PwmError::SetDirection)?;
+        self.current_direction = direction;
+
+        let delay = self.profile.next_delay();
+        self.next_period(delay)
+    }
+}
+
+impl<Driver, Profile, Convert> MotionControl
+    for PwmMotionControl<Driver, Profile, Convert>
+where
+    Driver: SetDirection + PwmStep,
+    Profile: MotionProfile,
+    Profile::Velocity: Copy,
+    Convert: DelayToTicks<Profile::Delay, Ticks = Driver::Period>,
+{
+    type Velocity = Profile::Velocity;
+    type Error = PwmError<
+        <Driver as SetDirection>::Error,
+        <<Driver as SetDirection>::Dir as OutputPin>::Error,
+        <Driver as PwmStep>::Error,
+        Convert::Error,
+    >;
+
+    fn move_to_position(
+        &mut self,
+        max_velocity: Self::Velocity,
+        target_step: i32,
+    ) -> Result<(), Self::Error> {
+        // Steps made so far were made in the previous direction.
+        self.count_steps()?;
+
+        self.profile.enter_position_mode(
+            max_velocity,
+            self.current_step.distance(target_step),
+        );
+        self.max_velocity = Some(max_velocity);
+        self.rotating = false;
+        self.target_step = target_step;
+
+        let direction = if target_step > self.current_step {
+            Direction::Forward
+        } else {
+            Direction::Backward
+        };
+        self.start_motion(direction)
+    }
+
+    fn rotate(
+        &mut self,
+        direction: Direction,
+        velocity: Self::Velocity,
+    ) -> Result<(), Self::Error> {
+        // Steps made so far were made in the previous direction.
+        self.count_steps()?;
+
+        retarget::enter_rotation(&mut self.profile, velocity);
+        self.max_velocity = Some(velocity);
+        self.rotating = true;
+
+        self.start_motion(direction)
+    }
+
+    /// Change the maximum velocity of the ongoing motion
+    ///
+    /// See [`SoftwareMotionControl`'s implementation], which this works like.
+    ///
+    /// [`SoftwareMotionControl`'s implementation]:
+    ///     super::SoftwareMotionControl::set_max_speed
+    fn set_max_speed(
+        &mut self,
+        max_velocity: Self::Velocity,
+    ) -> Result<(), Self::Error> {
+        self.count_steps()?;
+        retarget::set_max_speed(
+            &mut self.profile,
+            &mut self.max_velocity,
+            max_velocity,
+            self.rotating,
+            self.current_step,
+            self.target_step,
+        );
+
+        Ok(())
+    }
+
+    fn reset_position(&mut self, step: i32) -> Result<(), Self::Error> {
+        // Any remaining steps are still made, so the target moves along with
+        // the position.
+        self.target_step = self
+            .target_step
+            .wrapping_add(step.wrapping_sub(self.current_step));
+        self.current_step = step;
+        Ok(())
+    }
+
+    fn current_step(&mut self) -> Result<i32, Self::Error> {
+        self.count_steps()?;
+        Ok(self.current_step)
+    }
+
+    fn current_direction(&mut self) -> Result<Direction, Self::Error> {
+        Ok(self.current_direction)
+    }
+
+    fn reverse(&mut self) -> Result<(), Self::Error> {
+        if !self.moving {
+            return Ok(());
+        }
+
+        self.count_steps()?;
+        let reversal = retarget::reversal(
+            self.max_velocity,
+            self.rotating,
+            self.current_direction,
+            self.current_step,
+            self.target_step,
+        );
+
+        match reversal {
+            Reversal::Nothing => Ok(()),
+            Reversal::Rotate(direction, velocity) => {
+                self.rotate(direction, velocity)
+            }
+            Reversal::MoveTo(max_velocity, target_step) => {
+                self.move_to_position(max_velocity, target_step)
+            }
+        }
+    }
+
+    fn remaining_steps(&mut self) -> Result<Option<u32>, Self::Error> {
+        if self.rotating {
+            return Ok(None);
+        }
+
+        self.count_steps()?;
+        Ok(Some(self.current_step.distance(self.target_step)))
+    }
+
+    /// Abort any ongoing motion immediately
+    ///
+    /// Stops the STEP pulses. Any steps that have been made up to that point
+    /// are still counted.
+    fn abort(&mut self) -> Result<(), Self::Error> {
+        self.driver.stop().map_err(PwmError::PwmStep)?;
+        self.moving = false;
+        self.rotating = false;
+        self.count_steps()?;
+
+        Ok(())
+    }
+
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        if !self.moving {
+            return Ok(false);
+        }
+
+        if self.rotating {
+            if let Some(max_velocity) = self.max_velocity {
+                retarget::enter_rotation(&mut self.profile, max_velocity);
+            }
+        }
+
+        let steps = self.count_steps()?;
+        if steps == 0 {
+            return Ok(true);
+        }
+
+        // Every step that was made used up one delay. The next one becomes
+        // the period of the next step.
+        let mut delay = None;
+        for _ in 0..steps {
+            delay = self.profile.next_delay();
+            if delay.is_none() {
+                break;
+            }
+        }
+        self.next_period(delay)?;
+
+        Ok(self.moving)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::convert::Infallible;
+
+    use embedded_hal::digital::PinState::High;
+    use embedded_time::duration::Nanoseconds;
+    use ramp_maker::Flat;
+
+    use crate::{
+        compat::Ticks,
+        test_util::{Event, Log, MockDelayToTicks, MockPin},
+        traits::{MotionControl as _, PwmStep, SetDirection},
+    };
+
+    use super::PwmMotionControl;
+
+    /// A mock PWM channel, with a DIR pin
+    ///
+    /// The tests make steps, by adding to `pending_steps`.
+    struct MockPwm {
+        dir: MockPin,
+        periods: Vec<u32>,
+        stops: usize,
+        pending_steps: u32,
+    }
+
+    impl PwmStep for MockPwm {
+        type Period = Ticks<u32, 1_000_000>;
+        type Error = Infallible;
+
+        fn set_period(
+            &mut self,
+            period: Self::Period,
+        ) -> Result<(), Self::Error> {
+            self.periods.push(period.0);
+            Ok(())
+        }
+
+        fn stop(&mut self) -> Result<(), Self::Error> {
+            self.stops += 1;
+            Ok(())
+        }
+
+        fn take_steps(&mut self) -> Result<u32, Self::Error> {
+            Ok(core::mem::take(&mut self.pending_steps))
+        }
+    }
+
+    impl SetDirection for MockPwm {
+        const SETUP_TIME: Nanoseconds = Nanoseconds(0);
+
+        type Dir = MockPin;
+        type Error = Infallible;
+
+        fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+            Ok(&mut self.dir)
+        }
+    }
+
+    #[test]
+    fn pwm_motion_control_should_program_one_period_per_step() {
+        let log = Log::new();
+        let mut motion_control = PwmMotionControl::new(
+            MockPwm {
+                dir: MockPin::new("dir", &log),
+                periods: Vec::new(),
+                stops: 0,
+                pending_steps: 0,
+            },
+            Flat::new(),
+            MockDelayToTicks,
+        );
+
+        motion_control.move_to_position(0.01, 3).unwrap();
+        assert_eq!(log.events(), [Event::Pin("dir", High)]);
+        assert_eq!(motion_control.driver().periods, [100]);
+
+        // The timer hasn't produced a step yet.
+        assert!(motion_control.update().unwrap());
+        assert_eq!(motion_control.driver().periods, [100]);
+
+        motion_control.driver_mut().pending_steps = 1;
+        assert!(motion_control.update().unwrap());
+        assert_eq!(motion_control.current_step().unwrap(), 1);
+        assert_eq!(motion_control.driver().periods, [100, 100]);
+
+        // Two steps between updates still use up two delays.
+        motion_control.driver_mut().pending_steps = 2;
+        assert!(!motion_control.update().unwrap());
+        assert_eq!(motion_control.current_step().unwrap(), 3);
+        assert_eq!(motion_control.driver().periods, [100, 100]);
+        assert_eq!(motion_control.driver().stops, 1);
+    }
+}
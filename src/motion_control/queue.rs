@@ -0,0 +1,99 @@
+/// The number of moves that [`SoftwareMotionControl`] can queue
+///
+/// See [`SoftwareMotionControl::queue_move`].
+///
+/// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+/// [`SoftwareMotionControl::queue_move`]: super::SoftwareMotionControl::queue_move
+pub const MOVE_QUEUE_CAPACITY: usize = 8;
+
+/// A fixed-capacity FIFO queue of moves
+///
+/// Each move consists of the maximum velocity and the target step.
+pub struct MoveQueue<Velocity> {
+    moves: [Option<(Velocity, i32)>; MOVE_QUEUE_CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl<Velocity> MoveQueue<Velocity> {
+    pub fn new() -> Self {
+        Self {
+            moves: [(); MOVE_QUEUE_CAPACITY].map(|()| None),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add a move to the back of the queue
+    ///
+    /// Returns the move as the error, if the queue is full.
+    pub fn push(
+        &mut self,
+        max_velocity: Velocity,
+        target_step: i32,
+    ) -> Result<(), (Velocity, i32)> {
+        if self.len == MOVE_QUEUE_CAPACITY {
+            return Err((max_velocity, target_step));
+        }
+
+        let index = (self.head + self.len) % MOVE_QUEUE_CAPACITY;
+        self.moves[index] = Some((max_velocity, target_step));
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Remove the move from the front of the queue
+    pub fn pop(&mut self) -> Option<(Velocity, i32)> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let next = self.moves[self.head].take();
+        self.head = (self.head + 1) % MOVE_QUEUE_CAPACITY;
+        self.len -= 1;
+
+        next
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MoveQueue, MOVE_QUEUE_CAPACITY};
+
+    #[test]
+    fn move_queue_should_be_fifo_and_wrap_around() {
+        let mut queue = MoveQueue::new();
+
+        for round in 0..3 {
+            for i in 0..MOVE_QUEUE_CAPACITY as i32 {
+                queue.push((), round * 100 + i).unwrap();
+            }
+            assert_eq!(queue.push((), -1), Err(((), -1)));
+            assert_eq!(queue.len(), MOVE_QUEUE_CAPACITY);
+
+            for i in 0..MOVE_QUEUE_CAPACITY as i32 {
+                assert_eq!(queue.pop(), Some(((), round * 100 + i)));
+            }
+            assert_eq!(queue.pop(), None);
+
+            // Offset the head for the next round.
+            queue.push((), 0).unwrap();
+            queue.pop();
+        }
+
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+use ramp_maker::MotionProfile;
+
+/// Accelerates through resonance bands of a motor
+///
+/// Stepper motors have speed ranges at which they resonate, which can make
+/// them lose steps or stall. This wraps a motion profile (like
+/// [`ramp_maker::Trapezoidal`]) and keeps it from lingering in those ranges
+/// while accelerating or decelerating.
+///
+/// The forbidden ranges are given as pairs of delays `(min, max)`, in the same
+/// unit as the delays produced by the motion profile. Any delay strictly
+/// between `min` and `max` is forbidden. While the motor is accelerating,
+/// forbidden delays are replaced with `min`, which crosses the band at the
+/// faster speed right away. While the motor is decelerating, they are replaced
+/// with `max`, dropping to the slower speed right away. The width of a band
+/// determines the size of that jump in speed, so keep bands narrow.
+///
+/// If the maximum velocity of a motion lies within a band, the faster edge of
+/// that band would exceed it. In that case, `max` is used when accelerating
+/// too, and the motion cruises at the slower edge of the band.
+///
+/// # Interaction with RampMaker
+///
+/// RampMaker's motion profiles don't support this directly, so this only
+/// replaces the delays they produce. The wrapped profile still produces one
+/// delay per step, and its internal state (like its idea of the current
+/// velocity) is not affected. This means the number of steps is unchanged, and
+/// once a band has been crossed, the ramp continues where it would have been
+/// without crossing it faster. Whether the motor is accelerating or
+/// decelerating is determined by comparing each delay to the previous one.
+pub struct ResonanceAvoidance<Profile, const N: usize>
+where
+    Profile: MotionProfile,
+{
+    profile: Profile,
+    bands: [(Profile::Delay, Profile::Delay); N],
+
+    min_delay: Option<Profile::Delay>,
+    previous_delay: Option<Profile::Delay>,
+    accelerating: bool,
+}
+
+impl<Profile, const N: usize> ResonanceAvoidance<Profile, N>
+where
+    Profile: MotionProfile,
+{
+    /// Create a new instance of `ResonanceAvoidance`
+    ///
+    /// `bands` are the forbidden ranges of delays. See the type documentation
+    /// for details.
+    pub fn new(
+        profile: Profile,
+        bands: [(Profile::Delay, Profile::Delay); N],
+    ) -> Self {
+        Self {
+            profile,
+            bands,
+            min_delay: None,
+            previous_delay: None,
+            accelerating: true,
+        }
+    }
+
+    /// Access the wrapped motion profile
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// Mutably access the wrapped motion profile
+    pub fn profile_mut(&mut self) -> &mut Profile {
+        &mut self.profile
+    }
+
+    /// Release the wrapped motion profile
+    pub fn release(self) -> Profile {
+        self.profile
+    }
+}
+
+impl<Profile, Num, const N: usize> MotionProfile
+    for ResonanceAvoidance<Profile, N>
+where
+    Profile: MotionProfile<Velocity = Num, Delay = Num>,
+    Num: Copy + PartialOrd + num_traits::Zero + num_traits::Inv<Output = Num>,
+{
+    type Velocity = Num;
+    type Delay = Num;
+
+    fn enter_position_mode(
+        &mut self,
+        max_velocity: Self::Velocity,
+        num_steps: u32,
+    ) {
+        self.min_delay = if max_velocity.is_zero() {
+            None
+        } else {
+            Some(max_velocity.inv())
+        };
+
+        // This might be an ongoing motion, so the previous delay is kept.
+        self.profile.enter_position_mode(max_velocity, num_steps);
+    }
+
+    fn next_delay(&mut self) -> Option<Self::Delay> {
+        let delay = match self.profile.next_delay() {
+            Some(delay) => delay,
+            None => {
+                // The motion has ended. The next one starts from a standstill.
+                self.previous_delay = None;
+                self.accelerating = true;
+                return None;
+            }
+        };
+
+        // While cruising, the delay stays the same, and so does the verdict.
+        if let Some(previous_delay) = self.previous_delay {
+            if delay < previous_delay {
+                self.accelerating = true;
+            } else if delay > previous_delay {
+                self.accelerating = false;
+            }
+        }
+        self.previous_delay = Some(delay);
+
+        for &(min, max) in &self.bands {
+            if min < delay && delay < max {
+                let min_allowed = match self.min_delay {
+                    Some(min_delay) => min >= min_delay,
+                    None => true,
+                };
+
+                return Some(if self.accelerating && min_allowed {
+                    min
+                } else {
+                    max
+                });
+            }
+        }
+
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use ramp_maker::{MotionProfile, Trapezoidal};
+
+    use crate::motion_control::profile_delays;
+
+    use super::ResonanceAvoidance;
+
+    // See the test in `profile_delays`, for why this doesn't use `f32`.
+    type Num = fixed::FixedI64<typenum::U32>;
+
+    const BAND: (u32, u32) = (1100, 1700);
+
+    fn delays(
+        profile: impl MotionProfile<Velocity = Num, Delay = Num>,
+    ) -> Vec<u32> {
+        profile_delays(profile, Num::from_num(0.001), 30)
+            .map(|delay| delay.to_num())
+            .collect()
+    }
+
+    fn in_band(delays: &[u32]) -> usize {
+        delays
+            .iter()
+            .filter(|&&delay| BAND.0 < delay && delay < BAND.1)
+            .count()
+    }
+
+    fn trapezoidal() -> Trapezoidal<Num> {
+        Trapezoidal::new(Num::from_num(0.000_000_1))
+    }
+
+    #[test]
+    fn resonance_avoidance_should_spend_fewer_steps_in_band() {
+        let band = (Num::from_num(BAND.0), Num::from_num(BAND.1));
+
+        let plain = delays(trapezoidal());
+        let avoided = delays(ResonanceAvoidance::new(trapezoidal(), [band]));
+
+        assert!(in_band(&plain) > 0, "{:?}", plain);
+        assert_eq!(in_band(&avoided), 0, "{:?}", avoided);
+        assert_eq!(avoided.len(), plain.len());
+
+        // Accelerating crosses at the faster edge, decelerating at the slower
+        // one.
+        let crossing: Vec<_> = avoided
+            .iter()
+            .copied()
+            .filter(|&delay| delay == BAND.0 || delay == BAND.1)
+            .collect();
+        assert_eq!(crossing.first(), Some(&BAND.0), "{:?}", avoided);
+        assert_eq!(crossing.last(), Some(&BAND.1), "{:?}", avoided);
+    }
+
+    #[test]
+    fn resonance_avoidance_should_respect_max_velocity() {
+        // The maximum velocity of 0.001 is inside this band.
+        let band = (Num::from_num(900), Num::from_num(BAND.1));
+
+        let avoided = delays(ResonanceAvoidance::new(trapezoidal(), [band]));
+
+        assert!(avoided.iter().all(|&delay| delay >= 1000), "{:?}", avoided);
+    }
+}
@@ -0,0 +1,102 @@
+use ramp_maker::MotionProfile;
+
+use crate::Direction;
+
+use super::StepCounter;
+
+/// Tell the motion profile to keep going, for a rotation
+///
+/// RampMaker's motion profiles only support moving a number of steps. We tell
+/// it to go as far as it can. To make sure it never runs out of steps, this
+/// needs to be called again on every update, while rotating.
+pub fn enter_rotation<Profile>(
+    profile: &mut Profile,
+    velocity: Profile::Velocity,
+) where
+    Profile: MotionProfile,
+{
+    profile.enter_position_mode(velocity, u32::MAX);
+}
+
+/// Change the maximum velocity of the ongoing motion
+///
+/// Re-enters position mode of the motion profile with the new maximum velocity
+/// and the steps that are left. Returns `false` without changing anything, if
+/// there never was a motion.
+pub fn set_max_speed<Profile, Counter>(
+    profile: &mut Profile,
+    max_velocity: &mut Option<Profile::Velocity>,
+    new_max_velocity: Profile::Velocity,
+    rotating: bool,
+    position: Counter,
+    target_step: Counter,
+) -> bool
+where
+    Profile: MotionProfile,
+    Profile::Velocity: Copy,
+    Counter: StepCounter,
+{
+    // If there never was a motion, there's nothing to change.
+    if max_velocity.is_none() {
+        return false;
+    }
+
+    let steps_left = if rotating {
+        u32::MAX
+    } else {
+        position.distance(target_step)
+    };
+
+    profile.enter_position_mode(new_max_velocity, steps_left);
+    *max_velocity = Some(new_max_velocity);
+
+    true
+}
+
+/// What reversing the ongoing motion amounts to
+///
+/// See [`reversal`].
+pub enum Reversal<Velocity, Counter> {
+    /// There is no motion to reverse
+    Nothing,
+
+    /// Rotate in the given direction instead
+    Rotate(Direction, Velocity),
+
+    /// Move to the given target instead
+    MoveTo(Velocity, Counter),
+}
+
+/// Determine how to reverse the ongoing motion
+///
+/// A rotation is reversed by rotating in the opposite direction. A move is
+/// reversed by mirroring its target at the current position.
+pub fn reversal<Velocity, Counter>(
+    max_velocity: Option<Velocity>,
+    rotating: bool,
+    current_direction: Direction,
+    position: Counter,
+    target_step: Counter,
+) -> Reversal<Velocity, Counter>
+where
+    Counter: StepCounter,
+{
+    let max_velocity = match max_velocity {
+        Some(max_velocity) => max_velocity,
+        // No motion has ever been started.
+        None => return Reversal::Nothing,
+    };
+
+    if rotating {
+        return Reversal::Rotate(current_direction.opposite(), max_velocity);
+    }
+
+    if position == target_step {
+        // Nothing left to reverse.
+        return Reversal::Nothing;
+    }
+
+    // Mirror the target at the current position. This needs to happen in
+    // `Counter`, as the position might not fit into `i32`.
+    Reversal::MoveTo(max_velocity, target_step.saturating_mirror(position))
+}
@@ -15,10 +15,15 @@ use crate::{
 
 use super::{
     error::{Error, TimeConversionError},
-    DelayToTicks,
+    DelayToTicks, OnStep, StepCounter,
 };
 
-pub enum State<Driver, Timer, Profile: MotionProfile> {
+pub enum State<
+    Driver,
+    Timer,
+    Profile: MotionProfile,
+    Ticks = <Timer as timer::CountDown>::Time,
+> {
     Idle {
         driver: Driver,
         timer: Timer,
@@ -28,6 +33,13 @@ pub enum State<Driver, Timer, Profile: MotionProfile> {
         future: StepFuture<Driver, Timer>,
         delay: Profile::Delay,
     },
+    /// The step has been made, but starting the timer for the step delay has
+    /// failed, and needs to be retried
+    StartStepDelay {
+        driver: Driver,
+        timer: Timer,
+        ticks: Ticks,
+    },
     StepDelay {
         driver: Driver,
         timer: Timer,
@@ -35,13 +47,58 @@ pub enum State<Driver, Timer, Profile: MotionProfile> {
     Invalid,
 }
 
-pub fn update<Driver, Timer, Profile, Convert>(
+impl<Driver, Timer, Profile, Ticks> State<Driver, Timer, Profile, Ticks>
+where
+    Profile: MotionProfile,
+{
+    /// Access the driver, in any state
+    ///
+    /// Returns `None`, if a previous panic left the state invalid.
+    pub fn driver(&self) -> Option<&Driver> {
+        match self {
+            State::Idle { driver, .. }
+            | State::StartStepDelay { driver, .. }
+            | State::StepDelay { driver, .. } => Some(driver),
+            State::SetDirection(future) => Some(future.driver()),
+            State::Step { future, .. } => Some(future.driver()),
+            State::Invalid => None,
+        }
+    }
+
+    /// Access the driver mutably, in any state
+    ///
+    /// Returns `None`, if a previous panic left the state invalid.
+    pub fn driver_mut(&mut self) -> Option<&mut Driver> {
+        match self {
+            State::Idle { driver, .. }
+            | State::StartStepDelay { driver, .. }
+            | State::StepDelay { driver, .. } => Some(driver),
+            State::SetDirection(future) => Some(future.driver_mut()),
+            State::Step { future, .. } => Some(future.driver_mut()),
+            State::Invalid => None,
+        }
+    }
+}
+
+// All of these are borrowed from separate fields of `SoftwareMotionControl`,
+// which can't be passed as a whole, as its state is being replaced.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub fn update<Driver, Timer, Profile, Convert, Counter, Hook>(
     mut state: State<Driver, Timer, Profile>,
     new_motion: &mut Option<Direction>,
     profile: &mut Profile,
-    current_step: &mut i32,
+    next_delay: &mut Option<Profile::Delay>,
+    current_step: &mut Counter,
     current_direction: &mut Direction,
+    invert_direction: bool,
+    pulse_length: Option<Nanoseconds>,
     convert: &Convert,
+    on_step: &mut Hook,
+    mut move_ticks: Option<&mut Option<Timer::Time>>,
+    mut rotary_wrap: Option<RotaryWrap<Counter>>,
+    timing: &mut impl RecordTiming<Timer::Time>,
+    max_transitions: usize,
 ) -> (
     Result<
         bool,
@@ -62,36 +119,69 @@ where
     Timer: timer::CountDown,
     Profile: MotionProfile,
     Convert: DelayToTicks<Profile::Delay, Ticks = Timer::Time>,
-    Convert::Ticks: TryFrom<Nanoseconds> + ops::Sub<Output = Convert::Ticks>,
+    Convert::Ticks: TryFrom<Nanoseconds>
+        + ops::Add<Output = Convert::Ticks>
+        + ops::Sub<Output = Convert::Ticks>
+        + PartialOrd
+        + Copy,
+    Counter: StepCounter,
+    Hook: OnStep<Counter>,
 {
+    // Every iteration after the first one follows a state transition.
+    let mut transitions = 0;
+
     loop {
+        if transitions == max_transitions {
+            // The caller doesn't want us to do more work in this call. The new
+            // state will be handled on the next one.
+            return (Ok(true), state);
+        }
+
         match state {
             State::Idle { driver, timer } => {
                 // Being idle can mean that there's actually nothing to do, or
                 // it might just be a short breather before more work comes in.
 
                 if let Some(direction) = new_motion.take() {
+                    // Only the signal is inverted. Steps are counted in the
+                    // direction that was requested.
+                    let signal = if invert_direction {
+                        direction.opposite()
+                    } else {
+                        direction
+                    };
+
                     // A new motion has been started. This might override an
                     // ongoing one, but it makes no difference here.
                     //
                     // Let's update the state, but don't return just yet. We
                     // have more stuff to do (polling the future).
                     state = State::SetDirection(SetDirectionFuture::new(
-                        direction, driver, timer,
+                        signal, driver, timer,
                     ));
                     *current_direction = direction;
+                    transitions += 1;
                     continue;
                 }
 
                 // No new motion has been started, but we might still have an
-                // ongoing one. Let's ask the motion profile.
-                if let Some(delay) = profile.next_delay() {
+                // ongoing one. Let's ask the motion profile, unless we already
+                // have while peeking.
+                let delay = match next_delay.take() {
+                    Some(delay) => Some(delay),
+                    None => profile.next_delay(),
+                };
+                if let Some(delay) = delay {
                     // There's a motion ongoing. Let's start the next step, but
                     // again, don't return yet. The future needs to be polled.
+                    let pulse_length =
+                        pulse_length.unwrap_or_else(|| driver.pulse_length());
                     state = State::Step {
-                        future: StepFuture::new(driver, timer),
+                        future: StepFuture::new(driver, timer)
+                            .with_pulse_length(pulse_length),
                         delay,
                     };
+                    transitions += 1;
                     continue;
                 }
 
@@ -102,11 +192,14 @@ where
             State::SetDirection(mut future) => {
                 match future.poll() {
                     Poll::Ready(Ok(())) => {
-                        // Direction has been set. Set state back to idle, so we
-                        // can figure out what to do next in the next loop
-                        // iteration.
+                        // Direction has been set, and the future has waited for
+                        // the DIR setup time, so it's safe to step now. Set
+                        // state back to idle, so we can figure out what to do
+                        // next in the next loop iteration.
                         let (driver, timer) = future.release();
+                        timing.set_direction(driver.setup_time());
                         state = State::Idle { driver, timer };
+                        transitions += 1;
                         continue;
                     }
                     Poll::Ready(Err(err)) => {
@@ -131,32 +224,48 @@ where
                     Poll::Ready(Ok(())) => {
                         // A step was made. Now we need to wait out the rest of
                         // the step delay before we can do something else.
-
-                        *current_step += *current_direction as i32;
-
+                        //
+                        // Steps are only counted once their pulse has
+                        // completed, so a pulse that is cut short by `abort`
+                        // never is.
                         let (driver, mut timer) = future.release();
-                        let delay_left: Timer::Time = match delay_left(
+                        let delay_left = match account_step(
+                            &driver,
                             delay,
-                            Driver::PULSE_LENGTH,
+                            current_step,
+                            *current_direction,
+                            pulse_length,
                             convert,
+                            on_step,
+                            move_ticks.as_deref_mut(),
+                            rotary_wrap.as_mut(),
+                            timing,
                         ) {
                             Ok(delay_left) => delay_left,
                             Err(err) => {
                                 return (
-                                    Err(Error::TimeConversion(err)),
+                                    Err(err),
                                     State::Idle { driver, timer },
                                 )
                             }
                         };
 
                         if let Err(err) = timer.start(delay_left) {
+                            // The step has been counted already, so don't go
+                            // back to it. See `State::StartStepDelay`.
                             return (
                                 Err(Error::StepDelay(err)),
-                                State::Idle { driver, timer },
+                                State::StartStepDelay {
+                                    driver,
+                                    timer,
+                                    ticks: delay_left,
+                                },
                             );
                         }
 
+                        timing.step_delay(delay_left);
                         state = State::StepDelay { driver, timer };
+                        transitions += 1;
                         continue;
                     }
                     Poll::Ready(Err(err)) => {
@@ -176,12 +285,38 @@ where
                     }
                 }
             }
+            State::StartStepDelay {
+                driver,
+                mut timer,
+                ticks,
+            } => {
+                // `CountDown::start` isn't an `nb` operation in the version of
+                // `embedded-hal` we use, so a timer that can't be started right
+                // now has to return an error. The state stays as it is, so the
+                // next call retries, instead of skipping the step delay.
+                if let Err(err) = timer.start(ticks) {
+                    return (
+                        Err(Error::StepDelay(err)),
+                        State::StartStepDelay {
+                            driver,
+                            timer,
+                            ticks,
+                        },
+                    );
+                }
+
+                timing.step_delay(ticks);
+                state = State::StepDelay { driver, timer };
+                transitions += 1;
+                continue;
+            }
             State::StepDelay { driver, mut timer } => {
                 match timer.wait() {
                     Ok(()) => {
                         // We've waited out the step delay. Return to idle
                         // state, to figure out what's next.
                         state = State::Idle { driver, timer };
+                        transitions += 1;
                         continue;
                     }
                     Err(nb::Error::WouldBlock) => {
@@ -204,35 +339,346 @@ where
                 //
                 // A panic in this closure is always going to be a
                 // bug, and once that happened, we're in an invalid
-                // state. Not a lot we can do about it.
-                panic!("Invalid internal state, caused by a previous panic.")
+                // state. Not a lot we can do about it, except letting the
+                // caller know.
+                return (Err(Error::InvalidState), State::Invalid);
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub fn finish_step<Driver, Timer, Profile, Convert, Counter, Hook>(
+    state: State<Driver, Timer, Profile>,
+    current_step: &mut Counter,
+    current_direction: Direction,
+    pulse_length: Option<Nanoseconds>,
+    convert: &Convert,
+    on_step: &mut Hook,
+    move_ticks: Option<&mut Option<Timer::Time>>,
+    mut rotary_wrap: Option<RotaryWrap<Counter>>,
+    timing: &mut impl RecordTiming<Timer::Time>,
+) -> (
+    Result<
+        (),
+        Error<
+            <Driver as SetDirection>::Error,
+            <<Driver as SetDirection>::Dir as OutputPin>::Error,
+            <Driver as Step>::Error,
+            <<Driver as Step>::Step as OutputPin>::Error,
+            Timer::Error,
+            <Timer::Time as TryFrom<Nanoseconds>>::Error,
+            Convert::Error,
+        >,
+    >,
+    State<Driver, Timer, Profile>,
+)
+where
+    Driver: SetDirection + Step,
+    Timer: timer::CountDown,
+    Profile: MotionProfile,
+    Convert: DelayToTicks<Profile::Delay, Ticks = Timer::Time>,
+    Convert::Ticks: TryFrom<Nanoseconds>
+        + ops::Add<Output = Convert::Ticks>
+        + ops::Sub<Output = Convert::Ticks>
+        + PartialOrd
+        + Copy,
+    Counter: StepCounter,
+    Hook: OnStep<Counter>,
+{
+    match state {
+        State::Step { mut future, delay } => match future.wait() {
+            Ok(()) => {
+                // The step has been made, and is accounted for the same way
+                // as in `update`. The step delay isn't started, as the motion
+                // ends here.
+                let (driver, timer) = future.release();
+                let result = account_step(
+                    &driver,
+                    delay,
+                    current_step,
+                    current_direction,
+                    pulse_length,
+                    convert,
+                    on_step,
+                    move_ticks,
+                    rotary_wrap.as_mut(),
+                    timing,
+                )
+                .map(|_| ());
+                (result, State::Idle { driver, timer })
+            }
+            Err(err) => (Err(Error::Step(err)), State::Step { future, delay }),
+        },
+        state => (Ok(()), state),
+    }
+}
+
+/// Account for a step that has been made
+///
+/// Counts the step, passes it to the hook, and adds its length to the ticks
+/// of the move. Returns the delay left until the next step. This is shared by
+/// `update` and `finish_step`, so a step counts the same, whichever of them
+/// completes its pulse.
+///
+/// The step is counted and passed to the hook first, as it has been made, even
+/// if its delay turns out to be invalid.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+fn account_step<
+    Driver,
+    Delay,
+    Convert,
+    Counter,
+    Hook,
+    SetDirectionError,
+    DirPinError,
+    StepError,
+    StepPinError,
+    TimerError,
+>(
+    driver: &Driver,
+    delay: Delay,
+    current_step: &mut Counter,
+    current_direction: Direction,
+    pulse_length: Option<Nanoseconds>,
+    convert: &Convert,
+    on_step: &mut Hook,
+    move_ticks: Option<&mut Option<Convert::Ticks>>,
+    rotary_wrap: Option<&mut RotaryWrap<Counter>>,
+    timing: &mut impl RecordTiming<Convert::Ticks>,
+) -> Result<
+    Convert::Ticks,
+    Error<
+        SetDirectionError,
+        DirPinError,
+        StepError,
+        StepPinError,
+        TimerError,
+        <Convert::Ticks as TryFrom<Nanoseconds>>::Error,
+        Convert::Error,
+    >,
+>
+where
+    Driver: Step,
+    Convert: DelayToTicks<Delay>,
+    Convert::Ticks: TryFrom<Nanoseconds>
+        + ops::Add<Output = Convert::Ticks>
+        + ops::Sub<Output = Convert::Ticks>
+        + PartialOrd
+        + Copy,
+    Counter: StepCounter,
+    Hook: OnStep<Counter>,
+{
+    // A rotation can go on for long enough to overflow the position.
+    count_step(current_step, current_direction, rotary_wrap);
+    on_step.on_step(*current_step, current_direction);
+
+    let (delay_left, step_ticks) = delay_left(
+        delay,
+        pulse_length.unwrap_or_else(|| driver.pulse_length()),
+        driver.low_time(),
+        convert,
+    )
+    .map_err(|err| match err {
+        DelayLeftError::TimeConversion(err) => Error::TimeConversion(err),
+        DelayLeftError::TooShort => Error::DelayTooShort,
+        DelayLeftError::Zero => Error::ZeroDelay,
+        DelayLeftError::TimerResolution => Error::SpeedExceedsTimerResolution,
+    })?;
+
+    if let Some(move_ticks) = move_ticks {
+        *move_ticks = Some(match move_ticks.take() {
+            Some(ticks) => ticks + step_ticks,
+            None => step_ticks,
+        });
+    }
+    timing.step(step_ticks - delay_left);
+
+    Ok(delay_left)
+}
+
+/// Records how many ticks are spent in each phase of a motion
+///
+/// Does nothing by default, and for `()`, which is used, unless the
+/// `timing-stats` feature is enabled. See [`TimingStats`].
+///
+/// [`TimingStats`]: super::TimingStats
+pub trait RecordTiming<Ticks> {
+    /// The DIR signal has been set, and its setup time waited for
+    fn set_direction(&mut self, _setup_time: Nanoseconds) {}
+
+    /// A STEP pulse of the given length has been completed
+    fn step(&mut self, _ticks: Ticks) {}
+
+    /// The timer has been started for the delay until the next step
+    fn step_delay(&mut self, _ticks: Ticks) {}
+}
+
+impl<Ticks> RecordTiming<Ticks> for () {}
+
+/// Wraps the position of a rotary axis
+///
+/// See [`SoftwareMotionControl::set_rotary_wrap`].
+///
+/// [`SoftwareMotionControl::set_rotary_wrap`]:
+///     super::SoftwareMotionControl::set_rotary_wrap
+pub struct RotaryWrap<'r, Counter> {
+    pub steps_per_revolution: Counter,
+
+    /// The target of the ongoing move, which is wrapped along with the
+    /// position, or `None` while rotating
+    pub target_step: Option<&'r mut Counter>,
+}
+
+pub fn count_step<Counter>(
+    current_step: &mut Counter,
+    direction: Direction,
+    rotary_wrap: Option<&mut RotaryWrap<Counter>>,
+) where
+    Counter: StepCounter,
+{
+    *current_step = current_step.wrapping_step(direction);
+
+    let rotary_wrap = match rotary_wrap {
+        Some(rotary_wrap) => rotary_wrap,
+        None => return,
+    };
+
+    // The position changes by one step at a time, so it can be off by at most
+    // one revolution.
+    let zero = Counter::from(0);
+    let offset = if *current_step >= rotary_wrap.steps_per_revolution {
+        zero - rotary_wrap.steps_per_revolution
+    } else if *current_step < zero {
+        rotary_wrap.steps_per_revolution
+    } else {
+        return;
+    };
+
+    *current_step = *current_step + offset;
+    if let Some(target_step) = &mut rotary_wrap.target_step {
+        **target_step = **target_step + offset;
+    }
+}
+
+pub fn abort<Driver, Timer, Profile>(
+    state: State<Driver, Timer, Profile>,
+) -> State<Driver, Timer, Profile>
+where
+    Driver: SetDirection + Step,
+    Timer: timer::CountDown,
+    Timer::Time: TryFrom<Nanoseconds>,
+    Profile: MotionProfile,
+{
+    match state {
+        State::Idle { driver, timer } => State::Idle { driver, timer },
+        State::SetDirection(future) => {
+            let (driver, timer) = future.release();
+            State::Idle { driver, timer }
+        }
+        State::Step { future, .. } => {
+            // The STEP pulse might be cut short here. It's up to the caller to
+            // make sure the pin ends up in a defined state. The step isn't
+            // counted, as that only happens once the pulse has finished.
+            let (driver, timer) = future.release();
+            State::Idle { driver, timer }
+        }
+        State::StartStepDelay { driver, timer, .. }
+        | State::StepDelay { driver, timer } => State::Idle { driver, timer },
+        // There's nothing left to abort. Callers check for this beforehand,
+        // to report `Error::InvalidState`.
+        State::Invalid => State::Invalid,
+    }
+}
+
+/// Returns the delay left after the STEP pulse, and the length of the whole
+/// step, including the pulse
+#[allow(clippy::type_complexity)]
 fn delay_left<Delay, Convert>(
     delay: Delay,
     pulse_length: Nanoseconds,
+    low_time: Nanoseconds,
     convert: &Convert,
 ) -> Result<
-    Convert::Ticks,
-    TimeConversionError<
+    (Convert::Ticks, Convert::Ticks),
+    DelayLeftError<
         <Convert::Ticks as TryFrom<Nanoseconds>>::Error,
         Convert::Error,
     >,
 >
 where
     Convert: DelayToTicks<Delay>,
-    Convert::Ticks: TryFrom<Nanoseconds> + ops::Sub<Output = Convert::Ticks>,
+    Convert::Ticks: TryFrom<Nanoseconds>
+        + ops::Add<Output = Convert::Ticks>
+        + ops::Sub<Output = Convert::Ticks>
+        + PartialOrd
+        + Copy,
 {
-    let delay: Convert::Ticks = convert
-        .delay_to_ticks(delay)
-        .map_err(|err| TimeConversionError::DelayToTicks(err))?;
-    let pulse_length: Convert::Ticks = pulse_length
-        .try_into()
-        .map_err(|err| TimeConversionError::NanosecondsToTicks(err))?;
+    let delay: Convert::Ticks =
+        convert.delay_to_ticks(delay).map_err(|err| {
+            DelayLeftError::TimeConversion(TimeConversionError::DelayToTicks(
+                err,
+            ))
+        })?;
+    let pulse_length: Convert::Ticks = nanoseconds_to_ticks(pulse_length)?;
+    let low_time: Convert::Ticks = nanoseconds_to_ticks(low_time)?;
+    let zero: Convert::Ticks = nanoseconds_to_ticks(Nanoseconds(0))?;
+
+    // Depending on the timer, starting it with zero ticks might make it finish
+    // immediately or never. Either way, it's not what the motion profile was
+    // meant to do.
+    if delay <= zero {
+        return Err(DelayLeftError::Zero);
+    }
 
+    // The subtraction below would underflow for unsigned tick types, resulting
+    // in a huge delay.
+    if delay < pulse_length {
+        return Err(DelayLeftError::TooShort);
+    }
+
+    // The delay rounded to the same number of ticks as the STEP pulse, which
+    // leaves no time between steps. The timer is too coarse for this speed.
+    // This needs to be checked before the low time is taken into account
+    // below, or the speed would silently be capped.
     let delay_left = delay - pulse_length;
-    Ok(delay_left)
+    if delay_left <= zero {
+        return Err(DelayLeftError::TimerResolution);
+    }
+
+    // The driver needs the STEP pin to be idle for some time, before the next
+    // pulse can start. If the motion profile asks for less than that, the
+    // motor will go slower than requested.
+    if delay_left < low_time {
+        return Ok((low_time, pulse_length + low_time));
+    }
+
+    Ok((delay_left, pulse_length + delay_left))
+}
+
+fn nanoseconds_to_ticks<Ticks, DelayToTicksError>(
+    nanoseconds: Nanoseconds,
+) -> Result<Ticks, DelayLeftError<Ticks::Error, DelayToTicksError>>
+where
+    Ticks: TryFrom<Nanoseconds>,
+{
+    nanoseconds.try_into().map_err(|err| {
+        DelayLeftError::TimeConversion(
+            TimeConversionError::NanosecondsToTicks {
+                nanoseconds: nanoseconds.0,
+                error: err,
+            },
+        )
+    })
+}
+
+enum DelayLeftError<NanosecondsToTicksError, DelayToTicksError> {
+    TimeConversion(
+        TimeConversionError<NanosecondsToTicksError, DelayToTicksError>,
+    ),
+    TooShort,
+    Zero,
+    TimerResolution,
 }
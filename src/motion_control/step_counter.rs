@@ -0,0 +1,70 @@
+use core::ops;
+
+use crate::Direction;
+
+/// The type used by [`SoftwareMotionControl`] to count steps
+///
+/// `SoftwareMotionControl` uses `i32` by default. Each step is counted with
+/// wrapping arithmetic, so the position of a motor that keeps rotating in one
+/// direction wraps from `i32::MAX` to `i32::MIN` after around 2 billion steps.
+/// Applications where that is a concern can use `i64` instead, which won't
+/// overflow within any realistic runtime. See
+/// [`SoftwareMotionControl::with_step_counter`].
+///
+/// This trait is implemented for `i32` and `i64`.
+///
+/// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+/// [`SoftwareMotionControl::with_step_counter`]:
+///     super::SoftwareMotionControl::with_step_counter
+pub trait StepCounter:
+    Copy + Ord + From<i32> + ops::Add<Output = Self> + ops::Sub<Output = Self>
+{
+    /// Count one step in the given direction, wrapping around on overflow
+    fn wrapping_step(self, direction: Direction) -> Self;
+
+    /// The number of steps between `self` and `other`
+    ///
+    /// Saturates at `u32::MAX`.
+    fn distance(self, other: Self) -> u32;
+
+    /// Convert to `i32`, wrapping around, if the value doesn't fit
+    fn wrapping_to_i32(self) -> i32;
+
+    /// Mirror `self` at `center`
+    ///
+    /// Returns the position that is as far from `center` as `self`, but on the
+    /// other side. Saturates at the bounds of the type.
+    fn saturating_mirror(self, center: Self) -> Self;
+}
+
+macro_rules! impl_step_counter {
+    ($($ty:ty,)*) => {
+        $(
+            impl StepCounter for $ty {
+                fn wrapping_step(self, direction: Direction) -> Self {
+                    self.wrapping_add(direction as $ty)
+                }
+
+                fn distance(self, other: Self) -> u32 {
+                    // Can't overflow, as `i128` is wider than any of the
+                    // types this is implemented for.
+                    let distance = (self as i128 - other as i128).unsigned_abs();
+                    distance.min(u32::MAX as u128) as u32
+                }
+
+                fn wrapping_to_i32(self) -> i32 {
+                    self as i32
+                }
+
+                fn saturating_mirror(self, center: Self) -> Self {
+                    // Can't overflow, for the same reason as above.
+                    let mirrored = 2 * center as i128 - self as i128;
+                    mirrored.clamp(<$ty>::MIN as i128, <$ty>::MAX as i128)
+                        as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_step_counter!(i32, i64,);
@@ -0,0 +1,78 @@
+use core::{convert::TryFrom, ops};
+
+use embedded_time::duration::Nanoseconds;
+
+use super::state::RecordTiming;
+
+/// How long [`SoftwareMotionControl`] spent in each phase of its motions
+///
+/// Adds up the timer ticks [`MotionControl::update`] waits in each phase: the
+/// DIR setup time before a motion, the STEP pulses, and the delays between
+/// steps. Together with the time an `update` call takes, this helps to decide
+/// on a polling frequency, or whether to use interrupts instead.
+///
+/// These are the ticks the timer is started with, not measured time. Like
+/// [`MotionStats::total_ticks`], the sums are not protected against overflow,
+/// which can happen after a while, depending on the timer's tick type. Each
+/// sum is `None`, until its phase has been entered once.
+///
+/// Only available with the `timing-stats` feature. See
+/// [`SoftwareMotionControl::timing_stats`].
+///
+/// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+/// [`SoftwareMotionControl::timing_stats`]:
+///     super::SoftwareMotionControl::timing_stats
+/// [`MotionControl::update`]: crate::traits::MotionControl::update
+/// [`MotionStats::total_ticks`]: super::MotionStats::total_ticks
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimingStats<Ticks> {
+    /// Ticks spent waiting for the DIR setup time
+    pub set_direction: Option<Ticks>,
+
+    /// Ticks spent in STEP pulses
+    pub step: Option<Ticks>,
+
+    /// Ticks spent in the delays between two steps
+    pub step_delay: Option<Ticks>,
+}
+
+impl<Ticks> Default for TimingStats<Ticks> {
+    fn default() -> Self {
+        Self {
+            set_direction: None,
+            step: None,
+            step_delay: None,
+        }
+    }
+}
+
+impl<Ticks> RecordTiming<Ticks> for TimingStats<Ticks>
+where
+    Ticks: TryFrom<Nanoseconds> + ops::Add<Output = Ticks> + Copy,
+{
+    fn set_direction(&mut self, setup_time: Nanoseconds) {
+        // If it didn't convert, the DIR signal would have failed already.
+        if let Ok(ticks) = Ticks::try_from(setup_time) {
+            add(&mut self.set_direction, ticks);
+        }
+    }
+
+    fn step(&mut self, ticks: Ticks) {
+        add(&mut self.step, ticks);
+    }
+
+    fn step_delay(&mut self, ticks: Ticks) {
+        add(&mut self.step_delay, ticks);
+    }
+}
+
+fn add<Ticks>(sum: &mut Option<Ticks>, ticks: Ticks)
+where
+    Ticks: ops::Add<Output = Ticks> + Copy,
+{
+    *sum = Some(match *sum {
+        Some(sum) => sum + ticks,
+        None => ticks,
+    });
+}
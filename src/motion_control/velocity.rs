@@ -0,0 +1,272 @@
+use core::ops;
+
+use crate::step_mode::StepMode;
+
+/// Converts common velocity units into steps per timer tick
+///
+/// The velocity passed to [`MotionControl`] methods like
+/// [`MotionControl::move_to_position`] and [`MotionControl::rotate`] is passed
+/// on to the motion profile. When using [`SoftwareMotionControl`], it is
+/// expected in steps (or microsteps, depending on the step mode) per timer
+/// tick. This converts revolutions per minute and steps per second into that
+/// unit, based on the motor's steps per revolution, the step mode, and the
+/// frequency of the timer (`TIMER_HZ`).
+///
+/// ``` rust
+/// use stepper::{motion_control::VelocityConverter, step_mode::StepMode16};
+///
+/// // A motor with 1.8° per step (200 full steps per revolution), driven with
+/// // 16 microsteps per step, using a 1 MHz timer.
+/// let convert = VelocityConverter::<1_000_000>::new(200, StepMode16::M16);
+///
+/// // 60 RPM are 3200 microsteps per second, or 0.0032 microsteps per tick.
+/// let velocity: f32 = convert.rpm(60.0);
+/// assert!((velocity - 0.0032).abs() < 1e-9);
+/// ```
+///
+/// [`MotionControl`]: crate::traits::MotionControl
+/// [`MotionControl::move_to_position`]: crate::traits::MotionControl::move_to_position
+/// [`MotionControl::rotate`]: crate::traits::MotionControl::rotate
+/// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VelocityConverter<const TIMER_HZ: u32> {
+    steps_per_revolution: u32,
+}
+
+impl<const TIMER_HZ: u32> VelocityConverter<TIMER_HZ> {
+    /// Create a new instance of `VelocityConverter`
+    ///
+    /// `full_steps_per_revolution` is the number of full steps the motor makes
+    /// per revolution (for example `200` for a motor with 1.8° per step).
+    /// `step_mode` is the step mode the driver is configured with.
+    pub fn new<Mode>(full_steps_per_revolution: u32, step_mode: Mode) -> Self
+    where
+        Mode: StepMode,
+    {
+        let microsteps: u16 = step_mode.into();
+
+        Self {
+            steps_per_revolution: full_steps_per_revolution
+                * u32::from(microsteps),
+        }
+    }
+
+    /// The number of steps per revolution, including microsteps
+    pub fn steps_per_revolution(&self) -> u32 {
+        self.steps_per_revolution
+    }
+
+    /// Convert revolutions per minute into steps per timer tick
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the steps per revolution or the timer frequency can't be
+    /// represented by `Num`.
+    pub fn rpm<Num>(&self, rpm: Num) -> Num
+    where
+        Num: num_traits::NumCast
+            + ops::Mul<Output = Num>
+            + ops::Div<Output = Num>,
+    {
+        // Multiply first, to not lose precision with fixed-point numbers.
+        let steps_per_minute = rpm * num(self.steps_per_revolution);
+        self.steps_per_second(steps_per_minute / num(60))
+    }
+
+    /// Convert steps per second into steps per timer tick
+    ///
+    /// Since the steps per revolution are not needed for this conversion,
+    /// steps per second are taken to include microsteps.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the timer frequency can't be represented by `Num`.
+    pub fn steps_per_second<Num>(&self, steps_per_second: Num) -> Num
+    where
+        Num: num_traits::NumCast + ops::Div<Output = Num>,
+    {
+        steps_per_second / num(TIMER_HZ)
+    }
+}
+
+/// A speed, in steps per second
+///
+/// Motion profiles expect velocities in steps per timer tick, which makes it
+/// easy to pass a value in the wrong unit. `Speed` makes the unit explicit and
+/// converts into the profile's representation, once the timer frequency
+/// (`TIMER_HZ`) is known.
+///
+/// As with [`VelocityConverter::steps_per_second`], steps include microsteps.
+///
+/// ``` rust
+/// use stepper::motion_control::Speed;
+///
+/// // 1000 steps per second are 0.001 steps per tick, at 1 MHz.
+/// let velocity: f32 = Speed::steps_per_second(1000.0).per_tick::<1_000_000>();
+/// assert!((velocity - 0.001).abs() < 1e-9);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Speed<Num>(Num);
+
+impl<Num> Speed<Num> {
+    /// Create a speed from steps per second
+    pub fn steps_per_second(steps_per_second: Num) -> Self {
+        Self(steps_per_second)
+    }
+
+    /// Return the speed in steps per second
+    pub fn to_steps_per_second(self) -> Num {
+        self.0
+    }
+
+    /// Convert the speed into steps per timer tick
+    ///
+    /// This is the unit that [`SoftwareMotionControl`] expects for the maximum
+    /// velocity of a motion.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the timer frequency can't be represented by `Num`.
+    ///
+    /// [`SoftwareMotionControl`]: super::SoftwareMotionControl
+    pub fn per_tick<const TIMER_HZ: u32>(self) -> Num
+    where
+        Num: num_traits::NumCast + ops::Div<Output = Num>,
+    {
+        self.0 / num(TIMER_HZ)
+    }
+}
+
+/// An acceleration, in steps per second squared
+///
+/// Like [`Speed`], but for the acceleration that motion profiles like
+/// [`ramp_maker::Trapezoidal`] are configured with. Those expect steps per
+/// timer tick squared.
+///
+/// ``` rust
+/// use stepper::motion_control::Acceleration;
+///
+/// // 1000 steps per second squared are 0.000_000_001 steps per tick squared,
+/// // at 1 MHz.
+/// let acceleration: f64 = Acceleration::steps_per_second_squared(1000.0)
+///     .per_tick_squared::<1_000_000>();
+/// assert!((acceleration - 0.000_000_001).abs() < 1e-18);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Acceleration<Num>(Num);
+
+impl<Num> Acceleration<Num> {
+    /// Create an acceleration from steps per second squared
+    pub fn steps_per_second_squared(steps_per_second_squared: Num) -> Self {
+        Self(steps_per_second_squared)
+    }
+
+    /// Return the acceleration in steps per second squared
+    pub fn to_steps_per_second_squared(self) -> Num {
+        self.0
+    }
+
+    /// Convert the acceleration into steps per timer tick squared
+    ///
+    /// # Panics
+    ///
+    /// Panics, if the timer frequency can't be represented by `Num`.
+    pub fn per_tick_squared<const TIMER_HZ: u32>(self) -> Num
+    where
+        Num: num_traits::NumCast + ops::Div<Output = Num>,
+    {
+        // Divide twice, as the squared frequency might not fit into `u32`.
+        self.0 / num(TIMER_HZ) / num(TIMER_HZ)
+    }
+}
+
+fn num<Num: num_traits::NumCast>(value: u32) -> Num {
+    Num::from(value).expect("Value can't be represented by number type")
+}
+
+#[cfg(test)]
+mod tests {
+    use ramp_maker::{Flat, MotionProfile as _};
+
+    use crate::step_mode::{StepMode16, StepMode256};
+
+    use super::{Acceleration, Speed, VelocityConverter};
+
+    #[test]
+    fn rpm_should_result_in_expected_delay_for_flat_profile() {
+        // 300 RPM with 200 full steps per revolution are 1000 steps per second,
+        // or 1000 ticks per step at 1 MHz.
+        let convert =
+            VelocityConverter::<1_000_000>::new(200, StepMode256::Full);
+
+        let mut profile = Flat::<f32>::new();
+        profile.enter_position_mode(convert.rpm(300.0), 2);
+
+        for _ in 0..2 {
+            let delay = profile.next_delay().unwrap();
+            assert!((delay - 1000.0).abs() < 0.01, "delay: {}", delay);
+        }
+        assert_eq!(profile.next_delay(), None);
+    }
+
+    #[test]
+    fn steps_per_revolution_should_include_microsteps() {
+        let convert = VelocityConverter::<1_000_000>::new(200, StepMode16::M16);
+
+        assert_eq!(convert.steps_per_revolution(), 3200);
+        assert_eq!(convert.rpm(60.0f32), convert.steps_per_second(3200.0f32));
+    }
+
+    #[test]
+    fn speed_should_convert_into_steps_per_tick() {
+        let cases = [(1000.0, 0.001), (3200.0, 0.0032), (0.0, 0.0)];
+        for (steps_per_second, steps_per_tick) in IntoIterator::into_iter(cases)
+        {
+            let speed = Speed::steps_per_second(steps_per_second);
+            let velocity: f64 = speed.per_tick::<1_000_000>();
+            assert!((velocity - steps_per_tick).abs() < 1e-12);
+        }
+
+        // 1000 steps per second are 1 step per tick at 1 kHz.
+        let velocity: f64 = Speed::steps_per_second(1000.0).per_tick::<1000>();
+        assert!((velocity - 1.0).abs() < 1e-12);
+
+        let convert = VelocityConverter::<1_000_000>::new(200, StepMode16::M16);
+        assert_eq!(
+            Speed::steps_per_second(3200.0f32).per_tick::<1_000_000>(),
+            convert.steps_per_second(3200.0f32),
+        );
+    }
+
+    #[test]
+    fn acceleration_should_convert_into_steps_per_tick_squared() {
+        let cases = [(1000.0, 0.000_000_001), (2.0e6, 0.000_002)];
+        for (per_second_squared, per_tick_squared) in
+            IntoIterator::into_iter(cases)
+        {
+            let acceleration =
+                Acceleration::steps_per_second_squared(per_second_squared);
+            let value: f64 = acceleration.per_tick_squared::<1_000_000>();
+            assert!((value - per_tick_squared).abs() < 1e-18);
+        }
+
+        // The squared frequency doesn't fit into `u32`.
+        let value: f64 = Acceleration::steps_per_second_squared(16.0e18)
+            .per_tick_squared::<4_000_000_000>();
+        assert!((value - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn speed_should_result_in_expected_delay_for_flat_profile() {
+        let mut profile = Flat::<f32>::new();
+        profile.enter_position_mode(
+            Speed::steps_per_second(500.0).per_tick::<1_000_000>(),
+            1,
+        );
+
+        let delay = profile.next_delay().unwrap();
+        assert!((delay - 2000.0).abs() < 0.01, "delay: {}", delay);
+    }
+}
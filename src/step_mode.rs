@@ -1,6 +1,6 @@
 //! Types related to working with a driver's microstepping mode
 
-use core::convert::TryFrom;
+use core::{convert::TryFrom, fmt};
 
 use paste::paste;
 
@@ -16,6 +16,20 @@ pub trait StepMode:
     /// Starts at the mode for configuring full steps and ends at the highest
     /// supported number of microsteps per step.
     fn iter() -> Self::Iter;
+
+    /// Returns the mode with the highest number of microsteps per step
+    fn finest() -> Self {
+        Self::iter()
+            .max_by_key(|&mode| mode.into())
+            .expect("Every step mode supports full steps")
+    }
+
+    /// Returns the mode for configuring full steps
+    fn coarsest() -> Self {
+        Self::iter()
+            .min_by_key(|&mode| mode.into())
+            .expect("Every step mode supports full steps")
+    }
 }
 
 macro_rules! generate_step_mode_enums {
@@ -121,7 +135,17 @@ macro_rules! generate_step_mode_enums {
                 "Defines the microstepping mode for drivers with a resolution \
                 of up to " $max " microsteps"
             ]
-            #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+            #[doc = ""]
+            #[doc =
+                "With the `serde` feature enabled, this is serialized as the \
+                number of microsteps per full step (see the `u16` conversions)."
+            ]
+            #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Ord, PartialOrd)]
+            #[cfg_attr(
+                feature = "serde",
+                derive(serde::Serialize, serde::Deserialize),
+                serde(into = "u16", try_from = "u16")
+            )]
             pub enum [<StepMode $max>] {
                 $($variant_output)*
             }
@@ -202,14 +226,20 @@ generate_step_mode_enums! {
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct InvalidStepModeError;
 
+impl fmt::Display for InvalidStepModeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid step mode")
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    // Only tests `StepMode256`. This should be fine, since all other step mode
+    // Mostly tests `StepMode256`. This should be fine, since all other step mode
     // enums are generated by the same code.
 
     use core::convert::TryFrom;
 
-    use super::{StepMode as _, StepMode256};
+    use super::{InvalidStepModeError, StepMode, StepMode16, StepMode256};
 
     #[test]
     fn step_mode_should_convert_into_microsteps_per_step() {
@@ -241,6 +271,28 @@ mod tests {
         assert_eq!(<StepMode256 as TryFrom<u16>>::try_from(256), Ok(M256));
     }
 
+    #[test]
+    fn step_mode_should_reject_unsupported_microsteps_per_step() {
+        assert_eq!(
+            <StepMode256 as TryFrom<u16>>::try_from(0),
+            Err(InvalidStepModeError)
+        );
+        assert_eq!(
+            <StepMode256 as TryFrom<u16>>::try_from(3),
+            Err(InvalidStepModeError)
+        );
+        assert_eq!(
+            <StepMode256 as TryFrom<u16>>::try_from(512),
+            Err(InvalidStepModeError)
+        );
+
+        // Values beyond the resolution of a smaller enum are rejected too.
+        assert_eq!(
+            <StepMode16 as TryFrom<u16>>::try_from(32),
+            Err(InvalidStepModeError)
+        );
+    }
+
     #[test]
     fn step_mode_should_provide_iterator_over_modes() {
         use StepMode256::*;
@@ -248,4 +300,41 @@ mod tests {
         let modes: Vec<_> = StepMode256::iter().collect();
         assert_eq!(modes, [Full, M2, M4, M8, M16, M32, M64, M128, M256]);
     }
+
+    #[test]
+    fn step_mode_should_provide_finest_and_coarsest_mode() {
+        assert_eq!(StepMode256::finest(), StepMode256::M256);
+        assert_eq!(StepMode256::coarsest(), StepMode256::Full);
+
+        // A driver that supports up to 1/16 steps.
+        assert_eq!(<StepMode16 as Into<u16>>::into(StepMode16::finest()), 16);
+        assert_eq!(<StepMode16 as Into<u16>>::into(StepMode16::coarsest()), 1);
+    }
+
+    #[test]
+    fn step_mode_should_be_usable_as_map_key() {
+        let microsteps: std::collections::HashMap<_, u16> = StepMode256::iter()
+            .map(|step_mode| (step_mode, step_mode.into()))
+            .collect();
+
+        assert_eq!(microsteps.len(), 9);
+        assert_eq!(microsteps[&StepMode256::Full], 1);
+        assert_eq!(microsteps[&StepMode256::M256], 256);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn step_mode_should_round_trip_through_serde() {
+        for step_mode in StepMode256::iter() {
+            let json = serde_json::to_string(&step_mode).unwrap();
+            let microsteps: u16 = step_mode.into();
+            assert_eq!(json, microsteps.to_string());
+
+            let deserialized: StepMode256 =
+                serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized, step_mode);
+        }
+
+        assert!(serde_json::from_str::<StepMode256>("3").is_err());
+    }
 }
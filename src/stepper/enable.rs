@@ -0,0 +1,166 @@
+use core::{
+    convert::{Infallible, TryFrom, TryInto as _},
+    task::Poll,
+};
+
+use embedded_hal::timer::nb as timer;
+use embedded_time::duration::Nanoseconds;
+
+use crate::traits::SetEnable;
+
+use super::SignalError;
+
+/// The "future" returned by [`Stepper::enable`]
+///
+/// Please note that this type provides a custom API and does not implement
+/// [`core::future::Future`]. This might change, when using futures for embedded
+/// development becomes more practical.
+///
+/// [`Stepper::enable`]: crate::Stepper::enable
+#[must_use]
+pub struct EnableFuture<Driver, Timer> {
+    driver: Driver,
+    timer: Timer,
+    state: State,
+}
+
+impl<Driver, Timer> EnableFuture<Driver, Timer>
+where
+    Driver: SetEnable,
+    Timer: timer::CountDown,
+    Timer::Time: TryFrom<Nanoseconds>,
+{
+    /// Create new instance of `EnableFuture`
+    ///
+    /// This constructor is public to provide maximum flexibility for
+    /// non-standard use cases. Most users can ignore this and just use
+    /// [`Stepper::enable`] instead.
+    ///
+    /// [`Stepper::enable`]: crate::Stepper::enable
+    pub fn new(driver: Driver, timer: Timer) -> Self {
+        Self {
+            driver,
+            timer,
+            state: State::Initial,
+        }
+    }
+
+    /// Poll the future
+    ///
+    /// The future must be polled for the operation to make progress. The
+    /// operation won't start, until this method has been called once. Returns
+    /// [`Poll::Pending`], if the operation is not finished yet, or
+    /// [`Poll::Ready`], once it is.
+    ///
+    /// If this method returns [`Poll::Pending`], the user can opt to keep
+    /// calling it at a high frequency (see [`Self::wait`]) until the operation
+    /// completes, or set up an interrupt that fires once the timer finishes
+    /// counting down, and call this method again once it does.
+    #[allow(clippy::type_complexity)]
+    pub fn poll(
+        &mut self,
+    ) -> Poll<
+        Result<
+            (),
+            SignalError<
+                Infallible, // only applies to `SetDirection`, `Step`
+                Driver::Error,
+                <Timer::Time as TryFrom<Nanoseconds>>::Error,
+                Timer::Error,
+            >,
+        >,
+    > {
+        match self.state {
+            State::Initial => {
+                self.driver.enable().map_err(SignalError::Pin)?;
+
+                let ticks: Timer::Time =
+                    Driver::HOLD_TIME.try_into().map_err(|err| {
+                        SignalError::NanosecondsToTicks {
+                            nanoseconds: Driver::HOLD_TIME.0,
+                            error: err,
+                        }
+                    })?;
+                self.timer.start(ticks).map_err(SignalError::Timer)?;
+
+                self.state = State::Enabling;
+                Poll::Pending
+            }
+            State::Enabling => match self.timer.wait() {
+                Ok(()) => {
+                    self.state = State::Finished;
+                    Poll::Ready(Ok(()))
+                }
+                Err(nb::Error::Other(err)) => {
+                    self.state = State::Finished;
+                    Poll::Ready(Err(SignalError::Timer(err)))
+                }
+                Err(nb::Error::WouldBlock) => Poll::Pending,
+            },
+            State::Finished => Poll::Ready(Ok(())),
+        }
+    }
+
+    /// Wait until the operation completes
+    ///
+    /// This method will call [`Self::poll`] in a busy loop until the operation
+    /// has finished. See [`Self::wait_with`], if you need to do something
+    /// between calls.
+    #[allow(clippy::type_complexity)]
+    pub fn wait(
+        &mut self,
+    ) -> Result<
+        (),
+        SignalError<
+            Infallible, // only applies to `SetDirection`, `Step`
+            Driver::Error,
+            <Timer::Time as TryFrom<Nanoseconds>>::Error,
+            Timer::Error,
+        >,
+    > {
+        self.wait_with(|| {})
+    }
+
+    /// Wait until the operation completes, calling `idle` between polls
+    ///
+    /// Works like [`Self::wait`], except that `idle` is called every time
+    /// [`Self::poll`] returns [`Poll::Pending`]. See
+    /// [`StepFuture::wait_with`] for details.
+    ///
+    /// [`StepFuture::wait_with`]: crate::StepFuture::wait_with
+    #[allow(clippy::type_complexity)]
+    pub fn wait_with<F>(
+        &mut self,
+        mut idle: F,
+    ) -> Result<
+        (),
+        SignalError<
+            Infallible, // only applies to `SetDirection`, `Step`
+            Driver::Error,
+            <Timer::Time as TryFrom<Nanoseconds>>::Error,
+            Timer::Error,
+        >,
+    >
+    where
+        F: FnMut(),
+    {
+        loop {
+            if let Poll::Ready(result) = self.poll() {
+                return result;
+            }
+
+            idle();
+        }
+    }
+
+    /// Drop the future and release the resources that were moved into it
+    pub fn release(self) -> (Driver, Timer) {
+        (self.driver, self.timer)
+    }
+}
+
+enum State {
+    Initial,
+    Enabling,
+    Finished,
+}
@@ -1,3 +1,5 @@
+use core::fmt;
+
 use crate::motion_control;
 
 /// Unified error type
@@ -7,6 +9,7 @@ use crate::motion_control;
 ///
 /// [`Stepper`]: crate::Stepper
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error<
     PinUnavailableError,
     PinError,
@@ -114,8 +117,38 @@ impl<
     }
 }
 
+impl<
+        PinUnavailableError,
+        PinError,
+        NanosecondsToTicksError,
+        DelayToTicksError,
+        TimerError,
+    > fmt::Display
+    for Error<
+        PinUnavailableError,
+        PinError,
+        NanosecondsToTicksError,
+        DelayToTicksError,
+        TimerError,
+    >
+where
+    PinUnavailableError: fmt::Debug,
+    PinError: fmt::Debug,
+    NanosecondsToTicksError: fmt::Debug,
+    DelayToTicksError: fmt::Debug,
+    TimerError: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Signal(err) => write!(f, "{}", err),
+            Self::MotionControl(err) => write!(f, "{}", err),
+        }
+    }
+}
+
 /// An error that can occur while using this API
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum SignalError<
     PinUnavailableError,
     PinError,
@@ -131,8 +164,218 @@ pub enum SignalError<
     Pin(PinError),
 
     /// An error occurred while converting nanoseconds to timer ticks
-    NanosecondsToTicks(NanosecondsToTicksError),
+    NanosecondsToTicks {
+        /// The value that could not be converted, in nanoseconds
+        nanoseconds: u32,
+
+        /// The error returned by the conversion
+        error: NanosecondsToTicksError,
+    },
 
     /// An error originated from working with a timer
     Timer(TimerError),
+
+    /// The operation has already failed
+    ///
+    /// Returned when polling a future again, after it has already returned an
+    /// error that ended the operation. The original error has been returned
+    /// before.
+    AlreadyFailed,
+}
+
+impl<PinUnavailableError, PinError, NanosecondsToTicksError, TimerError>
+    fmt::Display
+    for SignalError<
+        PinUnavailableError,
+        PinError,
+        NanosecondsToTicksError,
+        TimerError,
+    >
+where
+    PinUnavailableError: fmt::Debug,
+    PinError: fmt::Debug,
+    NanosecondsToTicksError: fmt::Debug,
+    TimerError: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::PinUnavailable(err) => {
+                write!(f, "Pin unavailable: {:?}", err)
+            }
+            Self::Pin(err) => write!(f, "Pin error: {:?}", err),
+            Self::NanosecondsToTicks { nanoseconds, error } => write!(
+                f,
+                "Failed to convert {} ns to timer ticks: {:?}",
+                nanoseconds, error
+            ),
+            Self::Timer(err) => write!(f, "Timer error: {:?}", err),
+            Self::AlreadyFailed => write!(f, "Operation has already failed"),
+        }
+    }
+}
+
+/// An error that can occur while homing
+///
+/// See [`Stepper::home`].
+///
+/// [`Stepper::home`]: crate::Stepper::home
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HomeError<MotionControlError, SwitchError> {
+    /// An error originated from the motion control implementation
+    MotionControl(MotionControlError),
+
+    /// An error originated from reading the limit switch
+    ///
+    /// When homing to a mechanical stop (see [`Stepper::home_to_stop`]), this
+    /// is an error from reading the driver's stall detection instead.
+    ///
+    /// [`Stepper::home_to_stop`]: crate::Stepper::home_to_stop
+    Switch(SwitchError),
+
+    /// The motion was aborted, before the limit switch (or the stop) was
+    /// reached
+    Aborted,
+}
+
+/// An error that occurred while updating one axis of a [`MultiStepper`]
+///
+/// See [`MultiStepper::poll_all`].
+///
+/// [`MultiStepper`]: crate::MultiStepper
+/// [`MultiStepper::poll_all`]: crate::MultiStepper::poll_all
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AxisError<T> {
+    /// The index of the axis that caused the error
+    pub axis: usize,
+
+    /// The error that occurred
+    pub error: T,
+}
+
+/// An error that can occur while comparing the position to an encoder
+///
+/// See [`Stepper::lost_steps`].
+///
+/// [`Stepper::lost_steps`]: crate::Stepper::lost_steps
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EncoderError<MotionControlError, ReadError> {
+    /// An error originated from the motion control implementation
+    MotionControl(MotionControlError),
+
+    /// An error originated from reading the encoder
+    Encoder(ReadError),
+}
+
+/// A timing constant of a driver
+///
+/// See [`TimingError`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimingConstant {
+    /// [`Step::PULSE_LENGTH`](crate::traits::Step::PULSE_LENGTH)
+    PulseLength,
+
+    /// [`Step::LOW_TIME`](crate::traits::Step::LOW_TIME)
+    LowTime,
+
+    /// [`SetDirection::SETUP_TIME`](crate::traits::SetDirection::SETUP_TIME)
+    DirectionSetupTime,
+
+    /// [`SetStepMode::SETUP_TIME`](crate::traits::SetStepMode::SETUP_TIME)
+    StepModeSetupTime,
+
+    /// [`SetStepMode::HOLD_TIME`](crate::traits::SetStepMode::HOLD_TIME)
+    StepModeHoldTime,
+
+    /// [`SetStepMode::RESET_TIME`](crate::traits::SetStepMode::RESET_TIME)
+    StepModeResetTime,
+}
+
+/// A timing constant of a driver can't be represented by a timer
+///
+/// See [`Stepper::from_driver_checked`] and
+/// [`Stepper::check_step_mode_timing`].
+///
+/// [`Stepper::from_driver_checked`]: crate::Stepper::from_driver_checked
+/// [`Stepper::check_step_mode_timing`]:
+///     crate::Stepper::check_step_mode_timing
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimingError<NanosecondsToTicksError> {
+    /// The constant could not be converted into timer ticks
+    NanosecondsToTicks {
+        /// The constant that could not be converted
+        constant: TimingConstant,
+
+        /// The value of the constant, in nanoseconds
+        nanoseconds: u32,
+
+        /// The error returned by the conversion
+        error: NanosecondsToTicksError,
+    },
+
+    /// The constant is not zero, but converts into zero timer ticks
+    ///
+    /// This means the timer is too slow to wait for the required time. Waiting
+    /// for zero ticks would violate the driver's timing requirements.
+    TooShortForTimer {
+        /// The constant that is too short
+        constant: TimingConstant,
+
+        /// The value of the constant, in nanoseconds
+        nanoseconds: u32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString as _;
+
+    use crate::motion_control;
+
+    use super::{Error, SignalError};
+
+    type TestSignalError = SignalError<u8, u8, u8, u8>;
+
+    #[test]
+    fn signal_error_should_display_message() {
+        let cases = [
+            (TestSignalError::PinUnavailable(1), "Pin unavailable: 1"),
+            (TestSignalError::Pin(2), "Pin error: 2"),
+            (
+                TestSignalError::NanosecondsToTicks {
+                    nanoseconds: 500,
+                    error: 3,
+                },
+                "Failed to convert 500 ns to timer ticks: 3",
+            ),
+            (TestSignalError::Timer(4), "Timer error: 4"),
+            (
+                TestSignalError::AlreadyFailed,
+                "Operation has already failed",
+            ),
+        ];
+
+        for (error, message) in IntoIterator::into_iter(cases) {
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn error_should_display_message_of_wrapped_error() {
+        type TestError = Error<u8, u8, u8, u8, u8>;
+
+        assert_eq!(
+            TestError::Signal(SignalError::Pin(2)).to_string(),
+            "Pin error: 2"
+        );
+        assert_eq!(
+            TestError::MotionControl(motion_control::Error::ZeroDelay)
+                .to_string(),
+            "Step delay of zero timer ticks"
+        );
+    }
 }
@@ -0,0 +1,308 @@
+use core::{convert::TryFrom, task::Poll};
+
+use embedded_hal::digital::{blocking::InputPin, PinState};
+
+use crate::{traits::MotionControl, Direction};
+
+use super::HomeError;
+
+/// The "future" returned by [`Stepper::home`]
+///
+/// Please note that this type provides a custom API and does not implement
+/// [`core::future::Future`]. This might change, when using futures for embedded
+/// development becomes more practical.
+///
+/// [`Stepper::home`]: crate::Stepper::home
+#[must_use]
+pub struct HomeFuture<Driver: MotionControl, Switch> {
+    driver: Driver,
+    switch: Switch,
+    active_state: PinState,
+    direction: Direction,
+    velocity: Driver::Velocity,
+    back_off_steps: u32,
+    state: State,
+}
+
+impl<Driver, Switch> HomeFuture<Driver, Switch>
+where
+    Driver: MotionControl,
+    Switch: InputPin,
+{
+    /// Create new instance of `HomeFuture`
+    ///
+    /// This constructor is public to provide maximum flexibility for
+    /// non-standard use cases. Most users can ignore this and just use
+    /// [`Stepper::home`] instead.
+    ///
+    /// [`Stepper::home`]: crate::Stepper::home
+    pub fn new(
+        driver: Driver,
+        switch: Switch,
+        active_state: PinState,
+        direction: Direction,
+        velocity: Driver::Velocity,
+        back_off_steps: u32,
+    ) -> Self {
+        Self {
+            driver,
+            switch,
+            active_state,
+            direction,
+            velocity,
+            back_off_steps,
+            state: State::Initial,
+        }
+    }
+
+    /// Poll the future
+    ///
+    /// The future must be polled for the operation to make progress. The
+    /// operation won't start, until this method has been called once. Returns
+    /// [`Poll::Pending`], if the operation is not finished yet, or
+    /// [`Poll::Ready`], once it is.
+    ///
+    /// Once finished, returns the number of steps traveled until the limit
+    /// switch was triggered. This doesn't include the steps made while backing
+    /// off.
+    ///
+    /// If this method returns [`Poll::Pending`], the user can opt to keep
+    /// calling it at a high frequency (see [`Self::wait`]) until the operation
+    /// completes, or set up an interrupt that fires once the timer finishes
+    /// counting down, and call this method again once it does.
+    #[allow(clippy::type_complexity)]
+    pub fn poll(
+        &mut self,
+    ) -> Poll<Result<u32, HomeError<Driver::Error, Switch::Error>>> {
+        match self.state {
+            State::Initial => {
+                let start_step = self
+                    .driver
+                    .current_step()
+                    .map_err(HomeError::MotionControl)?;
+
+                // If the switch is already active, we're already home.
+                if self.switch_is_active()? {
+                    return self.back_off(0);
+                }
+
+                self.driver
+                    .rotate(self.direction, self.velocity)
+                    .map_err(HomeError::MotionControl)?;
+                self.state = State::Homing { start_step };
+                Poll::Pending
+            }
+            State::Homing { start_step } => {
+                if self.switch_is_active()? {
+                    self.driver.abort().map_err(HomeError::MotionControl)?;
+
+                    let current_step = self
+                        .driver
+                        .current_step()
+                        .map_err(HomeError::MotionControl)?;
+                    let traveled =
+                        current_step.wrapping_sub(start_step).unsigned_abs();
+
+                    return self.back_off(traveled);
+                }
+
+                let still_moving =
+                    self.driver.update().map_err(HomeError::MotionControl)?;
+                if !still_moving {
+                    // The rotation ended without reaching the switch. This can
+                    // only happen, if someone else aborted it.
+                    self.state = State::Finished { traveled: 0 };
+                    return Poll::Ready(Err(HomeError::Aborted));
+                }
+
+                Poll::Pending
+            }
+            State::BackingOff { traveled } => {
+                let still_moving =
+                    self.driver.update().map_err(HomeError::MotionControl)?;
+                if still_moving {
+                    Poll::Pending
+                } else {
+                    self.state = State::Finished { traveled };
+                    Poll::Ready(Ok(traveled))
+                }
+            }
+            State::Finished { traveled } => Poll::Ready(Ok(traveled)),
+        }
+    }
+
+    /// Wait until the operation completes
+    ///
+    /// This method will call [`Self::poll`] in a busy loop until the operation
+    /// has finished. See [`Self::wait_with`], if you need to do something
+    /// between calls.
+    pub fn wait(
+        &mut self,
+    ) -> Result<u32, HomeError<Driver::Error, Switch::Error>> {
+        self.wait_with(|| {})
+    }
+
+    /// Wait until the operation completes, calling `idle` between polls
+    ///
+    /// Works like [`Self::wait`], except that `idle` is called every time
+    /// [`Self::poll`] returns [`Poll::Pending`]. See
+    /// [`StepFuture::wait_with`] for details.
+    ///
+    /// [`StepFuture::wait_with`]: crate::StepFuture::wait_with
+    pub fn wait_with<F>(
+        &mut self,
+        mut idle: F,
+    ) -> Result<u32, HomeError<Driver::Error, Switch::Error>>
+    where
+        F: FnMut(),
+    {
+        loop {
+            if let Poll::Ready(result) = self.poll() {
+                return result;
+            }
+
+            idle();
+        }
+    }
+
+    /// Drop the future and release the resources that were moved into it
+    pub fn release(self) -> (Driver, Switch) {
+        (self.driver, self.switch)
+    }
+
+    fn switch_is_active(
+        &mut self,
+    ) -> Result<bool, HomeError<Driver::Error, Switch::Error>> {
+        let is_active = match self.active_state {
+            PinState::High => self.switch.is_high(),
+            PinState::Low => self.switch.is_low(),
+        };
+
+        is_active.map_err(HomeError::Switch)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn back_off(
+        &mut self,
+        traveled: u32,
+    ) -> Poll<Result<u32, HomeError<Driver::Error, Switch::Error>>> {
+        if self.back_off_steps == 0 {
+            self.state = State::Finished { traveled };
+            return Poll::Ready(Ok(traveled));
+        }
+
+        let current_step = match self.driver.current_step() {
+            Ok(current_step) => current_step,
+            Err(err) => return Poll::Ready(Err(HomeError::MotionControl(err))),
+        };
+        // Positions are `i32`, so a longer back-off can't be reached anyway.
+        // Wrapping would back off in the wrong direction.
+        let back_off_steps =
+            i32::try_from(self.back_off_steps).unwrap_or(i32::MAX);
+        let target_step = match self.direction {
+            Direction::Forward => current_step.saturating_sub(back_off_steps),
+            Direction::Backward => current_step.saturating_add(back_off_steps),
+        };
+
+        if let Err(err) =
+            self.driver.move_to_position(self.velocity, target_step)
+        {
+            return Poll::Ready(Err(HomeError::MotionControl(err)));
+        }
+
+        self.state = State::BackingOff { traveled };
+        Poll::Pending
+    }
+}
+
+enum State {
+    Initial,
+    Homing { start_step: i32 },
+    BackingOff { traveled: u32 },
+    Finished { traveled: u32 },
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::digital::PinState;
+
+    use crate::{
+        test_util::{
+            mock_motion_control, Event, Log, MockInputPin, MockMotionControl,
+        },
+        Direction, Stepper,
+    };
+
+    type TestStepper = Stepper<MockMotionControl>;
+
+    fn stepper(log: &Log) -> TestStepper {
+        Stepper::from_driver(mock_motion_control(log))
+    }
+
+    #[test]
+    fn home_should_stop_at_switch_and_back_off() {
+        let log = Log::new();
+        let mut stepper = stepper(&log);
+        let switch = MockInputPin::new(PinState::High);
+
+        let mut future = stepper.home(
+            switch.clone(),
+            PinState::Low,
+            Direction::Backward,
+            0.01,
+            2,
+        );
+
+        let traveled = loop {
+            if log.count(Event::Pin("step", PinState::Low)) == 5 {
+                switch.set(PinState::Low);
+            }
+            if let core::task::Poll::Ready(result) = future.poll() {
+                break result.unwrap();
+            }
+        };
+
+        assert_eq!(traveled, 5);
+        assert_eq!(stepper.current_step().unwrap(), -3);
+        // The 6th step had already been started, when the switch was read.
+        // It was cut short by the abort and isn't counted.
+        assert_eq!(log.count(Event::Pin("step", PinState::High)), 8);
+    }
+
+    #[test]
+    fn home_should_not_move_if_switch_is_already_active() {
+        let log = Log::new();
+        let mut stepper = stepper(&log);
+        let switch = MockInputPin::new(PinState::High);
+
+        let traveled = stepper
+            .home(switch, PinState::High, Direction::Forward, 0.01, 0)
+            .wait()
+            .unwrap();
+
+        assert_eq!(traveled, 0);
+        assert_eq!(stepper.current_step().unwrap(), 0);
+        assert_eq!(log.events(), []);
+    }
+
+    #[test]
+    fn home_should_saturate_back_off_beyond_position_range() {
+        let log = Log::new();
+        let mut stepper = stepper(&log);
+        let switch = MockInputPin::new(PinState::High);
+
+        let mut future = stepper.home(
+            switch,
+            PinState::High,
+            Direction::Forward,
+            0.01,
+            u32::MAX,
+        );
+        assert!(future.poll().is_pending());
+        drop(future);
+
+        // The back-off goes as far as the position allows. Wrapping would have
+        // made it a single step in the wrong direction.
+        assert_eq!(stepper.remaining_steps().unwrap(), Some(i32::MAX as u32));
+    }
+}
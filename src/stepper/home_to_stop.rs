@@ -0,0 +1,293 @@
+use core::task::Poll;
+
+use crate::{
+    traits::{MotionControl, StallDetection},
+    Direction,
+};
+
+use super::HomeError;
+
+/// The "future" returned by [`Stepper::home_to_stop`]
+///
+/// Please note that this type provides a custom API and does not implement
+/// [`core::future::Future`]. This might change, when using futures for embedded
+/// development becomes more practical.
+///
+/// [`Stepper::home_to_stop`]: crate::Stepper::home_to_stop
+#[must_use]
+pub struct HomeToStopFuture<Driver: MotionControl> {
+    driver: Driver,
+    direction: Direction,
+    velocity: Driver::Velocity,
+    min_travel: u32,
+    state: State,
+}
+
+impl<Driver> HomeToStopFuture<Driver>
+where
+    Driver: MotionControl + StallDetection,
+{
+    /// Create new instance of `HomeToStopFuture`
+    ///
+    /// This constructor is public to provide maximum flexibility for
+    /// non-standard use cases. Most users can ignore this and just use
+    /// [`Stepper::home_to_stop`] instead.
+    ///
+    /// [`Stepper::home_to_stop`]: crate::Stepper::home_to_stop
+    pub fn new(
+        driver: Driver,
+        direction: Direction,
+        velocity: Driver::Velocity,
+        min_travel: u32,
+    ) -> Self {
+        Self {
+            driver,
+            direction,
+            velocity,
+            min_travel,
+            state: State::Initial,
+        }
+    }
+
+    /// Poll the future
+    ///
+    /// The future must be polled for the operation to make progress. The
+    /// operation won't start, until this method has been called once. Returns
+    /// [`Poll::Pending`], if the operation is not finished yet, or
+    /// [`Poll::Ready`], once it is.
+    ///
+    /// Once finished, returns the number of steps traveled until the stall was
+    /// detected. The position has been reset to zero at that point.
+    ///
+    /// If this method returns [`Poll::Pending`], the user can opt to keep
+    /// calling it at a high frequency (see [`Self::wait`]) until the operation
+    /// completes, or set up an interrupt that fires once the timer finishes
+    /// counting down, and call this method again once it does.
+    #[allow(clippy::type_complexity)]
+    pub fn poll(
+        &mut self,
+    ) -> Poll<
+        Result<
+            u32,
+            HomeError<
+                <Driver as MotionControl>::Error,
+                <Driver as StallDetection>::Error,
+            >,
+        >,
+    > {
+        match self.state {
+            State::Initial => {
+                let start_step = self
+                    .driver
+                    .current_step()
+                    .map_err(HomeError::MotionControl)?;
+
+                self.driver
+                    .rotate(self.direction, self.velocity)
+                    .map_err(HomeError::MotionControl)?;
+                self.state = State::Homing { start_step };
+                Poll::Pending
+            }
+            State::Homing { start_step } => {
+                let current_step = self
+                    .driver
+                    .current_step()
+                    .map_err(HomeError::MotionControl)?;
+                let traveled =
+                    current_step.wrapping_sub(start_step).unsigned_abs();
+
+                // While the motor accelerates, stall detection isn't reliable.
+                // Don't even read it, until the motor has traveled far enough.
+                if traveled >= self.min_travel
+                    && self.driver.is_stalled().map_err(HomeError::Switch)?
+                {
+                    self.driver.abort().map_err(HomeError::MotionControl)?;
+                    self.driver
+                        .reset_position(0)
+                        .map_err(HomeError::MotionControl)?;
+
+                    self.state = State::Finished { traveled };
+                    return Poll::Ready(Ok(traveled));
+                }
+
+                let still_moving =
+                    self.driver.update().map_err(HomeError::MotionControl)?;
+                if !still_moving {
+                    // The rotation ended without a stall. This can only
+                    // happen, if someone else aborted it.
+                    self.state = State::Finished { traveled: 0 };
+                    return Poll::Ready(Err(HomeError::Aborted));
+                }
+
+                Poll::Pending
+            }
+            State::Finished { traveled } => Poll::Ready(Ok(traveled)),
+        }
+    }
+
+    /// Wait until the operation completes
+    ///
+    /// This method will call [`Self::poll`] in a busy loop until the operation
+    /// has finished. See [`Self::wait_with`], if you need to do something
+    /// between calls.
+    pub fn wait(
+        &mut self,
+    ) -> Result<
+        u32,
+        HomeError<
+            <Driver as MotionControl>::Error,
+            <Driver as StallDetection>::Error,
+        >,
+    > {
+        self.wait_with(|| {})
+    }
+
+    /// Wait until the operation completes, calling `idle` between polls
+    ///
+    /// Works like [`Self::wait`], except that `idle` is called every time
+    /// [`Self::poll`] returns [`Poll::Pending`]. See
+    /// [`StepFuture::wait_with`] for details.
+    ///
+    /// [`StepFuture::wait_with`]: crate::StepFuture::wait_with
+    pub fn wait_with<F>(
+        &mut self,
+        mut idle: F,
+    ) -> Result<
+        u32,
+        HomeError<
+            <Driver as MotionControl>::Error,
+            <Driver as StallDetection>::Error,
+        >,
+    >
+    where
+        F: FnMut(),
+    {
+        loop {
+            if let Poll::Ready(result) = self.poll() {
+                return result;
+            }
+
+            idle();
+        }
+    }
+
+    /// Drop the future and release the resources that were moved into it
+    pub fn release(self) -> Driver {
+        self.driver
+    }
+}
+
+enum State {
+    Initial,
+    Homing { start_step: i32 },
+    Finished { traveled: u32 },
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use embedded_hal::digital::{blocking::InputPin as _, PinState};
+    use embedded_time::duration::Nanoseconds;
+
+    use crate::{
+        test_util::{
+            mock_motion_control_with, Event, Log, MockDriver, MockInputPin,
+            MockPin, MockTimer,
+        },
+        traits::{SetDirection, StallDetection, Step},
+        Direction, Stepper,
+    };
+
+    /// A driver that reports a stall through a DIAG pin
+    struct StallingDriver {
+        driver: MockDriver,
+        diag: MockInputPin,
+    }
+
+    impl Step for StallingDriver {
+        const PULSE_LENGTH: Nanoseconds = MockDriver::PULSE_LENGTH;
+
+        type Step = MockPin;
+        type Error = Infallible;
+
+        fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+            self.driver.step()
+        }
+    }
+
+    impl SetDirection for StallingDriver {
+        const SETUP_TIME: Nanoseconds = MockDriver::SETUP_TIME;
+
+        type Dir = MockPin;
+        type Error = Infallible;
+
+        fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+            self.driver.dir()
+        }
+    }
+
+    impl StallDetection for StallingDriver {
+        type Diag = MockInputPin;
+        type Error = Infallible;
+
+        fn diag(&mut self) -> Result<&mut Self::Diag, Self::Error> {
+            Ok(&mut self.diag)
+        }
+
+        fn is_stalled(&mut self) -> Result<bool, Self::Error> {
+            self.diag.is_high()
+        }
+    }
+
+    #[test]
+    fn home_to_stop_should_stop_at_stall_and_zero_position() {
+        let log = Log::new();
+        let diag = MockInputPin::new(PinState::Low);
+        let mut stepper = Stepper::from_driver(mock_motion_control_with(
+            StallingDriver {
+                driver: MockDriver::new(&log),
+                diag: diag.clone(),
+            },
+            MockTimer::new(&log),
+        ));
+        stepper.reset_position(100).unwrap();
+
+        let mut future = stepper.home_to_stop(Direction::Backward, 0.01, 0);
+        let traveled = loop {
+            if log.count(Event::Pin("step", PinState::Low)) == 7 {
+                diag.set(PinState::High);
+            }
+            if let core::task::Poll::Ready(result) = future.poll() {
+                break result.unwrap();
+            }
+        };
+
+        assert_eq!(traveled, 7);
+        assert_eq!(stepper.current_step().unwrap(), 0);
+        // The 8th step had already been started, when the stall was read.
+        assert_eq!(log.count(Event::Pin("step", PinState::High)), 8);
+    }
+
+    #[test]
+    fn home_to_stop_should_ignore_stall_before_min_travel() {
+        let log = Log::new();
+        // Reports a stall right away, as some drivers do while accelerating.
+        let diag = MockInputPin::new(PinState::High);
+        let mut stepper = Stepper::from_driver(mock_motion_control_with(
+            StallingDriver {
+                driver: MockDriver::new(&log),
+                diag,
+            },
+            MockTimer::new(&log),
+        ));
+
+        let traveled = stepper
+            .home_to_stop(Direction::Forward, 0.01, 3)
+            .wait()
+            .unwrap();
+
+        assert_eq!(traveled, 3);
+        assert_eq!(stepper.current_step().unwrap(), 0);
+    }
+}
@@ -1,26 +1,61 @@
+mod enable;
 mod error;
+mod home;
+mod home_to_stop;
 mod move_to;
+mod multi;
+mod rotate;
 mod set_direction;
 mod set_step_mode;
 mod step;
+mod step_n;
 
 pub use self::{
-    error::{Error, SignalError},
+    enable::EnableFuture,
+    error::{
+        AxisError, EncoderError, Error, HomeError, SignalError, TimingConstant,
+        TimingError,
+    },
+    home::HomeFuture,
+    home_to_stop::HomeToStopFuture,
     move_to::MoveToFuture,
+    multi::{MoveByFuture, MultiStepper},
+    rotate::RotateFuture,
     set_direction::SetDirectionFuture,
     set_step_mode::SetStepModeFuture,
     step::StepFuture,
+    step_n::StepNFuture,
 };
 
-use core::convert::{Infallible, TryFrom};
+#[cfg(feature = "async")]
+pub use self::step::{TimerWaker, WakingStepFuture};
+
+use core::{
+    convert::{Infallible, TryFrom, TryInto as _},
+    ops,
+};
 
-use embedded_hal::{digital::blocking::OutputPin, timer::nb as timer};
+use embedded_hal::{
+    digital::{
+        blocking::{InputPin, OutputPin, StatefulOutputPin},
+        PinState,
+    },
+    timer::nb as timer,
+};
 use embedded_time::duration::Nanoseconds;
+use ramp_maker::MotionProfile;
 
 use crate::{
+    linear::{self, Rounding},
+    motion_control::{
+        BusyError, DelayToTicks, MotionOutcome, OnStep, SoftwareMotionControl,
+        Speed, StepCounter,
+    },
     traits::{
-        EnableDirectionControl, EnableMotionControl, EnableStepControl,
-        EnableStepModeControl, MotionControl, SetDirection, SetStepMode, Step,
+        ChopperMode, EnableDirectionControl, EnableDriverControl,
+        EnableMotionControl, EnableStallDetection, EnableStepControl,
+        EnableStepModeControl, Encoder, MotionControl, SetDirection, SetEnable,
+        SetStepMode, StallDetection, Step,
     },
     util::ref_mut::RefMut,
     Direction,
@@ -63,6 +98,18 @@ use crate::{
 /// that method has been called, the methods that control the hardware
 /// capability are available.
 ///
+/// ## Driver control
+///
+/// Enable this capability with [`Stepper::enable_driver_control`] and use it
+/// with [`Stepper::enable`] and [`Stepper::disable`]. This controls whether
+/// the motor coils are energized, typically through the driver's ENABLE pin.
+///
+/// ## Stall detection
+///
+/// Enable this capability with [`Stepper::enable_stall_detection`] and use it
+/// with [`Stepper::is_stalled`]. This is typically only supported by drivers
+/// that have a DIAG pin that signals a stalled motor.
+///
 /// ## Step mode control
 ///
 /// Enable this capability with [`Stepper::enable_step_mode_control`] and use it
@@ -85,11 +132,16 @@ use crate::{
 /// ## Motion control
 ///
 /// Enable motion control with [`Stepper::enable_motion_control`] and use it
-/// with [`Stepper::move_to_position`] and [`Stepper::reset_position`].
+/// with [`Stepper::move_to_position`], [`Stepper::rotate`], [`Stepper::home`],
+/// [`Stepper::reset_position`], [`Stepper::current_step`],
+/// [`Stepper::current_direction`], [`Stepper::reverse`],
+/// [`Stepper::remaining_steps`], and [`Stepper::abort`].
 ///
 /// Motion control capability is directly supported by motion control chips, but
 /// a software implementation based on direction and step control exists in the
 /// [`motion_control`] module, to make the capability available for all drivers.
+/// With the software implementation, [`Stepper::is_idle`],
+/// [`Stepper::is_moving`], and [`Stepper::reset`] are also available.
 ///
 /// [`motion_control`]: crate::motion_control
 ///
@@ -128,10 +180,57 @@ pub struct Stepper<Driver> {
 
 impl<Driver> Stepper<Driver> {
     /// Create a new `Stepper` instance from a driver
+    ///
+    /// The driver doesn't need to go through the `enable_*` methods, if it
+    /// already implements the traits for the capabilities it is used with. A
+    /// custom driver that implements [`Step`] and [`SetDirection`] directly
+    /// can be used with [`Stepper::step`] and [`Stepper::set_direction`] right
+    /// away, or with [`Stepper::enable_motion_control`], to get motion control
+    /// through the software fallback.
     pub fn from_driver(driver: Driver) -> Self {
         Self { driver }
     }
 
+    /// Create a new `Stepper` instance, after checking the driver's timing
+    ///
+    /// Checks that the driver's pulse length, low time, and setup time (see
+    /// [`Step::pulse_length`], [`Step::low_time`], and
+    /// [`SetDirection::setup_time`]) can be represented by a timer of type
+    /// `Timer`. Each of them must convert into the timer's `Time` type, and
+    /// must not convert into zero ticks, unless it is zero itself.
+    ///
+    /// Otherwise, those problems would only show up once a signal is
+    /// generated, either as an error, or as a signal that violates the
+    /// driver's timing requirements. Please note that values that aren't a
+    /// multiple of the timer period are still rounded, according to the
+    /// conversion implemented by the timer.
+    ///
+    /// See [`Stepper::check_step_mode_timing`], if your driver supports step
+    /// mode control.
+    pub fn from_driver_checked<Timer>(
+        driver: Driver,
+    ) -> Result<Self, TimingError<<Timer::Time as TryFrom<Nanoseconds>>::Error>>
+    where
+        Driver: Step + SetDirection,
+        Timer: timer::CountDown,
+        Timer::Time: TryFrom<Nanoseconds> + PartialOrd,
+    {
+        check_timing::<Timer::Time>(
+            TimingConstant::PulseLength,
+            driver.pulse_length(),
+        )?;
+        check_timing::<Timer::Time>(
+            TimingConstant::LowTime,
+            driver.low_time(),
+        )?;
+        check_timing::<Timer::Time>(
+            TimingConstant::DirectionSetupTime,
+            driver.setup_time(),
+        )?;
+
+        Ok(Self::from_driver(driver))
+    }
+
     /// Access a reference to the wrapped driver
     ///
     /// Can be used to access driver-specific functionality that can't be
@@ -151,10 +250,145 @@ impl<Driver> Stepper<Driver> {
     /// Release the wrapped driver
     ///
     /// Drops this instance of `Stepper` and returns the wrapped driver.
+    ///
+    /// If motion control has been enabled using the software fallback, the
+    /// returned driver is a [`SoftwareMotionControl`], which can be released
+    /// further into the original driver and timer, using
+    /// [`SoftwareMotionControl::release`].
+    ///
+    /// [`SoftwareMotionControl::release`]: crate::motion_control::SoftwareMotionControl::release
     pub fn release(self) -> Driver {
         self.driver
     }
 
+    /// Enable driver control
+    ///
+    /// Consumes this instance of `Stepper` and returns a new instance that
+    /// provides control over enabling and disabling the driver. Once this
+    /// method has been called, the [`Stepper::enable`] and
+    /// [`Stepper::disable`] methods become available.
+    ///
+    /// Takes the hardware resources that are required for enabling and
+    /// disabling the driver as an argument. What exactly those are depends on
+    /// the specific driver. Typically it's going to be the output pin that is
+    /// connected to the hardware's ENABLE pin.
+    ///
+    /// This method is only available, if the driver supports enabling driver
+    /// control. It might no longer be available, once driver control has been
+    /// enabled.
+    pub fn enable_driver_control<Resources>(
+        self,
+        res: Resources,
+    ) -> Stepper<Driver::WithDriverControl>
+    where
+        Driver: EnableDriverControl<Resources>,
+    {
+        Stepper {
+            driver: self.driver.enable_driver_control(res),
+        }
+    }
+
+    /// Enable the driver, energizing the motor coils
+    ///
+    /// The returned future waits for the driver's hold time, after which the
+    /// driver is ready to step.
+    ///
+    /// You might need to call [`Stepper::enable_driver_control`] to make this
+    /// method available.
+    pub fn enable<'r, Timer>(
+        &'r mut self,
+        timer: &'r mut Timer,
+    ) -> EnableFuture<RefMut<'r, Driver>, RefMut<'r, Timer>>
+    where
+        Driver: SetEnable,
+        Timer: timer::CountDown,
+        Timer::Time: TryFrom<Nanoseconds>,
+    {
+        EnableFuture::new(RefMut(&mut self.driver), RefMut(timer))
+    }
+
+    /// Disable the driver, de-energizing the motor coils
+    ///
+    /// You might need to call [`Stepper::enable_driver_control`] to make this
+    /// method available.
+    pub fn disable(&mut self) -> Result<(), Driver::Error>
+    where
+        Driver: SetEnable,
+    {
+        self.driver.disable()
+    }
+
+    /// Indicate whether the driver is enabled
+    ///
+    /// See [`SetEnable::is_enabled`] for details.
+    ///
+    /// You might need to call [`Stepper::enable_driver_control`] to make this
+    /// method available.
+    pub fn is_enabled(&self) -> Option<bool>
+    where
+        Driver: SetEnable,
+    {
+        self.driver.is_enabled()
+    }
+
+    /// Switch between the quiet and the strong chopper mode
+    ///
+    /// Selects the quiet mode (like stealthChop), if `quiet` is `true`, which
+    /// reduces noise at low speeds. Selects the mode that provides more torque
+    /// at high speeds (like spreadCycle), if `quiet` is `false`. See
+    /// [`ChopperMode`] for details.
+    ///
+    /// Only available, if the driver supports this. Some drivers need to be
+    /// configured first. For example, you might need to call
+    /// [`Stepper::enable_step_mode_control`] to make this method available.
+    pub fn set_quiet(&mut self, quiet: bool) -> Result<(), Driver::Error>
+    where
+        Driver: ChopperMode,
+    {
+        self.driver.set_quiet(quiet)
+    }
+
+    /// Enable stall detection
+    ///
+    /// Consumes this instance of `Stepper` and returns a new instance that
+    /// can detect whether the motor has stalled. Once this method has been
+    /// called, the [`Stepper::is_stalled`] method becomes available.
+    ///
+    /// Takes the hardware resources that are required for stall detection as
+    /// an argument. What exactly those are depends on the specific driver.
+    /// Typically it's going to be the input pin that is connected to the
+    /// hardware's DIAG pin.
+    ///
+    /// This method is only available, if the driver supports enabling stall
+    /// detection. It might no longer be available, once stall detection has
+    /// been enabled.
+    pub fn enable_stall_detection<Resources>(
+        self,
+        res: Resources,
+    ) -> Stepper<Driver::WithStallDetection>
+    where
+        Driver: EnableStallDetection<Resources>,
+    {
+        Stepper {
+            driver: self.driver.enable_stall_detection(res),
+        }
+    }
+
+    /// Indicates whether the driver has detected a stall
+    ///
+    /// Stall detection is not tied to any ongoing motion. It's up to the user
+    /// to call this method regularly while the motor is moving, and react to a
+    /// stall, for example by calling [`Stepper::abort`].
+    ///
+    /// You might need to call [`Stepper::enable_stall_detection`] to make this
+    /// method available.
+    pub fn is_stalled(&mut self) -> Result<bool, Driver::Error>
+    where
+        Driver: StallDetection,
+    {
+        self.driver.is_stalled()
+    }
+
     /// Enable microstepping mode control
     ///
     /// Consumes this instance of `Stepper` and returns a new instance that
@@ -169,6 +403,7 @@ impl<Driver> Stepper<Driver> {
     /// This method is only available, if the driver supports enabling step mode
     /// control. It might no longer be available, once step mode control has
     /// been enabled.
+    #[allow(clippy::type_complexity)]
     pub fn enable_step_mode_control<Resources, Timer>(
         self,
         res: Resources,
@@ -203,6 +438,11 @@ impl<Driver> Stepper<Driver> {
     /// hardware might not support microstepping at all, or only allow setting
     /// the step mode by changing physical switches.
     ///
+    /// Changing the step mode during a motion is not safe, as the tracked
+    /// position no longer matches the physical one afterwards. If motion
+    /// control is provided by [`SoftwareMotionControl`], this fails while a
+    /// motion is ongoing or pending.
+    ///
     /// You might need to call [`Stepper::enable_step_mode_control`] to make
     /// this method available.
     pub fn set_step_mode<'r, Timer>(
@@ -222,6 +462,51 @@ impl<Driver> Stepper<Driver> {
         )
     }
 
+    /// Returns the step mode that was last applied
+    ///
+    /// See [`SetStepMode::step_mode`] for details.
+    ///
+    /// You might need to call [`Stepper::enable_step_mode_control`] to make
+    /// this method available.
+    pub fn step_mode(&self) -> Option<Driver::StepMode>
+    where
+        Driver: SetStepMode,
+    {
+        self.driver.step_mode()
+    }
+
+    /// Check that the driver's step mode timing can be represented by a timer
+    ///
+    /// Checks [`SetStepMode::SETUP_TIME`], [`SetStepMode::HOLD_TIME`], and
+    /// [`SetStepMode::RESET_TIME`], the same way
+    /// [`Stepper::from_driver_checked`] checks the other timing constants.
+    ///
+    /// You might need to call [`Stepper::enable_step_mode_control`] to make
+    /// this method available.
+    pub fn check_step_mode_timing<Timer>(
+        &self,
+    ) -> Result<(), TimingError<<Timer::Time as TryFrom<Nanoseconds>>::Error>>
+    where
+        Driver: SetStepMode,
+        Timer: timer::CountDown,
+        Timer::Time: TryFrom<Nanoseconds> + PartialOrd,
+    {
+        check_timing::<Timer::Time>(
+            TimingConstant::StepModeSetupTime,
+            Driver::SETUP_TIME,
+        )?;
+        check_timing::<Timer::Time>(
+            TimingConstant::StepModeHoldTime,
+            Driver::HOLD_TIME,
+        )?;
+        check_timing::<Timer::Time>(
+            TimingConstant::StepModeResetTime,
+            Driver::RESET_TIME,
+        )?;
+
+        Ok(())
+    }
+
     /// Enable direction control
     ///
     /// Consumes this instance of `Stepper` and returns a new instance that
@@ -236,6 +521,7 @@ impl<Driver> Stepper<Driver> {
     /// This method is only available, if the driver supports enabling direction
     /// control. It might no longer be available, once direction control has
     /// been enabled.
+    #[allow(clippy::type_complexity)]
     pub fn enable_direction_control<Resources, Timer>(
         self,
         res: Resources,
@@ -331,6 +617,39 @@ impl<Driver> Stepper<Driver> {
         StepFuture::new(RefMut(&mut self.driver), RefMut(timer))
     }
 
+    /// Rotates the motor the given number of (micro-)steps at a constant speed
+    ///
+    /// Makes `count` steps in the direction that was previously set. `delay`
+    /// is the time from the start of one step to the start of the next. This
+    /// is a lightweight alternative to motion control (see
+    /// [`Stepper::enable_motion_control`]), for targets where computing a
+    /// motion profile is too expensive. There's no acceleration or
+    /// deceleration, so `delay` must be long enough for the motor to start at
+    /// that speed.
+    ///
+    /// After every STEP pulse, the returned future waits for the rest of
+    /// `delay`, but at least for the driver's [`Step::LOW_TIME`]. Since this
+    /// works on the bare driver, there's no position to update. Use
+    /// [`StepNFuture::steps_made`] to learn how far the motor went, or use
+    /// [`Stepper::step_n_tracked`] with [`SoftwareMotionControl`], which counts
+    /// every step into [`Stepper::current_step`].
+    ///
+    /// You might need to call [`Stepper::enable_step_control`] to make this
+    /// method available.
+    pub fn step_n<'r, Timer>(
+        &'r mut self,
+        count: u32,
+        delay: Nanoseconds,
+        timer: &'r mut Timer,
+    ) -> StepNFuture<RefMut<'r, Driver>, RefMut<'r, Timer>>
+    where
+        Driver: Step,
+        Timer: timer::CountDown,
+        Timer::Time: TryFrom<Nanoseconds>,
+    {
+        StepNFuture::new(RefMut(&mut self.driver), RefMut(timer), count, delay)
+    }
+
     /// Returns the step pulse length of the wrapped driver/controller
     ///
     /// The pulse length is also available through the [`Step`] trait. This
@@ -342,7 +661,64 @@ impl<Driver> Stepper<Driver> {
     where
         Driver: Step,
     {
-        Driver::PULSE_LENGTH
+        self.driver.pulse_length()
+    }
+
+    /// Read back the level the STEP pin is set to
+    ///
+    /// This is meant for debugging. It reports the level the STEP pin has been
+    /// set to (see [`StatefulOutputPin`]), not the electrical state of the
+    /// pin, which helps to confirm that STEP is driven as expected while
+    /// bringing up a board.
+    ///
+    /// Only available, if the driver's STEP pin implements
+    /// [`StatefulOutputPin`]. You might need to call
+    /// [`Stepper::enable_step_control`] to make this method available.
+    #[allow(clippy::type_complexity)]
+    pub fn last_step_level(
+        &mut self,
+    ) -> Result<
+        PinState,
+        SignalError<
+            <Driver as Step>::Error,
+            <Driver::Step as OutputPin>::Error,
+            Infallible, // only applies to methods that use a timer
+            Infallible, // only applies to methods that use a timer
+        >,
+    >
+    where
+        Driver: Step,
+        Driver::Step: StatefulOutputPin,
+    {
+        let pin = self.driver.step().map_err(SignalError::PinUnavailable)?;
+        pin_level(pin).map_err(SignalError::Pin)
+    }
+
+    /// Read back the level the DIR pin is set to
+    ///
+    /// Works like [`Stepper::last_step_level`], but for the DIR pin.
+    ///
+    /// Only available, if the driver's DIR pin implements
+    /// [`StatefulOutputPin`]. You might need to call
+    /// [`Stepper::enable_direction_control`] to make this method available.
+    #[allow(clippy::type_complexity)]
+    pub fn last_dir_level(
+        &mut self,
+    ) -> Result<
+        PinState,
+        SignalError<
+            <Driver as SetDirection>::Error,
+            <Driver::Dir as OutputPin>::Error,
+            Infallible, // only applies to methods that use a timer
+            Infallible, // only applies to methods that use a timer
+        >,
+    >
+    where
+        Driver: SetDirection,
+        Driver::Dir: StatefulOutputPin,
+    {
+        let pin = self.driver.dir().map_err(SignalError::PinUnavailable)?;
+        pin_level(pin).map_err(SignalError::Pin)
     }
 
     /// Enable motion control
@@ -403,11 +779,298 @@ impl<Driver> Stepper<Driver> {
         MoveToFuture::new(RefMut(&mut self.driver), max_velocity, target_step)
     }
 
+    /// Move the motor to the given position, at a typed maximum speed
+    ///
+    /// Works like [`Stepper::move_to_position`], except that the maximum
+    /// velocity is passed as a [`Speed`] in steps per second, instead of as a
+    /// raw value in the motion profile's unit. `TIMER_HZ` is the frequency of
+    /// the timer used for motion control, which is needed to convert the speed
+    /// into steps per timer tick.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `TIMER_HZ` can't be represented by the velocity type.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn move_to_position_at<'r, const TIMER_HZ: u32>(
+        &'r mut self,
+        max_speed: Speed<Driver::Velocity>,
+        target_step: i32,
+    ) -> MoveToFuture<RefMut<'r, Driver>>
+    where
+        Driver: MotionControl,
+        Driver::Velocity:
+            num_traits::NumCast + ops::Div<Output = Driver::Velocity>,
+    {
+        self.move_to_position(max_speed.per_tick::<TIMER_HZ>(), target_step)
+    }
+
+    /// Move the motor by the given number of steps
+    ///
+    /// Works like [`Stepper::move_to_position`], except that the target
+    /// position is relative to the current position (see
+    /// [`Stepper::current_step`]). The sign of `delta_steps` determines the
+    /// direction. Successive relative motions compose, as each one starts from
+    /// where the previous one ended.
+    ///
+    /// The current position is read, once the returned future is polled for
+    /// the first time.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn move_by<'r>(
+        &'r mut self,
+        max_velocity: Driver::Velocity,
+        delta_steps: i32,
+    ) -> MoveToFuture<RefMut<'r, Driver>>
+    where
+        Driver: MotionControl,
+    {
+        MoveToFuture::new_relative(
+            RefMut(&mut self.driver),
+            max_velocity,
+            delta_steps,
+        )
+    }
+
+    /// Move the motor until a limit switch is reached
+    ///
+    /// Rotates the motor in the given direction (`direction`) at the given
+    /// velocity (`velocity`), until the limit switch (`switch`) reads
+    /// `active_state`. The motion is then aborted (see [`Stepper::abort`]),
+    /// which means the motor stops without decelerating. Use a low velocity,
+    /// to make sure this doesn't lose steps.
+    ///
+    /// Afterwards, the motor moves `back_off_steps` steps in the opposite
+    /// direction, to release the switch. Pass `0` to skip this. The back-off
+    /// stops at the end of the range of positions, if it would go beyond it.
+    ///
+    /// If the switch is already active when homing starts, the motor doesn't
+    /// rotate at all, but it still backs off.
+    ///
+    /// The returned future resolves to the number of steps traveled until the
+    /// switch was reached. This is usually followed by a call to
+    /// [`Stepper::reset_position`], to define the current position as the
+    /// origin.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn home<'r, Switch>(
+        &'r mut self,
+        switch: Switch,
+        active_state: PinState,
+        direction: Direction,
+        velocity: Driver::Velocity,
+        back_off_steps: u32,
+    ) -> HomeFuture<RefMut<'r, Driver>, Switch>
+    where
+        Driver: MotionControl,
+        Switch: InputPin,
+    {
+        HomeFuture::new(
+            RefMut(&mut self.driver),
+            switch,
+            active_state,
+            direction,
+            velocity,
+            back_off_steps,
+        )
+    }
+
+    /// Home the motor by driving it into a mechanical stop
+    ///
+    /// Works like [`Stepper::home`], but instead of a limit switch, this
+    /// relies on the driver's stall detection (see [`Stepper::is_stalled`]),
+    /// for sensorless homing. The motor rotates in the given `direction`, at
+    /// the given `velocity`, until it stalls against the stop. Then the
+    /// motion is aborted, and the position is reset to zero.
+    ///
+    /// Stall detection tends to report false stalls while the motor is
+    /// accelerating, and some drivers report a stall right after starting. It
+    /// isn't checked, until the motor has traveled `min_travel` steps. This
+    /// means the motor needs to start at least that far from the stop, or it
+    /// will push against the stop until it has made those steps.
+    ///
+    /// The returned future resolves to the number of steps traveled until the
+    /// stall was detected.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] and
+    /// [`Stepper::enable_stall_detection`] to make this method available.
+    pub fn home_to_stop<'r>(
+        &'r mut self,
+        direction: Direction,
+        velocity: Driver::Velocity,
+        min_travel: u32,
+    ) -> HomeToStopFuture<RefMut<'r, Driver>>
+    where
+        Driver: MotionControl + StallDetection,
+    {
+        HomeToStopFuture::new(
+            RefMut(&mut self.driver),
+            direction,
+            velocity,
+            min_travel,
+        )
+    }
+
+    /// Rotate the motor in the given direction indefinitely
+    ///
+    /// Accelerates the motor to the given velocity (`velocity`), as far as the
+    /// motion profile allows, then keeps it rotating at that velocity. This is
+    /// useful for applications like conveyors or spindles, which don't have a
+    /// target position.
+    ///
+    /// The rotation never ends on its own. Call [`RotateFuture::abort`] to stop
+    /// it, or drop the future and start another motion. The position returned
+    /// by [`Stepper::current_step`] keeps being updated while rotating.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    ///
+    /// # Example
+    ///
+    /// Run a motor at a fixed number of revolutions per minute. This assumes
+    /// that the velocity is given in steps per timer tick, and a 1 MHz timer.
+    /// If your number type implements `num_traits::NumCast`, you can use
+    /// [`VelocityConverter`] instead of doing the conversion manually.
+    ///
+    /// [`VelocityConverter`]: crate::motion_control::VelocityConverter
+    ///
+    /// ``` rust,no_run
+    /// # fn main() -> Result<(), ()> {
+    /// # use stepper::embedded_time::duration::Nanoseconds;
+    /// #
+    /// # type Num = fixed::FixedI64<typenum::U32>;
+    /// #
+    /// # struct Pin;
+    /// # impl stepper::embedded_hal::digital::blocking::OutputPin for Pin {
+    /// #     type Error = core::convert::Infallible;
+    /// #     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// #
+    /// # struct Timer;
+    /// # impl stepper::embedded_hal::timer::nb::CountDown for Timer {
+    /// #     type Error = core::convert::Infallible;
+    /// #     type Time = Ticks;
+    /// #     fn start<T>(&mut self, count: T) -> Result<(), Self::Error>
+    /// #         where T: Into<Self::Time>
+    /// #     {
+    /// #         Ok(())
+    /// #     }
+    /// #     fn wait(&mut self) -> nb::Result<(), Self::Error> {
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    /// #
+    /// # #[derive(Clone, Copy, PartialEq, PartialOrd)]
+    /// # pub struct Ticks(Num);
+    /// # impl From<Nanoseconds> for Ticks {
+    /// #     fn from(_: Nanoseconds) -> Self {
+    /// #         Self(Num::from_num(0))
+    /// #     }
+    /// # }
+    /// # impl core::ops::Add for Ticks {
+    /// #     type Output = Self;
+    /// #     fn add(self, rhs: Self) -> Self::Output {
+    /// #         Self(self.0 + rhs.0)
+    /// #     }
+    /// # }
+    /// # impl core::ops::Sub for Ticks {
+    /// #     type Output = Self;
+    /// #     fn sub(self, rhs: Self) -> Self::Output {
+    /// #         Self(self.0 - rhs.0)
+    /// #     }
+    /// # }
+    /// #
+    /// # pub struct DelayToTicks;
+    /// # impl stepper::motion_control::DelayToTicks<Num> for DelayToTicks {
+    /// #     type Ticks = Ticks;
+    /// #     type Error = core::convert::Infallible;
+    /// #     fn delay_to_ticks(&self, delay: Num)
+    /// #         -> Result<Self::Ticks, Self::Error>
+    /// #     {
+    /// #         Ok(Ticks(delay.int()))
+    /// #     }
+    /// # }
+    /// #
+    /// # let mut timer = Timer;
+    /// # let profile = stepper::ramp_maker::Trapezoidal::new(
+    /// #     Num::from_num(0.001),
+    /// # );
+    /// # let mut stepper = stepper::Stepper::from_driver(
+    /// #     stepper::drivers::drv8825::DRV8825::new(),
+    /// # )
+    /// #     .enable_direction_control(
+    /// #         Pin,
+    /// #         stepper::Direction::Forward,
+    /// #         &mut timer,
+    /// #     )
+    /// #     .map_err(|_| ())?
+    /// #     .enable_step_control(Pin)
+    /// #     .enable_motion_control((timer, profile, DelayToTicks));
+    /// #
+    /// use stepper::Direction;
+    ///
+    /// const STEPS_PER_REVOLUTION: i64 = 200;
+    /// const TIMER_HZ: i64 = 1_000_000;
+    /// const RPM: i64 = 60;
+    ///
+    /// // Convert revolutions per minute into steps per timer tick.
+    /// let velocity = Num::from_num(RPM * STEPS_PER_REVOLUTION)
+    ///     / Num::from_num(60 * TIMER_HZ);
+    ///
+    /// let mut rotation = stepper.rotate(Direction::Forward, velocity);
+    /// loop {
+    ///     // Poll the rotation regularly, for example from the timer
+    ///     // interrupt, and call `abort` when it's time to stop.
+    ///     let _ = rotation.poll();
+    /// }
+    /// # }
+    /// ```
+    pub fn rotate<'r>(
+        &'r mut self,
+        direction: Direction,
+        velocity: Driver::Velocity,
+    ) -> RotateFuture<RefMut<'r, Driver>>
+    where
+        Driver: MotionControl,
+    {
+        RotateFuture::new(RefMut(&mut self.driver), direction, velocity)
+    }
+
+    /// Change the maximum velocity of the ongoing motion
+    ///
+    /// Applies the new maximum velocity (`max_velocity`) to the motion started
+    /// with [`Stepper::move_to_position`] or [`Stepper::rotate`], without
+    /// stopping. The target position and the current position are unaffected.
+    /// How the motor gets from the old to the new velocity depends on the
+    /// driver (see [`MotionControl::set_max_speed`]).
+    ///
+    /// While the future returned by [`Stepper::move_to_position`] or
+    /// [`Stepper::rotate`] exists, it borrows the `Stepper`. Use
+    /// [`MoveToFuture::set_max_speed`] or [`RotateFuture::set_max_speed`]
+    /// instead, in that case.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn set_max_speed(
+        &mut self,
+        max_velocity: Driver::Velocity,
+    ) -> Result<(), Driver::Error>
+    where
+        Driver: MotionControl,
+    {
+        self.driver.set_max_speed(max_velocity)
+    }
+
     /// Reset the position to the given value
     ///
     /// This should never result in a movement, as this method only overwrites
-    /// the internal position counter of the driver. However, it might influence
-    /// an already ongoing movement.
+    /// the internal position counter of the driver. [`SoftwareMotionControl`]
+    /// refuses to do this while a motion is ongoing (see
+    /// [`MotionControl::reset_position`]).
     ///
     /// You might need to call [`Stepper::enable_motion_control`] to make this
     /// method available.
@@ -417,4 +1080,1255 @@ impl<Driver> Stepper<Driver> {
     {
         self.driver.reset_position(step)
     }
+
+    /// Returns the current position
+    ///
+    /// The position is given in (micro-)steps, as tracked by the driver. It
+    /// reflects the last step that was completed, so during an ongoing
+    /// movement, it might lag behind the physical position by up to one step.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn current_step(&mut self) -> Result<i32, Driver::Error>
+    where
+        Driver: MotionControl,
+    {
+        self.driver.current_step()
+    }
+
+    /// Returns the current position in full steps
+    ///
+    /// Divides [`Stepper::current_step`] by the number of microsteps per step
+    /// of the step mode that was last applied (see [`Stepper::step_mode`]).
+    /// Positions between two full steps are rounded according to `rounding`.
+    ///
+    /// Returns `None`, if no step mode has been applied yet, as there's no way
+    /// to know what the position is counted in. Since changing the step mode
+    /// doesn't convert the position, this only makes sense, if the position
+    /// has been counted in the current step mode since it was last reset (see
+    /// [`Stepper::reset_position`]).
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] and
+    /// [`Stepper::enable_step_mode_control`] to make this method available.
+    pub fn full_steps(
+        &mut self,
+        rounding: Rounding,
+    ) -> Result<Option<i32>, <Driver as MotionControl>::Error>
+    where
+        Driver: MotionControl + SetStepMode,
+    {
+        self.position_in(1, rounding)
+    }
+
+    /// Returns the current position in revolutions
+    ///
+    /// Works like [`Stepper::full_steps`], but divides by the number of
+    /// microsteps per revolution, given `full_steps_per_revolution` (200, for
+    /// most motors).
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `full_steps_per_revolution` is zero.
+    pub fn revolutions(
+        &mut self,
+        full_steps_per_revolution: u32,
+        rounding: Rounding,
+    ) -> Result<Option<i32>, <Driver as MotionControl>::Error>
+    where
+        Driver: MotionControl + SetStepMode,
+    {
+        assert!(
+            full_steps_per_revolution != 0,
+            "Steps per revolution must not be zero"
+        );
+        self.position_in(full_steps_per_revolution, rounding)
+    }
+
+    fn position_in(
+        &mut self,
+        full_steps: u32,
+        rounding: Rounding,
+    ) -> Result<Option<i32>, <Driver as MotionControl>::Error>
+    where
+        Driver: MotionControl + SetStepMode,
+    {
+        let microsteps: u16 = match self.driver.step_mode() {
+            Some(step_mode) => step_mode.into(),
+            None => return Ok(None),
+        };
+        let current_step = self.driver.current_step()?;
+
+        // Can't overflow: `u16::MAX * u32::MAX` fits into `i64`.
+        let denominator = i64::from(microsteps) * i64::from(full_steps);
+        Ok(Some(linear::divide(
+            i64::from(current_step),
+            denominator,
+            rounding,
+        )))
+    }
+
+    /// Make progress on the ongoing motion, without waiting
+    ///
+    /// Calls [`MotionControl::update`] once and returns whether a motion is
+    /// still ongoing. Unlike the futures returned by methods like
+    /// [`Stepper::move_to_position`], this only borrows the `Stepper` for the
+    /// duration of the call. This makes it suitable for being called from an
+    /// interrupt handler (for example, once the timer used for motion control
+    /// fires), with the `Stepper` stored in a static resource. Start motions
+    /// through [`Stepper::driver_mut`] in that case, for example using
+    /// [`MotionControl::move_to_position`].
+    ///
+    /// With [`SoftwareMotionControl`], the internal state is moved out of
+    /// `self` and replaced with a placeholder during the call, then written
+    /// back. If the call panics, the placeholder stays, and all further calls
+    /// fail (see [`SoftwareMotionControl::reset`]). Since the method takes
+    /// `&mut self`, it can't be re-entered while the placeholder is in place.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn poll(&mut self) -> Result<bool, Driver::Error>
+    where
+        Driver: MotionControl,
+    {
+        self.driver.update()
+    }
+
+    /// Wait until the ongoing motion has finished
+    ///
+    /// Calls [`MotionControl::update`] in a busy loop, until no motion is
+    /// ongoing anymore, and returns the first error that occurs. This is the
+    /// motion control counterpart to [`StepFuture::wait`], for simple
+    /// synchronous programs that start motions through
+    /// [`Stepper::driver_mut`]. Returns immediately, if no motion is ongoing.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn wait_until_idle(&mut self) -> Result<(), Driver::Error>
+    where
+        Driver: MotionControl,
+    {
+        while self.driver.update()? {}
+        Ok(())
+    }
+
+    /// Returns how many steps the motor is behind the tracked position
+    ///
+    /// Compares the position tracked by motion control (see
+    /// [`Stepper::current_step`]) to the position measured by `encoder`.
+    /// Returns the tracked position minus the measured one. This is zero, if
+    /// no steps have been lost, positive, if the motor didn't go as far forward
+    /// (or went further backward) as commanded, and negative otherwise.
+    ///
+    /// This is meant to be called after a motion has finished. During an
+    /// ongoing motion, the result might be off by one, as the tracked position
+    /// can lag behind by up to one step. How many lost steps are acceptable
+    /// depends on the resolution and backlash of the encoder.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn lost_steps<Enc>(
+        &mut self,
+        encoder: &mut Enc,
+    ) -> Result<i32, EncoderError<Driver::Error, Enc::Error>>
+    where
+        Driver: MotionControl,
+        Enc: Encoder,
+    {
+        let commanded = self
+            .driver
+            .current_step()
+            .map_err(EncoderError::MotionControl)?;
+        let measured = encoder.position().map_err(EncoderError::Encoder)?;
+
+        Ok(commanded.wrapping_sub(measured))
+    }
+
+    /// Returns the current direction
+    ///
+    /// This is the direction of the ongoing motion, or of the last motion, if
+    /// none is ongoing.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn current_direction(&mut self) -> Result<Direction, Driver::Error>
+    where
+        Driver: MotionControl,
+    {
+        self.driver.current_direction()
+    }
+
+    /// Reverse the ongoing motion
+    ///
+    /// The rest of the ongoing motion is made in the opposite direction. For a
+    /// motion started with [`Stepper::move_to_position`], that means the motor
+    /// moves as many steps away from the original target, as it had left
+    /// before reversing. A rotation started with [`Stepper::rotate`] continues
+    /// in the opposite direction.
+    ///
+    /// How the motor changes direction depends on the driver. It might not
+    /// decelerate before doing so.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn reverse(&mut self) -> Result<(), Driver::Error>
+    where
+        Driver: MotionControl,
+    {
+        self.driver.reverse()
+    }
+
+    /// Returns the number of steps left in the current motion
+    ///
+    /// This can be used to track the progress of a motion. Returns `Ok(None)`,
+    /// if the driver doesn't know how many steps are left. Calling this method
+    /// doesn't influence the motion in any way.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn remaining_steps(&mut self) -> Result<Option<u32>, Driver::Error>
+    where
+        Driver: MotionControl,
+    {
+        self.driver.remaining_steps()
+    }
+
+    /// Abort any ongoing motion immediately
+    ///
+    /// Stops the motor right away, without ramping down, and discards the
+    /// rest of the motion. This is intended for emergencies, like a limit
+    /// switch or a driver fault being triggered.
+    ///
+    /// Stopping a motor at speed can cause it to lose steps. In addition, a
+    /// step that was interrupted might not have been tracked as such, which
+    /// means the position returned by [`Stepper::current_step`] might be off
+    /// afterwards. Consider homing again, before relying on the position.
+    ///
+    /// You might need to call [`Stepper::enable_motion_control`] to make this
+    /// method available.
+    pub fn abort(&mut self) -> Result<(), Driver::Error>
+    where
+        Driver: MotionControl,
+    {
+        self.driver.abort()
+    }
+}
+
+impl<Driver, Timer, Profile, Convert, Counter, Hook>
+    Stepper<
+        SoftwareMotionControl<Driver, Timer, Profile, Convert, Counter, Hook>,
+    >
+where
+    Timer: timer::CountDown,
+    Profile: MotionProfile,
+    Counter: StepCounter,
+{
+    /// Indicate whether there is no ongoing or pending motion
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::is_idle`] for
+    /// details.
+    pub fn is_idle(&self) -> bool {
+        self.driver.is_idle()
+    }
+
+    /// Indicate whether there is an ongoing or pending motion
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::is_moving`] for
+    /// details.
+    pub fn is_moving(&self) -> bool {
+        self.driver.is_moving()
+    }
+
+    /// Queue a move to the given position
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::queue_move`]
+    /// for details, including the limitations of queued moves. Queued moves
+    /// are started by [`Stepper::poll`], [`Stepper::wait_until_idle`], or the
+    /// future of any other motion.
+    pub fn queue_move(
+        &mut self,
+        max_velocity: Profile::Velocity,
+        target_step: i32,
+    ) -> Result<(), crate::motion_control::QueueFullError> {
+        self.driver.queue_move(max_velocity, target_step)
+    }
+
+    /// Returns the number of queued moves
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::queued_moves`].
+    pub fn queued_moves(&self) -> usize {
+        self.driver.queued_moves()
+    }
+
+    /// The timer ticks spent on the last move
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See
+    /// [`SoftwareMotionControl::last_move_ticks`].
+    pub fn last_move_ticks(&self) -> Option<Timer::Time>
+    where
+        Timer::Time: Copy,
+    {
+        self.driver.last_move_ticks()
+    }
+
+    /// Access a reference to the motion profile
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`].
+    pub fn profile(&self) -> &Profile {
+        self.driver.profile()
+    }
+
+    /// Access a mutable reference to the motion profile
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. This can be used to change the parameters of
+    /// the motion profile between motions (for example, the acceleration),
+    /// without reconstructing the `Stepper`. With RampMaker's profiles, that
+    /// means replacing the profile with a new one.
+    ///
+    /// Only use this while no motion is ongoing (see [`Stepper::is_idle`]).
+    /// The motion profile tracks the state of the ongoing motion, and changing
+    /// it mid-motion might make the motor jump to a different velocity, or
+    /// lose track of how many steps are left. A delay previewed by
+    /// [`Stepper::peek_next_delay`] is not recomputed either.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if a motion is ongoing or pending.
+    pub fn profile_mut(&mut self) -> &mut Profile {
+        debug_assert!(
+            self.driver.is_idle(),
+            "Motion profile must not be changed during a motion"
+        );
+        self.driver.profile_mut()
+    }
+
+    /// Invert the direction signal
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See
+    /// [`SoftwareMotionControl::set_invert_direction`] for details, including
+    /// how this interacts with homing.
+    pub fn set_invert_direction(&mut self, invert: bool) {
+        self.driver.set_invert_direction(invert)
+    }
+
+    /// Wrap the position once per revolution, for a rotary axis
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See
+    /// [`SoftwareMotionControl::set_rotary_wrap`] for details.
+    pub fn set_rotary_wrap(&mut self, steps_per_revolution: Option<u32>) {
+        self.driver.set_rotary_wrap(steps_per_revolution)
+    }
+
+    /// Fail a move that takes too many calls to finish
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::set_timeout`]
+    /// for details, including how to choose a timeout for slow moves.
+    pub fn set_timeout(&mut self, timeout: Option<u32>) {
+        self.driver.set_timeout(timeout)
+    }
+
+    /// Reject moves to positions outside of the given range
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See
+    /// [`SoftwareMotionControl::set_soft_limits`] for details, including how
+    /// this affects rotations.
+    ///
+    /// # Panics
+    ///
+    /// Panics, if `min` is greater than `max`.
+    pub fn set_soft_limits(&mut self, min: i32, max: i32) {
+        self.driver.set_soft_limits(min, max)
+    }
+
+    /// Remove the limits set by [`Stepper::set_soft_limits`]
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`].
+    pub fn clear_soft_limits(&mut self) {
+        self.driver.clear_soft_limits()
+    }
+
+    /// Override the length of the STEP pulse
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See
+    /// [`SoftwareMotionControl::set_pulse_length`] for details. To override
+    /// the pulse length of a single step, use [`StepFuture::with_pulse_length`]
+    /// instead.
+    pub fn set_pulse_length(&mut self, pulse_length: Option<Nanoseconds>) {
+        self.driver.set_pulse_length(pulse_length)
+    }
+
+    /// Preview the delay the motion profile will produce for the next step
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See
+    /// [`SoftwareMotionControl::peek_next_delay`] for details on how the
+    /// previewed delay is cached.
+    pub fn peek_next_delay(&mut self) -> Option<Profile::Delay>
+    where
+        Profile::Delay: Copy,
+    {
+        self.driver.peek_next_delay()
+    }
+
+    /// Access the statistics about how long each phase of a motion takes
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`], and the `timing-stats` feature is enabled.
+    /// See [`SoftwareMotionControl::timing_stats`] for details.
+    #[cfg(feature = "timing-stats")]
+    pub fn timing_stats(
+        &self,
+    ) -> crate::motion_control::TimingStats<Timer::Time>
+    where
+        Timer::Time: Copy,
+    {
+        self.driver.timing_stats()
+    }
+
+    /// Access the totals of all motions made so far
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`], and the `motion-stats` feature is enabled.
+    /// See [`SoftwareMotionControl::stats`] for details.
+    #[cfg(feature = "motion-stats")]
+    pub fn stats(&self) -> crate::motion_control::MotionStats<Timer::Time>
+    where
+        Timer::Time: Copy,
+    {
+        self.driver.stats()
+    }
+
+    /// Clear the totals returned by [`Stepper::stats`]
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`], and the `motion-stats` feature is enabled.
+    #[cfg(feature = "motion-stats")]
+    pub fn reset_stats(&mut self) {
+        self.driver.reset_stats()
+    }
+}
+
+impl<Driver, Timer, Profile, Convert, Counter, Hook>
+    Stepper<
+        SoftwareMotionControl<Driver, Timer, Profile, Convert, Counter, Hook>,
+    >
+where
+    Counter: StepCounter,
+    Driver: SetDirection + Step,
+    Profile: MotionProfile,
+    Timer: timer::CountDown,
+    Profile::Velocity: Copy,
+    Convert: DelayToTicks<Profile::Delay, Ticks = Timer::Time>,
+    Convert::Ticks: TryFrom<Nanoseconds>
+        + ops::Add<Output = Convert::Ticks>
+        + ops::Sub<Output = Convert::Ticks>
+        + PartialOrd
+        + Copy,
+    Hook: OnStep<Counter>,
+{
+    /// Return to a safe, idle state
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::reset`] for
+    /// details, including when recovery from a previous panic is possible.
+    #[allow(clippy::type_complexity)]
+    pub fn reset(
+        &mut self,
+    ) -> Result<
+        (),
+        <SoftwareMotionControl<
+            Driver,
+            Timer,
+            Profile,
+            Convert,
+            Counter,
+            Hook,
+        > as MotionControl>::Error,
+    >{
+        self.driver.reset()
+    }
+
+    /// Force all outputs low and return to idle, for use in a fault handler
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`], and the timer can be canceled. See
+    /// [`SoftwareMotionControl::safe_state`] for details, including how errors
+    /// are handled.
+    #[allow(clippy::type_complexity)]
+    pub fn safe_state(
+        &mut self,
+    ) -> Result<
+        (),
+        <SoftwareMotionControl<
+            Driver,
+            Timer,
+            Profile,
+            Convert,
+            Counter,
+            Hook,
+        > as MotionControl>::Error,
+    >
+    where
+        Timer: timer::Cancel,
+    {
+        self.driver.safe_state()
+    }
+
+    /// Make progress on the ongoing motion, doing a bounded amount of work
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. Works like [`Stepper::poll`], except that it
+    /// returns after at most `max_transitions` transitions of the internal
+    /// state machine. See [`SoftwareMotionControl::update_bounded`] for
+    /// details.
+    #[allow(clippy::type_complexity)]
+    pub fn poll_bounded(
+        &mut self,
+        max_transitions: usize,
+    ) -> Result<
+        bool,
+        <SoftwareMotionControl<
+            Driver,
+            Timer,
+            Profile,
+            Convert,
+            Counter,
+            Hook,
+        > as MotionControl>::Error,
+    >{
+        self.driver.update_bounded(max_transitions)
+    }
+
+    /// Make a single step in the current direction, and block until it's done
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. Meant for jogging the motor by hand, without
+    /// starting a motion. See [`SoftwareMotionControl::step_once`] for details.
+    #[allow(clippy::type_complexity)]
+    pub fn step_once(
+        &mut self,
+    ) -> Result<
+        (),
+        BusyError<
+            <SoftwareMotionControl<
+                Driver,
+                Timer,
+                Profile,
+                Convert,
+                Counter,
+                Hook,
+            > as MotionControl>::Error,
+        >,
+    > {
+        self.driver.step_once()
+    }
+
+    /// Make `count` steps at a constant rate, and block until they're done
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. Unlike [`Stepper::step_n`], every step is
+    /// counted into the position. See [`SoftwareMotionControl::step_n`] for
+    /// details.
+    #[allow(clippy::type_complexity)]
+    pub fn step_n_tracked(
+        &mut self,
+        count: u32,
+        delay: Nanoseconds,
+    ) -> Result<
+        (),
+        BusyError<
+            <SoftwareMotionControl<
+                Driver,
+                Timer,
+                Profile,
+                Convert,
+                Counter,
+                Hook,
+            > as MotionControl>::Error,
+        >,
+    > {
+        self.driver.step_n(count, delay)
+    }
+
+    /// Pause the ongoing motion
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::pause`] for
+    /// details.
+    #[allow(clippy::type_complexity)]
+    pub fn pause(
+        &mut self,
+    ) -> Result<
+        (),
+        <SoftwareMotionControl<
+            Driver,
+            Timer,
+            Profile,
+            Convert,
+            Counter,
+            Hook,
+        > as MotionControl>::Error,
+    >{
+        self.driver.pause()
+    }
+
+    /// Resume a paused motion
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::resume`] for
+    /// details.
+    #[allow(clippy::type_complexity)]
+    pub fn resume(
+        &mut self,
+    ) -> Result<
+        (),
+        <SoftwareMotionControl<
+            Driver,
+            Timer,
+            Profile,
+            Convert,
+            Counter,
+            Hook,
+        > as MotionControl>::Error,
+    >{
+        self.driver.resume()
+    }
+
+    /// Stop stepping, but keep the motor energized
+    ///
+    /// Discards the ongoing motion and any queued moves, without touching the
+    /// driver's pins. Unlike [`Stepper::abort`], this doesn't drive the STEP
+    /// pin to its idle state, and unlike [`Stepper::disable`], the coils stay
+    /// energized, so the motor keeps its holding torque.
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::hold`] for
+    /// details.
+    #[allow(clippy::type_complexity)]
+    pub fn hold(
+        &mut self,
+    ) -> Result<
+        (),
+        <SoftwareMotionControl<
+            Driver,
+            Timer,
+            Profile,
+            Convert,
+            Counter,
+            Hook,
+        > as MotionControl>::Error,
+    >{
+        self.driver.hold()
+    }
+
+    /// Report how the last motion ended
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::take_outcome`]
+    /// for details.
+    pub fn take_outcome(&mut self) -> Option<MotionOutcome> {
+        self.driver.take_outcome()
+    }
+
+    /// Indicate whether a motion has been paused
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::pause`].
+    pub fn is_paused(&self) -> bool {
+        self.driver.is_paused()
+    }
+
+    /// Complete the current STEP pulse, then abort
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::shutdown`] for
+    /// details, including why this isn't done on drop.
+    #[allow(clippy::type_complexity)]
+    pub fn shutdown(
+        &mut self,
+    ) -> Result<
+        (),
+        <SoftwareMotionControl<
+            Driver,
+            Timer,
+            Profile,
+            Convert,
+            Counter,
+            Hook,
+        > as MotionControl>::Error,
+    >{
+        self.driver.shutdown()
+    }
+
+    /// Complete or end the current STEP pulse, then abort, ignoring errors
+    ///
+    /// Only available, if motion control is provided by
+    /// [`SoftwareMotionControl`]. See [`SoftwareMotionControl::finish`].
+    pub fn finish(&mut self) {
+        self.driver.finish()
+    }
+}
+
+fn pin_level<Pin>(pin: &Pin) -> Result<PinState, Pin::Error>
+where
+    Pin: StatefulOutputPin,
+{
+    if pin.is_set_high()? {
+        Ok(PinState::High)
+    } else {
+        Ok(PinState::Low)
+    }
+}
+
+fn check_timing<Time>(
+    constant: TimingConstant,
+    duration: Nanoseconds,
+) -> Result<(), TimingError<Time::Error>>
+where
+    Time: TryFrom<Nanoseconds> + PartialOrd,
+{
+    let to_ticks = |duration: Nanoseconds| {
+        duration
+            .try_into()
+            .map_err(|err| TimingError::NanosecondsToTicks {
+                constant,
+                nanoseconds: duration.0,
+                error: err,
+            })
+    };
+
+    let ticks: Time = to_ticks(duration)?;
+    let zero: Time = to_ticks(Nanoseconds(0))?;
+
+    if duration.0 > 0 && ticks <= zero {
+        return Err(TimingError::TooShortForTimer {
+            constant,
+            nanoseconds: duration.0,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::convert::Infallible;
+
+    use embedded_hal::digital::PinState::{High, Low};
+    use embedded_time::duration::Nanoseconds;
+    use ramp_maker::{Flat, Trapezoidal};
+
+    use crate::{
+        motion_control::{
+            self, QueueFullError, SoftwareMotionControl, Speed,
+            MOVE_QUEUE_CAPACITY,
+        },
+        test_util::{
+            mock_motion_control, mock_motion_control_with, Event, Log,
+            MockDelayToTicks, MockDriver, MockTimer, Num, NumToTicks,
+        },
+        traits::{Encoder, MotionControl},
+        Direction, Stepper,
+    };
+
+    use super::{TimingConstant, TimingError};
+
+    /// An encoder that lags behind the commanded position
+    struct LaggingEncoder(i32);
+
+    impl Encoder for LaggingEncoder {
+        type Error = Infallible;
+
+        fn position(&mut self) -> Result<i32, Self::Error> {
+            Ok(self.0)
+        }
+    }
+
+    #[test]
+    fn from_driver_checked_should_reject_timer_too_slow_for_pulse() {
+        let log = Log::new();
+
+        // At 1 kHz, the 2 µs STEP pulse converts into zero ticks.
+        let result = Stepper::from_driver_checked::<MockTimer<1_000>>(
+            MockDriver::new(&log),
+        );
+
+        assert!(matches!(
+            result,
+            Err(TimingError::TooShortForTimer {
+                constant: TimingConstant::PulseLength,
+                nanoseconds: 2_000,
+            })
+        ));
+    }
+
+    #[test]
+    fn timing_checks_should_accept_fast_enough_timer() {
+        let log = Log::new();
+
+        let stepper =
+            Stepper::from_driver_checked::<MockTimer>(MockDriver::new(&log))
+                .unwrap();
+
+        assert_eq!(stepper.check_step_mode_timing::<MockTimer>(), Ok(()));
+    }
+
+    #[test]
+    fn timing_checks_should_reject_timer_too_slow_for_setup_time() {
+        let log = Log::new();
+
+        // At 500 kHz, the 2 µs STEP pulse is one tick, but the 1 µs setup
+        // time converts into zero ticks.
+        let stepper = Stepper::from_driver_checked::<MockTimer<500_000>>(
+            MockDriver::new(&log),
+        );
+        assert!(matches!(
+            stepper,
+            Err(TimingError::TooShortForTimer {
+                constant: TimingConstant::DirectionSetupTime,
+                ..
+            })
+        ));
+
+        let stepper = Stepper::from_driver(MockDriver::new(&log));
+        assert_eq!(
+            stepper.check_step_mode_timing::<MockTimer<500_000>>(),
+            Err(TimingError::TooShortForTimer {
+                constant: TimingConstant::StepModeSetupTime,
+                nanoseconds: 1_000,
+            })
+        );
+    }
+
+    #[test]
+    fn poll_should_make_progress_from_interrupt_handler() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control_with(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(1),
+        ));
+
+        // Stands in for a timer interrupt handler, which only gets temporary
+        // access to the `Stepper`.
+        fn on_interrupt<Driver: MotionControl>(
+            stepper: &mut Stepper<Driver>,
+        ) -> bool {
+            stepper.poll().ok().unwrap()
+        }
+
+        stepper.driver_mut().move_to_position(0.01, 3).unwrap();
+
+        let mut interrupts = 0;
+        while on_interrupt(&mut stepper) {
+            interrupts += 1;
+
+            // Between interrupts, the `Stepper` is fully accessible.
+            assert!(stepper.is_moving());
+        }
+
+        assert!(interrupts > 3);
+        assert!(stepper.is_idle());
+        assert_eq!(stepper.current_step().unwrap(), 3);
+    }
+
+    #[test]
+    fn from_driver_should_not_require_enabling_implemented_traits() {
+        let log = Log::new();
+
+        // `MockDriver` implements `Step` and `SetDirection` directly.
+        let mut stepper = Stepper::from_driver(MockDriver::new(&log));
+        let mut timer = MockTimer::new(&log);
+        stepper
+            .set_direction(Direction::Forward, &mut timer)
+            .wait()
+            .unwrap();
+        stepper.step(&mut timer).wait().unwrap();
+        assert_eq!(log.count(Event::Pin("step", High)), 1);
+
+        let mut stepper = stepper.enable_motion_control((
+            timer,
+            Flat::new(),
+            MockDelayToTicks,
+        ));
+        stepper.move_to_position(0.01, 3).wait().unwrap();
+        assert_eq!(stepper.current_step().unwrap(), 3);
+        assert_eq!(log.count(Event::Pin("step", High)), 4);
+    }
+
+    #[cfg(feature = "drv8825")]
+    #[test]
+    fn hold_should_stop_stepping_but_keep_driver_enabled() {
+        use crate::{drivers::drv8825::DRV8825, test_util::MockPin};
+
+        let log = Log::new();
+        let mut timer = MockTimer::new(&log);
+        let mut stepper = Stepper::from_driver(DRV8825::new())
+            .enable_driver_control(MockPin::new("enable", &log))
+            .enable_direction_control(
+                MockPin::new("dir", &log),
+                Direction::Forward,
+                &mut timer,
+            )
+            .unwrap()
+            .enable_step_control(MockPin::new("step", &log));
+        stepper.enable(&mut timer).wait().unwrap();
+        let mut stepper = stepper.enable_motion_control((
+            timer,
+            Flat::new(),
+            MockDelayToTicks,
+        ));
+
+        stepper.driver_mut().move_to_position(0.01, 10).unwrap();
+        stepper.driver_mut().queue_move(0.01, 20).unwrap();
+        while stepper.current_step().unwrap() < 3 {
+            stepper.poll().unwrap();
+        }
+        // Finish the step in progress, so the STEP pin is idle.
+        while !stepper.is_idle()
+            && log.events().last() != Some(&Event::Pin("step", Low))
+        {
+            stepper.poll().unwrap();
+        }
+        log.clear();
+
+        stepper.hold().unwrap();
+
+        assert!(stepper.is_idle());
+        assert_eq!(stepper.driver().queued_moves(), 0);
+        assert!(!stepper.poll().unwrap());
+        assert_eq!(log.events(), []);
+        assert_eq!(stepper.is_enabled(), Some(true));
+    }
+
+    #[test]
+    fn last_levels_should_report_pin_states() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(MockDriver::new(&log));
+        let mut timer = MockTimer::new(&log).with_polls(1);
+
+        assert_eq!(stepper.last_step_level(), Ok(Low));
+        assert_eq!(stepper.last_dir_level(), Ok(Low));
+
+        stepper
+            .set_direction(Direction::Forward, &mut timer)
+            .wait()
+            .unwrap();
+        assert_eq!(stepper.last_dir_level(), Ok(High));
+
+        // The STEP pin is high while the pulse is in progress.
+        let mut future = stepper.step(&mut timer);
+        assert!(future.poll().is_pending());
+        drop(future);
+        assert_eq!(stepper.last_step_level(), Ok(High));
+
+        stepper.step(&mut timer).wait().unwrap();
+        assert_eq!(stepper.last_step_level(), Ok(Low));
+        assert_eq!(stepper.last_dir_level(), Ok(High));
+    }
+
+    #[test]
+    fn wait_until_idle_should_complete_motion() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control_with(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(1),
+        ));
+
+        stepper.driver_mut().move_to_position(0.01, 3).unwrap();
+        stepper.wait_until_idle().unwrap();
+
+        assert!(stepper.is_idle());
+        assert_eq!(stepper.current_step().unwrap(), 3);
+        assert_eq!(log.count(Event::Pin("step", High)), 3);
+
+        // Nothing left to do.
+        stepper.wait_until_idle().unwrap();
+        assert_eq!(log.count(Event::Pin("step", High)), 3);
+    }
+
+    #[test]
+    fn profile_mut_should_change_acceleration_between_motions() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(SoftwareMotionControl::new(
+            MockDriver::new(&log),
+            MockTimer::new(&log),
+            Trapezoidal::new(Num::from_num(0.000_000_1)),
+            NumToTicks,
+        ));
+
+        let delays = || {
+            let delays: Vec<_> = log
+                .events()
+                .windows(2)
+                .filter_map(|events| match events {
+                    [Event::Pin("step", Low), Event::TimerStart(ticks)] => {
+                        Some(*ticks)
+                    }
+                    _ => None,
+                })
+                .collect();
+            log.clear();
+            delays
+        };
+
+        stepper
+            .move_to_position(Num::from_num(0.001), 10)
+            .wait()
+            .unwrap();
+        let slow = delays();
+
+        *stepper.profile_mut() = Trapezoidal::new(Num::from_num(0.000_001));
+        stepper
+            .move_to_position(Num::from_num(0.001), 0)
+            .wait()
+            .unwrap();
+        let fast = delays();
+
+        assert_eq!(slow.len(), 10);
+        assert_eq!(fast.len(), 10);
+
+        // Higher acceleration means shorter delays while accelerating.
+        assert!(fast[0] < slow[0], "{:?}, {:?}", slow, fast);
+    }
+
+    #[test]
+    fn move_to_position_at_should_convert_speed_to_velocity() {
+        let run = |move_to: &dyn Fn(&mut Stepper<_>)| {
+            let log = Log::new();
+            let mut stepper = Stepper::from_driver(mock_motion_control(&log));
+            move_to(&mut stepper);
+            assert_eq!(stepper.current_step().unwrap(), 3);
+            log.events()
+        };
+
+        // 10000 steps per second are 0.01 steps per tick, at 1 MHz.
+        let typed = run(&|stepper| {
+            stepper
+                .move_to_position_at::<1_000_000>(
+                    Speed::steps_per_second(10_000.0),
+                    3,
+                )
+                .wait()
+                .unwrap()
+        });
+        let raw =
+            run(&|stepper| stepper.move_to_position(0.01, 3).wait().unwrap());
+
+        assert_eq!(typed, raw);
+        assert!(typed.contains(&Event::TimerStart(100 - 2)), "{:?}", typed);
+    }
+
+    #[test]
+    fn queued_moves_should_execute_back_to_back() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control(&log));
+
+        stepper.queue_move(0.01, 3).unwrap();
+        stepper.queue_move(0.01, 1).unwrap();
+        assert_eq!(stepper.queued_moves(), 2);
+        assert!(stepper.is_moving());
+
+        // Polling doesn't report the end of the motion in between the moves.
+        let mut positions = Vec::new();
+        while stepper.poll().unwrap() {
+            positions.push(stepper.current_step().unwrap());
+        }
+
+        assert!(positions.contains(&3));
+        assert_eq!(stepper.current_step().unwrap(), 1);
+        assert_eq!(stepper.queued_moves(), 0);
+        assert_eq!(log.count(Event::Pin("step", High)), 5);
+        assert!(stepper.is_idle());
+    }
+
+    #[test]
+    fn queue_move_should_fail_if_queue_is_full() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control(&log));
+
+        for target in 0..MOVE_QUEUE_CAPACITY as i32 {
+            stepper.queue_move(0.01, target).unwrap();
+        }
+        assert_eq!(stepper.queue_move(0.01, 0), Err(QueueFullError));
+
+        // Aborting clears the queue.
+        stepper.abort().unwrap();
+        assert_eq!(stepper.queued_moves(), 0);
+        assert!(stepper.is_idle());
+    }
+
+    #[test]
+    fn lost_steps_should_compare_position_to_encoder() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control(&log));
+
+        stepper.move_to_position(0.01, 10).wait().unwrap();
+
+        assert_eq!(stepper.lost_steps(&mut LaggingEncoder(10)), Ok(0));
+        assert_eq!(stepper.lost_steps(&mut LaggingEncoder(8)), Ok(2));
+
+        stepper.move_to_position(0.01, -10).wait().unwrap();
+
+        // Moving backward, the encoder lags behind on the other side.
+        assert_eq!(stepper.lost_steps(&mut LaggingEncoder(-7)), Ok(-3));
+    }
+
+    #[test]
+    fn move_by_should_reject_targets_beyond_soft_limits() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control(&log));
+        stepper.set_soft_limits(0, 10);
+
+        stepper.move_by(0.01, 4).wait().unwrap();
+        stepper.move_by(0.01, 6).wait().unwrap();
+        assert_eq!(stepper.current_step().unwrap(), 10);
+
+        // Relative to the current position, which is already at the limit.
+        assert!(matches!(
+            stepper.move_by(0.01, 1).wait(),
+            Err(motion_control::Error::OutOfBounds)
+        ));
+        assert_eq!(stepper.current_step().unwrap(), 10);
+    }
+
+    #[test]
+    fn step_once_should_make_exactly_one_step() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control(&log));
+
+        stepper.step_once().unwrap();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("dir", High),
+                Event::TimerStart(1),
+                Event::TimerFinished,
+                Event::Pin("step", High),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                // The driver's LOW_TIME is zero, so there's no delay after.
+                Event::Pin("step", Low),
+            ]
+        );
+        assert_eq!(stepper.current_step().unwrap(), 1);
+        assert_eq!(stepper.remaining_steps().unwrap(), Some(0));
+
+        // After moving backward, a step goes backward too.
+        stepper.move_by(0.01, -3).wait().unwrap();
+        log.clear();
+        stepper.step_once().unwrap();
+        assert_eq!(log.count(Event::Pin("step", High)), 1);
+        assert_eq!(log.events().first(), Some(&Event::Pin("dir", Low)));
+        assert_eq!(stepper.current_step().unwrap(), -3);
+    }
+
+    #[test]
+    fn step_n_tracked_should_count_every_step() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control(&log));
+
+        stepper.step_n_tracked(3, Nanoseconds(10_000)).unwrap();
+
+        // Every step consists of the 2 µs STEP pulse, followed by the 8 µs
+        // left until the next step.
+        let step = [
+            Event::Pin("step", High),
+            Event::TimerStart(2),
+            Event::TimerFinished,
+            Event::Pin("step", Low),
+            Event::TimerStart(8),
+            Event::TimerFinished,
+        ];
+        let mut expected = vec![
+            Event::Pin("dir", High),
+            Event::TimerStart(1),
+            Event::TimerFinished,
+        ];
+        expected.extend(step.iter().cycle().take(3 * 6).cloned());
+        assert_eq!(log.events(), expected);
+        assert_eq!(stepper.current_step().unwrap(), 3);
+        assert_eq!(stepper.remaining_steps().unwrap(), Some(0));
+
+        // Steps up to the soft limit are made and counted.
+        stepper.driver_mut().set_soft_limits(-5, 5);
+        assert!(matches!(
+            stepper.step_n_tracked(3, Nanoseconds(10_000)),
+            Err(motion_control::BusyError::Other(
+                motion_control::Error::OutOfBounds
+            ))
+        ));
+        assert_eq!(stepper.current_step().unwrap(), 5);
+    }
+
+    #[test]
+    fn step_once_should_be_rejected_between_steps_of_a_motion() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control(&log));
+
+        stepper.driver_mut().move_to_position(0.01, 3).unwrap();
+
+        // Park the motion in the idle state, after setting the direction.
+        for _ in 0..3 {
+            assert!(stepper.driver_mut().update_bounded(1).unwrap());
+        }
+        log.clear();
+
+        assert!(matches!(
+            stepper.step_once(),
+            Err(motion_control::BusyError::Busy)
+        ));
+        assert_eq!(log.events(), []);
+
+        // The move isn't cut short.
+        stepper.wait_until_idle().unwrap();
+        assert_eq!(stepper.current_step().unwrap(), 3);
+    }
+
+    #[test]
+    fn full_steps_should_divide_by_microsteps() {
+        use crate::{linear::Rounding, step_mode::StepMode256};
+
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control(&log));
+
+        // Without a step mode, the unit of the position is unknown.
+        assert_eq!(stepper.full_steps(Rounding::Nearest).unwrap(), None);
+
+        stepper
+            .driver_mut()
+            .set_step_mode(StepMode256::M16)
+            .unwrap()
+            .wait()
+            .unwrap();
+
+        stepper.move_to_position(0.01, 16).wait().unwrap();
+        assert_eq!(stepper.full_steps(Rounding::Nearest).unwrap(), Some(1));
+
+        // Half a step is rounded away from zero, unless requested otherwise.
+        stepper.move_to_position(0.01, -24).wait().unwrap();
+        assert_eq!(stepper.full_steps(Rounding::Nearest).unwrap(), Some(-2));
+        assert_eq!(stepper.full_steps(Rounding::TowardZero).unwrap(), Some(-1));
+
+        // 1.5 revolutions of a 2-step motor.
+        stepper.move_to_position(0.01, 48).wait().unwrap();
+        assert_eq!(
+            stepper.revolutions(2, Rounding::TowardZero).unwrap(),
+            Some(1)
+        );
+        assert_eq!(stepper.revolutions(2, Rounding::Nearest).unwrap(), Some(2));
+    }
 }
@@ -2,13 +2,15 @@ use core::task::Poll;
 
 use crate::traits::MotionControl;
 
-/// The "future" returned by [`Stepper::move_to_position`]
+/// The "future" returned by [`Stepper::move_to_position`] and
+/// [`Stepper::move_by`]
 ///
 /// Please note that this type provides a custom API and does not implement
 /// [`core::future::Future`]. This might change, when using futures for embedded
 /// development becomes more practical.
 ///
 /// [`Stepper::move_to_position`]: crate::Stepper::move_to_position
+/// [`Stepper::move_by`]: crate::Stepper::move_by
 #[must_use]
 pub struct MoveToFuture<Driver: MotionControl> {
     driver: Driver,
@@ -35,7 +37,31 @@ where
             driver,
             state: State::Initial {
                 max_velocity,
-                target_step,
+                target: Target::Absolute(target_step),
+            },
+        }
+    }
+
+    /// Create new instance of `MoveToFuture` for a relative motion
+    ///
+    /// The target position is computed from [`MotionControl::current_step`]
+    /// and `delta_steps`, once the future is polled for the first time.
+    ///
+    /// This constructor is public to provide maximum flexibility for
+    /// non-standard use cases. Most users can ignore this and just use
+    /// [`Stepper::move_by`] instead.
+    ///
+    /// [`Stepper::move_by`]: crate::Stepper::move_by
+    pub fn new_relative(
+        driver: Driver,
+        max_velocity: Driver::Velocity,
+        delta_steps: i32,
+    ) -> Self {
+        Self {
+            driver,
+            state: State::Initial {
+                max_velocity,
+                target: Target::Relative(delta_steps),
             },
         }
     }
@@ -55,8 +81,15 @@ where
         match self.state {
             State::Initial {
                 max_velocity,
-                target_step,
+                target,
             } => {
+                let target_step = match target {
+                    Target::Absolute(target_step) => target_step,
+                    Target::Relative(delta_steps) => {
+                        self.driver.current_step()?.wrapping_add(delta_steps)
+                    }
+                };
+
                 self.driver.move_to_position(max_velocity, target_step)?;
                 self.state = State::Moving;
                 Poll::Pending
@@ -77,12 +110,48 @@ where
     /// Wait until the operation completes
     ///
     /// This method will call [`Self::poll`] in a busy loop until the operation
-    /// has finished.
+    /// has finished. See [`Self::wait_with`], if you need to do something
+    /// between calls.
     pub fn wait(&mut self) -> Result<(), Driver::Error> {
+        self.wait_with(|| {})
+    }
+
+    /// Wait until the operation completes, calling `idle` between polls
+    ///
+    /// Works like [`Self::wait`], except that `idle` is called every time
+    /// [`Self::poll`] returns [`Poll::Pending`]. This can be used to yield to
+    /// a scheduler, or to wait for an interrupt, instead of spinning.
+    pub fn wait_with<F>(&mut self, mut idle: F) -> Result<(), Driver::Error>
+    where
+        F: FnMut(),
+    {
         loop {
             if let Poll::Ready(result) = self.poll() {
                 return result;
             }
+
+            idle();
+        }
+    }
+
+    /// Change the maximum velocity of the ongoing motion
+    ///
+    /// See [`MotionControl::set_max_speed`] for details. If the motion hasn't
+    /// been started yet, it will be started with the new maximum velocity.
+    pub fn set_max_speed(
+        &mut self,
+        max_velocity: Driver::Velocity,
+    ) -> Result<(), Driver::Error> {
+        match &mut self.state {
+            State::Initial {
+                max_velocity: velocity,
+                ..
+            } => {
+                *velocity = max_velocity;
+                Ok(())
+            }
+            State::Moving => self.driver.set_max_speed(max_velocity),
+            State::Finished => Ok(()),
         }
     }
 
@@ -95,8 +164,51 @@ where
 enum State<Velocity> {
     Initial {
         max_velocity: Velocity,
-        target_step: i32,
+        target: Target,
     },
     Moving,
     Finished,
 }
+
+#[derive(Clone, Copy)]
+enum Target {
+    Absolute(i32),
+    Relative(i32),
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::digital::PinState::{High, Low};
+
+    use crate::{
+        test_util::{mock_motion_control, Event, Log},
+        Stepper,
+    };
+
+    #[test]
+    fn move_by_should_move_relative_to_current_position() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(mock_motion_control(&log));
+
+        stepper.move_by(0.01, 100).wait().unwrap();
+        assert_eq!(stepper.current_step().unwrap(), 100);
+        assert_eq!(log.events()[0], Event::Pin("dir", High));
+
+        log.clear();
+        stepper.move_by(0.01, -40).wait().unwrap();
+        assert_eq!(stepper.current_step().unwrap(), 60);
+
+        // The direction is changed and its setup time is waited for, before
+        // the first step in the new direction.
+        assert_eq!(
+            log.events()[..4],
+            [
+                Event::Pin("dir", Low),
+                Event::TimerStart(1),
+                Event::TimerFinished,
+                Event::Pin("step", High),
+            ]
+        );
+        assert_eq!(log.count(Event::Pin("step", High)), 40);
+    }
+}
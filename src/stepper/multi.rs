@@ -0,0 +1,457 @@
+use core::task::Poll;
+
+use crate::traits::MotionControl;
+
+use super::{AxisError, Stepper};
+
+/// Coordinates multiple steppers, so they move along a straight line
+///
+/// Wraps `N` instances of [`Stepper`] with motion control capability. Instead
+/// of moving each axis at its own maximum velocity, [`MultiStepper::move_by`]
+/// interleaves the steps of all axes using Bresenham's line algorithm, so all
+/// axes start and finish together. This is useful for machines like pen
+/// plotters, which need to move multiple axes in a coordinated way.
+///
+/// The axis with the most steps to make (the "major" axis) steps at the given
+/// velocity. All other axes only step, when that is required to keep them on
+/// the line.
+///
+/// # Limitations
+///
+/// Every step is made by starting a one-step motion on each axis that needs to
+/// step, then waiting until all of them have finished. This means all axes
+/// share the granularity of the slowest timer, and motion profiles have no
+/// chance to accelerate or decelerate. Use a motion profile with a constant
+/// velocity, like [`ramp_maker::Flat`].
+///
+/// All axes need to use the same driver type.
+///
+/// # Example
+///
+/// ``` rust
+/// # fn main() -> Result<(), ()> {
+/// # use stepper::embedded_time::duration::Nanoseconds;
+/// #
+/// # type Num = fixed::FixedI64<typenum::U32>;
+/// #
+/// # struct Pin;
+/// # impl stepper::embedded_hal::digital::blocking::OutputPin for Pin {
+/// #     type Error = core::convert::Infallible;
+/// #     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+/// #     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+/// # }
+/// #
+/// # struct Timer;
+/// # impl stepper::embedded_hal::timer::nb::CountDown for Timer {
+/// #     type Error = core::convert::Infallible;
+/// #     type Time = Ticks;
+/// #     fn start<T>(&mut self, count: T) -> Result<(), Self::Error>
+/// #         where T: Into<Self::Time>
+/// #     {
+/// #         Ok(())
+/// #     }
+/// #     fn wait(&mut self) -> nb::Result<(), Self::Error> {
+/// #         Ok(())
+/// #     }
+/// # }
+/// #
+/// # #[derive(Clone, Copy, PartialEq, PartialOrd)]
+/// # pub struct Ticks(Num);
+/// # impl From<Nanoseconds> for Ticks {
+/// #     fn from(_: Nanoseconds) -> Self {
+/// #         Self(Num::from_num(0))
+/// #     }
+/// # }
+/// # impl core::ops::Add for Ticks {
+/// #     type Output = Self;
+/// #     fn add(self, rhs: Self) -> Self::Output {
+/// #         Self(self.0 + rhs.0)
+/// #     }
+/// # }
+/// # impl core::ops::Sub for Ticks {
+/// #     type Output = Self;
+/// #     fn sub(self, rhs: Self) -> Self::Output {
+/// #         Self(self.0 - rhs.0)
+/// #     }
+/// # }
+/// #
+/// # pub struct DelayToTicks;
+/// # impl stepper::motion_control::DelayToTicks<Num> for DelayToTicks {
+/// #     type Ticks = Ticks;
+/// #     type Error = core::convert::Infallible;
+/// #     fn delay_to_ticks(&self, delay: Num)
+/// #         -> Result<Self::Ticks, Self::Error>
+/// #     {
+/// #         Ok(Ticks(delay.int()))
+/// #     }
+/// # }
+/// #
+/// # fn axis() -> Result<
+/// #     stepper::Stepper<
+/// #         stepper::motion_control::SoftwareMotionControl<
+/// #             stepper::drivers::drv8825::DRV8825<
+/// #                 (), (), (), (), (), (), (), Pin, Pin,
+/// #             >,
+/// #             Timer,
+/// #             stepper::ramp_maker::Flat<Num>,
+/// #             DelayToTicks,
+/// #         >,
+/// #     >,
+/// #     (),
+/// # > {
+/// #     let stepper = stepper::Stepper::from_driver(
+/// #         stepper::drivers::drv8825::DRV8825::new(),
+/// #     )
+/// #         .enable_direction_control(
+/// #             Pin,
+/// #             stepper::Direction::Forward,
+/// #             &mut Timer,
+/// #         )
+/// #         .map_err(|_| ())?
+/// #         .enable_step_control(Pin)
+/// #         .enable_motion_control((
+/// #             Timer,
+/// #             stepper::ramp_maker::Flat::new(),
+/// #             DelayToTicks,
+/// #         ));
+/// #     Ok(stepper)
+/// # }
+/// #
+/// use stepper::MultiStepper;
+///
+/// // Both axes must have motion control enabled. How to do that is explained
+/// // in the documentation of `Stepper`. We're using a motion profile with a
+/// // constant velocity here (`ramp_maker::Flat`).
+/// let x = axis()?;
+/// let y = axis()?;
+///
+/// let mut plotter = MultiStepper::new([x, y]);
+///
+/// // Move 300 steps along the x axis and 100 steps along the y axis. The y
+/// // axis makes one step for every 3 steps of the x axis.
+/// let max_velocity = Num::from_num(0.001); // steps / tick
+/// plotter.move_by(max_velocity, [300, 100]).wait().map_err(|_| ())?;
+///
+/// let [x, y] = plotter.steppers_mut();
+/// assert_eq!(x.current_step().map_err(|_| ())?, 300);
+/// assert_eq!(y.current_step().map_err(|_| ())?, 100);
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiStepper<Driver, const N: usize> {
+    steppers: [Stepper<Driver>; N],
+}
+
+impl<Driver, const N: usize> MultiStepper<Driver, N> {
+    /// Create a new `MultiStepper` instance from a number of steppers
+    pub fn new(steppers: [Stepper<Driver>; N]) -> Self {
+        Self { steppers }
+    }
+
+    /// Access a reference to the wrapped steppers
+    pub fn steppers(&self) -> &[Stepper<Driver>; N] {
+        &self.steppers
+    }
+
+    /// Access a mutable reference to the wrapped steppers
+    pub fn steppers_mut(&mut self) -> &mut [Stepper<Driver>; N] {
+        &mut self.steppers
+    }
+
+    /// Release the wrapped steppers
+    pub fn release(self) -> [Stepper<Driver>; N] {
+        self.steppers
+    }
+
+    /// Move all axes by the given number of steps, along a straight line
+    ///
+    /// `deltas` contains the number of steps for each axis, relative to their
+    /// current position. The axis with the most steps to make moves at
+    /// `max_velocity`, all other axes move proportionally slower.
+    ///
+    /// To abort the motion, drop the returned future, then call
+    /// [`Stepper::abort`] for each axis.
+    pub fn move_by<'r>(
+        &'r mut self,
+        max_velocity: Driver::Velocity,
+        deltas: [i32; N],
+    ) -> MoveByFuture<'r, Driver, N>
+    where
+        Driver: MotionControl,
+    {
+        MoveByFuture::new(&mut self.steppers, max_velocity, deltas)
+    }
+
+    /// Update all axes once, without waiting for any of them
+    ///
+    /// This is meant for axes that move independently from each other. Start a
+    /// motion on each axis separately (for example using
+    /// [`MotionControl::move_to_position`] through [`Self::steppers_mut`]),
+    /// then call this method repeatedly, until it returns `false`.
+    ///
+    /// Calls [`MotionControl::update`] on every axis and returns `true`, if any
+    /// of them is still moving. If an axis returns an error, the remaining axes
+    /// are still updated, so they keep moving. The first error is returned,
+    /// together with the index of the axis it originated from.
+    pub fn poll_all(&mut self) -> Result<bool, AxisError<Driver::Error>>
+    where
+        Driver: MotionControl,
+    {
+        let mut still_moving = false;
+        let mut result = Ok(());
+
+        for (axis, stepper) in self.steppers.iter_mut().enumerate() {
+            match stepper.driver_mut().update() {
+                Ok(moving) => still_moving |= moving,
+                Err(error) => {
+                    if result.is_ok() {
+                        result = Err(AxisError { axis, error });
+                    }
+                }
+            }
+        }
+
+        result.map(|()| still_moving)
+    }
+}
+
+/// The "future" returned by [`MultiStepper::move_by`]
+///
+/// Please note that this type provides a custom API and does not implement
+/// [`core::future::Future`]. This might change, when using futures for embedded
+/// development becomes more practical.
+#[must_use]
+pub struct MoveByFuture<'r, Driver: MotionControl, const N: usize> {
+    steppers: &'r mut [Stepper<Driver>; N],
+    max_velocity: Driver::Velocity,
+    deltas: [i32; N],
+
+    // The number of steps of the major axis, which steps every time.
+    major: u32,
+
+    // Bresenham error terms for each axis.
+    errors: [u32; N],
+
+    steps_made: u32,
+    stepping: bool,
+}
+
+impl<'r, Driver, const N: usize> MoveByFuture<'r, Driver, N>
+where
+    Driver: MotionControl,
+{
+    /// Create new instance of `MoveByFuture`
+    ///
+    /// This constructor is public to provide maximum flexibility for
+    /// non-standard use cases. Most users can ignore this and just use
+    /// [`MultiStepper::move_by`] instead.
+    pub fn new(
+        steppers: &'r mut [Stepper<Driver>; N],
+        max_velocity: Driver::Velocity,
+        deltas: [i32; N],
+    ) -> Self {
+        let major = deltas
+            .iter()
+            .map(|delta| delta.unsigned_abs())
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            steppers,
+            max_velocity,
+            deltas,
+            major,
+            // Starting at half the major axis' steps puts the minor axes'
+            // steps in the middle of the respective interval, instead of at
+            // its beginning.
+            errors: [major / 2; N],
+            steps_made: 0,
+            stepping: false,
+        }
+    }
+
+    /// Poll the future
+    ///
+    /// The future must be polled for the operation to make progress. The
+    /// operation won't start, until this method has been called once. Returns
+    /// [`Poll::Pending`], if the operation is not finished yet, or
+    /// [`Poll::Ready`], once it is.
+    ///
+    /// If this method returns [`Poll::Pending`], the user can opt to keep
+    /// calling it at a high frequency (see [`Self::wait`]) until the operation
+    /// completes, or set up an interrupt that fires once a timer finishes
+    /// counting down, and call this method again once it does.
+    pub fn poll(&mut self) -> Poll<Result<(), Driver::Error>> {
+        loop {
+            if self.stepping {
+                // Update every axis, even if one of them is still moving, to
+                // keep them going at the same time.
+                let mut still_moving = false;
+                for stepper in self.steppers.iter_mut() {
+                    still_moving |= stepper.driver_mut().update()?;
+                }
+
+                if still_moving {
+                    return Poll::Pending;
+                }
+
+                self.stepping = false;
+                self.steps_made += 1;
+            }
+
+            if self.steps_made >= self.major {
+                return Poll::Ready(Ok(()));
+            }
+
+            // Start the next step of the major axis, as well as the steps of
+            // all minor axes that have fallen behind the line.
+            for ((stepper, delta), error) in self
+                .steppers
+                .iter_mut()
+                .zip(self.deltas.iter())
+                .zip(self.errors.iter_mut())
+            {
+                *error += delta.unsigned_abs();
+                if *error < self.major {
+                    continue;
+                }
+                *error -= self.major;
+
+                let driver = stepper.driver_mut();
+                let target_step = driver.current_step()? + delta.signum();
+                driver.move_to_position(self.max_velocity, target_step)?;
+            }
+
+            self.stepping = true;
+        }
+    }
+
+    /// Wait until the operation completes
+    ///
+    /// This method will call [`Self::poll`] in a busy loop until the operation
+    /// has finished. See [`Self::wait_with`], if you need to do something
+    /// between calls.
+    pub fn wait(&mut self) -> Result<(), Driver::Error> {
+        self.wait_with(|| {})
+    }
+
+    /// Wait until the operation completes, calling `idle` between polls
+    ///
+    /// Works like [`Self::wait`], except that `idle` is called every time
+    /// [`Self::poll`] returns [`Poll::Pending`]. See
+    /// [`StepFuture::wait_with`] for details.
+    ///
+    /// [`StepFuture::wait_with`]: crate::StepFuture::wait_with
+    pub fn wait_with<F>(&mut self, mut idle: F) -> Result<(), Driver::Error>
+    where
+        F: FnMut(),
+    {
+        loop {
+            if let Poll::Ready(result) = self.poll() {
+                return result;
+            }
+
+            idle();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        motion_control,
+        test_util::{mock_motion_control, Log, MockMotionControl},
+        traits::MotionControl as _,
+        Stepper,
+    };
+
+    use super::{AxisError, MultiStepper};
+
+    type Axis = Stepper<MockMotionControl>;
+
+    fn axis(log: &Log) -> Axis {
+        Stepper::from_driver(mock_motion_control(log))
+    }
+
+    #[test]
+    fn move_by_should_keep_axes_on_a_straight_line() {
+        let mut multi_stepper =
+            MultiStepper::new([axis(&Log::new()), axis(&Log::new())]);
+
+        let mut future = multi_stepper.move_by(0.01, [6, -3]);
+        while future.poll().is_pending() {
+            let [x, y] = &mut *future.steppers;
+            let x = x.current_step().unwrap();
+            let y = y.current_step().unwrap();
+
+            // How far `y` is off the line, in steps of `y`, times 6.
+            assert!((y * 6 + x * 3).abs() <= 6);
+        }
+
+        let [x, y] = multi_stepper.steppers_mut();
+        assert_eq!(x.current_step().unwrap(), 6);
+        assert_eq!(y.current_step().unwrap(), -3);
+    }
+
+    #[test]
+    fn poll_all_should_update_axes_until_all_are_finished() {
+        let mut multi_stepper =
+            MultiStepper::new([axis(&Log::new()), axis(&Log::new())]);
+
+        let [x, y] = multi_stepper.steppers_mut();
+        x.driver_mut().move_to_position(0.01, 2).unwrap();
+        y.driver_mut().move_to_position(0.01, -5).unwrap();
+
+        let mut polls = 0;
+        while multi_stepper.poll_all().unwrap() {
+            polls += 1;
+        }
+
+        let [x, y] = multi_stepper.steppers_mut();
+        assert_eq!(x.current_step().unwrap(), 2);
+        assert_eq!(y.current_step().unwrap(), -5);
+        assert!(x.is_idle());
+        assert!(y.is_idle());
+
+        // The axis with more steps to make keeps the others waiting.
+        let mut single = axis(&Log::new());
+        single.driver_mut().move_to_position(0.01, -5).unwrap();
+        let mut single_polls = 0;
+        while single.driver_mut().update().unwrap() {
+            single_polls += 1;
+        }
+        assert_eq!(polls, single_polls);
+    }
+
+    #[test]
+    fn poll_all_should_report_axis_of_error() {
+        let mut multi_stepper =
+            MultiStepper::new([axis(&Log::new()), axis(&Log::new())]);
+
+        // One step per tick is faster than the mock driver's STEP pulse.
+        let [x, y] = multi_stepper.steppers_mut();
+        x.driver_mut().move_to_position(0.01, 2).unwrap();
+        y.driver_mut().move_to_position(1.0, 2).unwrap();
+
+        let error = loop {
+            match multi_stepper.poll_all() {
+                Ok(true) => continue,
+                Ok(false) => panic!("Expected error"),
+                Err(error) => break error,
+            }
+        };
+
+        assert_eq!(
+            error,
+            AxisError {
+                axis: 1,
+                error: motion_control::Error::DelayTooShort,
+            }
+        );
+
+        // The other axis keeps moving.
+        while multi_stepper.poll_all() != Ok(false) {}
+        let [x, _] = multi_stepper.steppers_mut();
+        assert_eq!(x.current_step().unwrap(), 2);
+    }
+}
@@ -0,0 +1,149 @@
+use core::task::Poll;
+
+use crate::{traits::MotionControl, Direction};
+
+/// The "future" returned by [`Stepper::rotate`]
+///
+/// Please note that this type provides a custom API and does not implement
+/// [`core::future::Future`]. This might change, when using futures for embedded
+/// development becomes more practical.
+///
+/// [`Stepper::rotate`]: crate::Stepper::rotate
+#[must_use]
+pub struct RotateFuture<Driver: MotionControl> {
+    driver: Driver,
+    state: State<Driver::Velocity>,
+}
+
+impl<Driver> RotateFuture<Driver>
+where
+    Driver: MotionControl,
+{
+    /// Create new instance of `RotateFuture`
+    ///
+    /// This constructor is public to provide maximum flexibility for
+    /// non-standard use cases. Most users can ignore this and just use
+    /// [`Stepper::rotate`] instead.
+    ///
+    /// [`Stepper::rotate`]: crate::Stepper::rotate
+    pub fn new(
+        driver: Driver,
+        direction: Direction,
+        velocity: Driver::Velocity,
+    ) -> Self {
+        Self {
+            driver,
+            state: State::Initial {
+                direction,
+                velocity,
+            },
+        }
+    }
+
+    /// Poll the future
+    ///
+    /// The future must be polled for the operation to make progress. The
+    /// operation won't start, until this method has been called once. Returns
+    /// [`Poll::Pending`], while the motor is rotating, or [`Poll::Ready`], once
+    /// the rotation has ended.
+    ///
+    /// The rotation only ends, if it is aborted (see [`Self::abort`]), or if an
+    /// error occurs.
+    ///
+    /// If this method returns [`Poll::Pending`], the user can opt to keep
+    /// calling it at a high frequency (see [`Self::wait`]), or set up an
+    /// interrupt that fires once the timer finishes counting down, and call
+    /// this method again once it does.
+    pub fn poll(&mut self) -> Poll<Result<(), Driver::Error>> {
+        match self.state {
+            State::Initial {
+                direction,
+                velocity,
+            } => {
+                self.driver.rotate(direction, velocity)?;
+                self.state = State::Rotating;
+                Poll::Pending
+            }
+            State::Rotating => {
+                let still_moving = self.driver.update()?;
+                if still_moving {
+                    Poll::Pending
+                } else {
+                    self.state = State::Finished;
+                    Poll::Ready(Ok(()))
+                }
+            }
+            State::Finished => Poll::Ready(Ok(())),
+        }
+    }
+
+    /// Wait until the operation completes
+    ///
+    /// This method will call [`Self::poll`] in a busy loop until the operation
+    /// has finished. Since the rotation never ends on its own, this only
+    /// returns, if an error occurs. See [`Self::wait_with`], if you need to do
+    /// something between calls.
+    pub fn wait(&mut self) -> Result<(), Driver::Error> {
+        self.wait_with(|| {})
+    }
+
+    /// Wait until the operation completes, calling `idle` between polls
+    ///
+    /// Works like [`Self::wait`], except that `idle` is called every time
+    /// [`Self::poll`] returns [`Poll::Pending`]. This can be used to yield to
+    /// a scheduler, or to wait for an interrupt, instead of spinning.
+    pub fn wait_with<F>(&mut self, mut idle: F) -> Result<(), Driver::Error>
+    where
+        F: FnMut(),
+    {
+        loop {
+            if let Poll::Ready(result) = self.poll() {
+                return result;
+            }
+
+            idle();
+        }
+    }
+
+    /// Abort the rotation immediately
+    ///
+    /// See [`MotionControl::abort`] for details. Once this method has returned
+    /// successfully, [`Self::poll`] returns [`Poll::Ready`].
+    pub fn abort(&mut self) -> Result<(), Driver::Error> {
+        self.driver.abort()?;
+        self.state = State::Finished;
+        Ok(())
+    }
+
+    /// Change the maximum velocity of the ongoing motion
+    ///
+    /// See [`MotionControl::set_max_speed`] for details. If the motion hasn't
+    /// been started yet, it will be started with the new maximum velocity.
+    pub fn set_max_speed(
+        &mut self,
+        max_velocity: Driver::Velocity,
+    ) -> Result<(), Driver::Error> {
+        match &mut self.state {
+            State::Initial { velocity, .. } => {
+                *velocity = max_velocity;
+                Ok(())
+            }
+            State::Rotating => self.driver.set_max_speed(max_velocity),
+            State::Finished => Ok(()),
+        }
+    }
+
+    /// Drop the future and release the resources that were moved into it
+    pub fn release(self) -> Driver {
+        self.driver
+    }
+}
+
+enum State<Velocity> {
+    Initial {
+        direction: Direction,
+        velocity: Velocity,
+    },
+    Rotating,
+    Finished,
+}
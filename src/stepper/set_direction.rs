@@ -58,6 +58,7 @@ where
     /// calling it at a high frequency (see [`Self::wait`]) until the operation
     /// completes, or set up an interrupt that fires once the timer finishes
     /// counting down, and call this method again once it does.
+    #[allow(clippy::type_complexity)]
     pub fn poll(
         &mut self,
     ) -> Poll<
@@ -73,27 +74,34 @@ where
     > {
         match self.state {
             State::Initial => {
-                match self.direction {
-                    Direction::Forward => self
-                        .driver
-                        .dir()
-                        .map_err(|err| SignalError::PinUnavailable(err))?
-                        .set_high()
-                        .map_err(|err| SignalError::Pin(err))?,
-                    Direction::Backward => self
-                        .driver
-                        .dir()
-                        .map_err(|err| SignalError::PinUnavailable(err))?
-                        .set_low()
-                        .map_err(|err| SignalError::Pin(err))?,
+                let forward = self.driver.dir_polarity();
+                let level = match self.direction {
+                    Direction::Forward => forward,
+                    Direction::Backward => !forward,
+                };
+                self.driver
+                    .dir()
+                    .map_err(SignalError::PinUnavailable)?
+                    .set_state(level)
+                    .map_err(SignalError::Pin)?;
+
+                let setup_time = self.driver.setup_time();
+                if setup_time.0 == 0 {
+                    // Depending on the timer, starting it with zero ticks
+                    // might make it finish immediately or never. There's
+                    // nothing to wait for anyway.
+                    self.state = State::Finished;
+                    return Poll::Ready(Ok(()));
                 }
 
-                let ticks: Timer::Time = Driver::SETUP_TIME
-                    .try_into()
-                    .map_err(|err| SignalError::NanosecondsToTicks(err))?;
-                self.timer
-                    .start(ticks)
-                    .map_err(|err| SignalError::Timer(err))?;
+                let ticks: Timer::Time =
+                    setup_time.try_into().map_err(|err| {
+                        SignalError::NanosecondsToTicks {
+                            nanoseconds: setup_time.0,
+                            error: err,
+                        }
+                    })?;
+                self.timer.start(ticks).map_err(SignalError::Timer)?;
 
                 self.state = State::DirectionSet;
                 Poll::Pending
@@ -116,7 +124,9 @@ where
     /// Wait until the operation completes
     ///
     /// This method will call [`Self::poll`] in a busy loop until the operation
-    /// has finished.
+    /// has finished. See [`Self::wait_with`], if you need to do something
+    /// between calls.
+    #[allow(clippy::type_complexity)]
     pub fn wait(
         &mut self,
     ) -> Result<
@@ -128,10 +138,38 @@ where
             Timer::Error,
         >,
     > {
+        self.wait_with(|| {})
+    }
+
+    /// Wait until the operation completes, calling `idle` between polls
+    ///
+    /// Works like [`Self::wait`], except that `idle` is called every time
+    /// [`Self::poll`] returns [`Poll::Pending`]. See
+    /// [`StepFuture::wait_with`] for details.
+    ///
+    /// [`StepFuture::wait_with`]: crate::StepFuture::wait_with
+    #[allow(clippy::type_complexity)]
+    pub fn wait_with<F>(
+        &mut self,
+        mut idle: F,
+    ) -> Result<
+        (),
+        SignalError<
+            Driver::Error,
+            <Driver::Dir as OutputPin>::Error,
+            <Timer::Time as TryFrom<Nanoseconds>>::Error,
+            Timer::Error,
+        >,
+    >
+    where
+        F: FnMut(),
+    {
         loop {
             if let Poll::Ready(result) = self.poll() {
                 return result;
             }
+
+            idle();
         }
     }
 
@@ -141,6 +179,18 @@ where
     }
 }
 
+impl<Driver, Timer> SetDirectionFuture<Driver, Timer> {
+    /// Access a reference to the wrapped driver
+    pub(crate) fn driver(&self) -> &Driver {
+        &self.driver
+    }
+
+    /// Access a mutable reference to the wrapped driver
+    pub(crate) fn driver_mut(&mut self) -> &mut Driver {
+        &mut self.driver
+    }
+}
+
 enum State {
     Initial,
     DirectionSet,
@@ -62,6 +62,7 @@ where
     /// calling it at a high frequency (see [`Self::wait`]) until the operation
     /// completes, or set up an interrupt that fires once the timer finishes
     /// counting down, and call this method again once it does.
+    #[allow(clippy::type_complexity)]
     pub fn poll(
         &mut self,
     ) -> Poll<
@@ -77,35 +78,38 @@ where
     > {
         match self.state {
             State::Initial => {
+                if Driver::MODE_PIN_SETTLE_TIME.0 != 0 {
+                    return self.apply_mode_pin(0);
+                }
+
                 self.driver
                     .apply_mode_config(self.step_mode)
-                    .map_err(|err| SignalError::Pin(err))?;
+                    .map_err(SignalError::Pin)?;
 
-                let ticks: Timer::Time = Driver::SETUP_TIME
-                    .try_into()
-                    .map_err(|err| SignalError::NanosecondsToTicks(err))?;
-                self.timer
-                    .start(ticks)
-                    .map_err(|err| SignalError::Timer(err))?;
-
-                self.state = State::ApplyingConfig;
-                Poll::Pending
+                self.start_setup_time()
             }
+            State::SettlingModePin { next } => match self.timer.wait() {
+                Ok(()) => self.apply_mode_pin(next),
+                Err(nb::Error::Other(err)) => {
+                    self.state = State::Finished;
+                    Poll::Ready(Err(SignalError::Timer(err)))
+                }
+                Err(nb::Error::WouldBlock) => Poll::Pending,
+            },
             State::ApplyingConfig => match self.timer.wait() {
                 Ok(()) => {
-                    self.driver
-                        .enable_driver()
-                        .map_err(|err| SignalError::Pin(err))?;
+                    self.driver.enable_driver().map_err(SignalError::Pin)?;
 
                     let ticks: Timer::Time = Driver::HOLD_TIME
                         .try_into()
-                        .map_err(|err| SignalError::NanosecondsToTicks(err))?;
-                    self.timer
-                        .start(ticks)
-                        .map_err(|err| SignalError::Timer(err))?;
+                        .map_err(|err| SignalError::NanosecondsToTicks {
+                            nanoseconds: Driver::HOLD_TIME.0,
+                            error: err,
+                        })?;
+                    self.timer.start(ticks).map_err(SignalError::Timer)?;
 
                     self.state = State::EnablingDriver;
-                    Poll::Ready(Ok(()))
+                    Poll::Pending
                 }
                 Err(nb::Error::Other(err)) => {
                     self.state = State::Finished;
@@ -129,10 +133,83 @@ where
         }
     }
 
+    /// Set one mode pin, then wait for it to settle, unless it was the last
+    #[allow(clippy::type_complexity)]
+    fn apply_mode_pin(
+        &mut self,
+        index: usize,
+    ) -> Poll<
+        Result<
+            (),
+            SignalError<
+                Infallible, // only applies to `SetDirection`, `Step`
+                Driver::Error,
+                <Timer::Time as TryFrom<Nanoseconds>>::Error,
+                Timer::Error,
+            >,
+        >,
+    > {
+        let finished = self
+            .driver
+            .apply_mode_pin(self.step_mode, index)
+            .map_err(SignalError::Pin)?;
+        if finished {
+            return self.start_setup_time();
+        }
+
+        let ticks: Timer::Time = Driver::MODE_PIN_SETTLE_TIME
+            .try_into()
+            .map_err(|err| SignalError::NanosecondsToTicks {
+                nanoseconds: Driver::MODE_PIN_SETTLE_TIME.0,
+                error: err,
+            })?;
+        self.timer.start(ticks).map_err(SignalError::Timer)?;
+
+        self.state = State::SettlingModePin { next: index + 1 };
+        Poll::Pending
+    }
+
+    /// Start waiting, after the step mode configuration has been applied
+    #[allow(clippy::type_complexity)]
+    fn start_setup_time(
+        &mut self,
+    ) -> Poll<
+        Result<
+            (),
+            SignalError<
+                Infallible, // only applies to `SetDirection`, `Step`
+                Driver::Error,
+                <Timer::Time as TryFrom<Nanoseconds>>::Error,
+                Timer::Error,
+            >,
+        >,
+    > {
+        // Both the setup time and the reset time start now, so waiting for the
+        // longer one satisfies both.
+        let delay = if Driver::RESET_TIME > Driver::SETUP_TIME {
+            Driver::RESET_TIME
+        } else {
+            Driver::SETUP_TIME
+        };
+
+        let ticks: Timer::Time = delay.try_into().map_err(|err| {
+            SignalError::NanosecondsToTicks {
+                nanoseconds: delay.0,
+                error: err,
+            }
+        })?;
+        self.timer.start(ticks).map_err(SignalError::Timer)?;
+
+        self.state = State::ApplyingConfig;
+        Poll::Pending
+    }
+
     /// Wait until the operation completes
     ///
     /// This method will call [`Self::poll`] in a busy loop until the operation
-    /// has finished.
+    /// has finished. See [`Self::wait_with`], if you need to do something
+    /// between calls.
+    #[allow(clippy::type_complexity)]
     pub fn wait(
         &mut self,
     ) -> Result<
@@ -144,10 +221,38 @@ where
             Timer::Error,
         >,
     > {
+        self.wait_with(|| {})
+    }
+
+    /// Wait until the operation completes, calling `idle` between polls
+    ///
+    /// Works like [`Self::wait`], except that `idle` is called every time
+    /// [`Self::poll`] returns [`Poll::Pending`]. See
+    /// [`StepFuture::wait_with`] for details.
+    ///
+    /// [`StepFuture::wait_with`]: crate::StepFuture::wait_with
+    #[allow(clippy::type_complexity)]
+    pub fn wait_with<F>(
+        &mut self,
+        mut idle: F,
+    ) -> Result<
+        (),
+        SignalError<
+            Infallible, // only applies to `SetDirection`, `Step`
+            Driver::Error,
+            <Timer::Time as TryFrom<Nanoseconds>>::Error,
+            Timer::Error,
+        >,
+    >
+    where
+        F: FnMut(),
+    {
         loop {
             if let Poll::Ready(result) = self.poll() {
                 return result;
             }
+
+            idle();
         }
     }
 
@@ -159,7 +264,191 @@ where
 
 enum State {
     Initial,
+    SettlingModePin { next: usize },
     ApplyingConfig,
     EnablingDriver,
     Finished,
 }
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use embedded_time::duration::Nanoseconds;
+
+    use embedded_hal::digital::PinState::{High, Low};
+
+    use crate::{
+        drivers::mode_pins::{ModePinError, A4988},
+        step_mode::{StepMode16, StepMode256},
+        test_util::{Event, Log, MockDriver, MockPin, MockTimer},
+        traits::SetStepMode,
+    };
+
+    use super::SetStepModeFuture;
+
+    #[test]
+    fn set_step_mode_should_wait_for_setup_and_hold_time() {
+        let log = Log::new();
+        let driver = MockDriver::new(&log);
+        assert_eq!(driver.step_mode(), None);
+
+        let mut future = SetStepModeFuture::new(
+            StepMode256::M16,
+            driver,
+            MockTimer::new(&log).with_polls(2),
+        );
+
+        while future.poll().is_pending() {}
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::ApplyModeConfig(16),
+                Event::TimerStart(1),
+                Event::TimerFinished,
+                Event::EnableDriver,
+                Event::TimerStart(1),
+                Event::TimerFinished,
+            ]
+        );
+
+        let (driver, _) = future.release();
+        assert_eq!(driver.step_mode(), Some(StepMode256::M16));
+    }
+
+    #[test]
+    fn set_step_mode_should_hold_driver_in_reset_for_reset_time() {
+        struct ResetDriver(MockDriver);
+
+        impl SetStepMode for ResetDriver {
+            const SETUP_TIME: Nanoseconds = Nanoseconds(1_000);
+            const HOLD_TIME: Nanoseconds = Nanoseconds(1_000);
+            const RESET_TIME: Nanoseconds = Nanoseconds(5_000);
+
+            type Error = Infallible;
+            type StepMode = StepMode256;
+
+            fn apply_mode_config(
+                &mut self,
+                step_mode: Self::StepMode,
+            ) -> Result<(), Self::Error> {
+                self.0.apply_mode_config(step_mode)
+            }
+
+            fn enable_driver(&mut self) -> Result<(), Self::Error> {
+                self.0.enable_driver()
+            }
+
+            fn step_mode(&self) -> Option<Self::StepMode> {
+                self.0.step_mode()
+            }
+        }
+
+        let log = Log::new();
+        let mut future = SetStepModeFuture::new(
+            StepMode256::M16,
+            ResetDriver(MockDriver::new(&log)),
+            MockTimer::new(&log).with_polls(2),
+        );
+
+        while future.poll().is_pending() {}
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::ApplyModeConfig(16),
+                Event::TimerStart(5),
+                Event::TimerFinished,
+                Event::EnableDriver,
+                Event::TimerStart(1),
+                Event::TimerFinished,
+            ]
+        );
+    }
+
+    #[test]
+    fn set_step_mode_should_change_mode_pins_one_at_a_time() {
+        struct SequencedDriver {
+            reset: MockPin,
+            pins: [MockPin; 3],
+        }
+
+        impl SetStepMode for SequencedDriver {
+            const SETUP_TIME: Nanoseconds = Nanoseconds(1_000);
+            const HOLD_TIME: Nanoseconds = Nanoseconds(1_000);
+            const MODE_PIN_SETTLE_TIME: Nanoseconds = Nanoseconds(2_000);
+
+            type Error = ModePinError<Infallible>;
+            type StepMode = StepMode16;
+
+            fn apply_mode_config(
+                &mut self,
+                step_mode: Self::StepMode,
+            ) -> Result<(), Self::Error> {
+                let [pin1, pin2, pin3] = &mut self.pins;
+                A4988.apply_mode_config(
+                    step_mode,
+                    &mut self.reset,
+                    [pin1, pin2, pin3],
+                )
+            }
+
+            fn apply_mode_pin(
+                &mut self,
+                step_mode: Self::StepMode,
+                index: usize,
+            ) -> Result<bool, Self::Error> {
+                let [pin1, pin2, pin3] = &mut self.pins;
+                A4988.apply_mode_pin(
+                    step_mode,
+                    &mut self.reset,
+                    [pin1, pin2, pin3],
+                    index,
+                )
+            }
+
+            fn enable_driver(&mut self) -> Result<(), Self::Error> {
+                A4988
+                    .enable_driver(&mut self.reset)
+                    .map_err(ModePinError::Pin)
+            }
+        }
+
+        let log = Log::new();
+        let driver = SequencedDriver {
+            reset: MockPin::new("reset", &log),
+            pins: [
+                MockPin::new("ms1", &log),
+                MockPin::new("ms2", &log),
+                MockPin::new("ms3", &log),
+            ],
+        };
+        let mut future = SetStepModeFuture::new(
+            StepMode16::M8,
+            driver,
+            MockTimer::new(&log).with_polls(1),
+        );
+
+        future.wait().unwrap();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("reset", Low),
+                Event::Pin("ms1", High),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                Event::Pin("ms2", High),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                Event::Pin("ms3", Low),
+                Event::TimerStart(1),
+                Event::TimerFinished,
+                Event::Pin("reset", High),
+                Event::TimerStart(1),
+                Event::TimerFinished,
+            ]
+        );
+    }
+}
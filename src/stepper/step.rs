@@ -3,6 +3,13 @@ use core::{
     task::Poll,
 };
 
+#[cfg(feature = "async")]
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Waker},
+};
+
 use embedded_hal::{digital::blocking::OutputPin, timer::nb as timer};
 use embedded_time::duration::Nanoseconds;
 
@@ -12,15 +19,16 @@ use super::SignalError;
 
 /// The "future" returned by [`Stepper::step`]
 ///
-/// Please note that this type provides a custom API and does not implement
-/// [`core::future::Future`]. This might change, when using futures for embedded
-/// development becomes more practical.
+/// Please note that this type provides a custom API and, by default, does not
+/// implement [`core::future::Future`]. If you enable the `async` feature, it
+/// implements `Future` in addition to the custom API, so it can be `.await`ed.
 ///
 /// [`Stepper::step`]: crate::Stepper::step
 #[must_use]
 pub struct StepFuture<Driver, Timer> {
     driver: Driver,
     timer: Timer,
+    pulse_length: Nanoseconds,
     state: State,
 }
 
@@ -39,12 +47,53 @@ where
     /// [`Stepper::step`]: crate::Stepper::step
     pub fn new(driver: Driver, timer: Timer) -> Self {
         Self {
+            pulse_length: driver.pulse_length(),
             driver,
             timer,
             state: State::Initial,
         }
     }
 
+    /// Create new instance of `StepFuture` for a STEP pulse that has already
+    /// been started
+    ///
+    /// This is intended for advanced use cases, where the user starts the STEP
+    /// pulse themselves (for example from an interrupt handler) and only wants
+    /// to use `StepFuture` to end it. The first call to [`Self::poll`] then
+    /// waits for the timer, instead of starting the pulse.
+    ///
+    /// The caller must make sure that, before this future is polled for the
+    /// first time:
+    ///
+    /// - The STEP pin has been set to the driver's pulse polarity (see
+    ///   [`Step::pulse_polarity`]).
+    /// - The timer has been started with a duration of at least
+    ///   [`Step::PULSE_LENGTH`], after the STEP pin has been set.
+    ///
+    /// If these preconditions aren't met, the STEP pulse might be shorter than
+    /// the driver requires, or not happen at all.
+    pub fn with_started_timer(driver: Driver, timer: Timer) -> Self {
+        Self {
+            pulse_length: driver.pulse_length(),
+            driver,
+            timer,
+            state: State::PulseStarted,
+        }
+    }
+
+    /// Override the length of the STEP pulse
+    ///
+    /// By default, the STEP pulse lasts for [`Step::PULSE_LENGTH`]. This
+    /// method sets a different length, which can be tuned at runtime, without
+    /// recompiling. Please note that a pulse length shorter than the one
+    /// required by the driver might cause steps to be missed.
+    ///
+    /// Has no effect, if the STEP pulse has already been started.
+    pub fn with_pulse_length(mut self, pulse_length: Nanoseconds) -> Self {
+        self.pulse_length = pulse_length;
+        self
+    }
+
     /// Poll the future
     ///
     /// The future must be polled for the operation to make progress. The
@@ -56,6 +105,17 @@ where
     /// calling it at a high frequency (see [`Self::wait`]) until the operation
     /// completes, or set up an interrupt that fires once the timer finishes
     /// counting down, and call this method again once it does.
+    ///
+    /// If the timer fails while the STEP pulse is active, the pulse is ended
+    /// before the error is returned. Any further calls return
+    /// [`SignalError::AlreadyFailed`], instead of reporting success.
+    ///
+    /// Starting the timer is different: If that fails, the error is returned,
+    /// but the STEP pulse remains active, and the next call tries to start the
+    /// timer again. This is meant for timers that can't be started while they
+    /// are still counting down. Stop polling and drop the future, if retrying
+    /// isn't desired, but keep in mind that this leaves the STEP pin active.
+    #[allow(clippy::type_complexity)]
     pub fn poll(
         &mut self,
     ) -> Poll<
@@ -69,21 +129,47 @@ where
             >,
         >,
     > {
+        let polarity = self.driver.pulse_polarity();
+
         match self.state {
-            State::Initial => {
-                // Start step pulse
-                self.driver
-                    .step()
-                    .map_err(|err| SignalError::PinUnavailable(err))?
-                    .set_high()
-                    .map_err(|err| SignalError::Pin(err))?;
-
-                let ticks: Timer::Time = Driver::PULSE_LENGTH
-                    .try_into()
-                    .map_err(|err| SignalError::NanosecondsToTicks(err))?;
-                self.timer
-                    .start(ticks)
-                    .map_err(|err| SignalError::Timer(err))?;
+            State::Initial if self.pulse_length.0 == 0 => {
+                // Depending on the timer, starting it with zero ticks might
+                // make it finish immediately or never. Drivers that don't need
+                // the pulse to last (for example, because setting the pin is a
+                // bus transaction that has completed, once it returns) get a
+                // pulse without the timer.
+                let pin =
+                    self.driver.step().map_err(SignalError::PinUnavailable)?;
+                pin.set_state(polarity).map_err(SignalError::Pin)?;
+                pin.set_state(!polarity).map_err(SignalError::Pin)?;
+
+                self.state = State::Finished;
+                Poll::Ready(Ok(()))
+            }
+            State::Initial | State::PulseActive => {
+                let ticks: Timer::Time =
+                    self.pulse_length.try_into().map_err(|err| {
+                        SignalError::NanosecondsToTicks {
+                            nanoseconds: self.pulse_length.0,
+                            error: err,
+                        }
+                    })?;
+
+                if let State::Initial = self.state {
+                    // Start step pulse
+                    self.driver
+                        .step()
+                        .map_err(SignalError::PinUnavailable)?
+                        .set_state(polarity)
+                        .map_err(SignalError::Pin)?;
+                    self.state = State::PulseActive;
+                }
+
+                // `CountDown::start` isn't an `nb` operation in the version of
+                // `embedded-hal` we use, so a timer that can't be started right
+                // now has to return an error. The STEP pin stays active, and
+                // the next call retries, instead of starting another pulse.
+                self.timer.start(ticks).map_err(SignalError::Timer)?;
 
                 self.state = State::PulseStarted;
                 Poll::Pending
@@ -94,28 +180,40 @@ where
                         // End step pulse
                         self.driver
                             .step()
-                            .map_err(|err| SignalError::PinUnavailable(err))?
-                            .set_low()
-                            .map_err(|err| SignalError::Pin(err))?;
+                            .map_err(SignalError::PinUnavailable)?
+                            .set_state(!polarity)
+                            .map_err(SignalError::Pin)?;
 
                         self.state = State::Finished;
                         Poll::Ready(Ok(()))
                     }
                     Err(nb::Error::Other(err)) => {
-                        self.state = State::Finished;
+                        self.state = State::Failed;
+
+                        // We don't know how long the pulse has lasted, but we
+                        // can't leave the STEP pin in its active state.
+                        self.driver
+                            .step()
+                            .map_err(SignalError::PinUnavailable)?
+                            .set_state(!polarity)
+                            .map_err(SignalError::Pin)?;
+
                         Poll::Ready(Err(SignalError::Timer(err)))
                     }
                     Err(nb::Error::WouldBlock) => Poll::Pending,
                 }
             }
             State::Finished => Poll::Ready(Ok(())),
+            State::Failed => Poll::Ready(Err(SignalError::AlreadyFailed)),
         }
     }
 
     /// Wait until the operation completes
     ///
     /// This method will call [`Self::poll`] in a busy loop until the operation
-    /// has finished.
+    /// has finished. See [`Self::wait_with`], if you need to do something
+    /// between calls.
+    #[allow(clippy::type_complexity)]
     pub fn wait(
         &mut self,
     ) -> Result<
@@ -127,10 +225,37 @@ where
             Timer::Error,
         >,
     > {
+        self.wait_with(|| {})
+    }
+
+    /// Wait until the operation completes, calling `idle` between polls
+    ///
+    /// Works like [`Self::wait`], except that `idle` is called every time
+    /// [`Self::poll`] returns [`Poll::Pending`]. This can be used to yield to
+    /// a scheduler, or to wait for an interrupt (for example, using the WFI
+    /// instruction on Cortex-M), instead of spinning.
+    #[allow(clippy::type_complexity)]
+    pub fn wait_with<F>(
+        &mut self,
+        mut idle: F,
+    ) -> Result<
+        (),
+        SignalError<
+            Driver::Error,
+            <Driver::Step as OutputPin>::Error,
+            <Timer::Time as TryFrom<Nanoseconds>>::Error,
+            Timer::Error,
+        >,
+    >
+    where
+        F: FnMut(),
+    {
         loop {
             if let Poll::Ready(result) = self.poll() {
                 return result;
             }
+
+            idle();
         }
     }
 
@@ -138,10 +263,539 @@ where
     pub fn release(self) -> (Driver, Timer) {
         (self.driver, self.timer)
     }
+
+    /// Get woken by the timer's interrupt, instead of busy-polling
+    ///
+    /// When `.await`ed, `StepFuture` asks the executor to poll it again right
+    /// away, as long as the timer is counting down, since [`timer::CountDown`]
+    /// provides no way to get notified. That keeps the CPU busy, which gets
+    /// expensive at high step rates.
+    ///
+    /// The future returned by this method registers the task's waker with
+    /// `timer_waker` instead, every time it is polled. It relies on the timer's
+    /// interrupt to wake the task, once the timer has finished counting down.
+    /// The interrupt glue needs to:
+    ///
+    /// 1. Enable the timer's interrupt, before the future is polled for the
+    ///    first time.
+    /// 2. In the interrupt handler, clear the interrupt flag, without resetting
+    ///    the state that makes [`timer::CountDown::wait`] return `Ok`.
+    /// 3. Then, wake the waker that was registered last.
+    ///
+    /// On a microcontroller, `embassy_sync`'s `AtomicWaker` or a similar
+    /// primitive can hold the waker, as it's safe to use from the interrupt
+    /// handler. Only available with the `async` feature.
+    ///
+    /// ``` rust
+    /// # use core::convert::Infallible;
+    /// # use std::{sync::Mutex, task::Waker};
+    /// #
+    /// # use stepper::{
+    /// #     embedded_hal::{digital::blocking::OutputPin, timer::nb as timer},
+    /// #     embedded_time::duration::Nanoseconds,
+    /// #     traits::Step,
+    /// #     StepFuture,
+    /// # };
+    /// #
+    /// # struct Pin;
+    /// # impl OutputPin for Pin {
+    /// #     type Error = Infallible;
+    /// #     fn set_low(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// #     fn set_high(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// #
+    /// # struct Driver(Pin);
+    /// # impl Step for Driver {
+    /// #     const PULSE_LENGTH: Nanoseconds = Nanoseconds(1000);
+    /// #     type Step = Pin;
+    /// #     type Error = Infallible;
+    /// #     fn step(&mut self) -> Result<&mut Pin, Infallible> { Ok(&mut self.0) }
+    /// # }
+    /// #
+    /// # struct Timer;
+    /// # impl timer::CountDown for Timer {
+    /// #     type Error = Infallible;
+    /// #     type Time = Nanoseconds;
+    /// #     fn start<T>(&mut self, _: T) -> Result<(), Self::Error>
+    /// #         where T: Into<Self::Time>
+    /// #     {
+    /// #         Ok(())
+    /// #     }
+    /// #     fn wait(&mut self) -> nb::Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// #
+    /// // Holds the waker of the task that waits for the timer. A `Mutex` is
+    /// // used here for demonstration. Use something that's safe to access
+    /// // from an interrupt handler on a microcontroller.
+    /// static TIMER_WAKER: Mutex<Option<Waker>> = Mutex::new(None);
+    ///
+    /// // The timer's interrupt handler.
+    /// fn timer_interrupt() {
+    ///     // Clear the interrupt flag here, then wake the task.
+    ///     if let Some(waker) = TIMER_WAKER.lock().unwrap().take() {
+    ///         waker.wake();
+    ///     }
+    /// }
+    ///
+    /// async fn step_task(driver: Driver, timer: Timer) {
+    ///     StepFuture::new(driver, timer)
+    ///         .wake_with(|waker: &Waker| {
+    ///             *TIMER_WAKER.lock().unwrap() = Some(waker.clone());
+    ///         })
+    ///         .await
+    ///         .unwrap();
+    /// }
+    /// #
+    /// # let _ = step_task(Driver(Pin), Timer);
+    /// # timer_interrupt();
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn wake_with<TW>(
+        self,
+        timer_waker: TW,
+    ) -> WakingStepFuture<Driver, Timer, TW>
+    where
+        TW: TimerWaker,
+    {
+        WakingStepFuture {
+            future: self,
+            timer_waker,
+        }
+    }
+}
+
+impl<Driver, Timer> StepFuture<Driver, Timer> {
+    /// Access a reference to the wrapped driver
+    pub(crate) fn driver(&self) -> &Driver {
+        &self.driver
+    }
+
+    /// Access a mutable reference to the wrapped driver
+    pub(crate) fn driver_mut(&mut self) -> &mut Driver {
+        &mut self.driver
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Driver, Timer> Future for StepFuture<Driver, Timer>
+where
+    Driver: Step + Unpin,
+    Timer: timer::CountDown + Unpin,
+    Timer::Time: TryFrom<Nanoseconds>,
+{
+    type Output = Result<
+        (),
+        SignalError<
+            Driver::Error,
+            <Driver::Step as OutputPin>::Error,
+            <Timer::Time as TryFrom<Nanoseconds>>::Error,
+            Timer::Error,
+        >,
+    >;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let poll = StepFuture::poll(self.get_mut());
+
+        // `CountDown` provides no way to get notified once the timer finishes
+        // counting down, so the best we can do is to ask the executor to poll
+        // us again.
+        if poll.is_pending() {
+            cx.waker().wake_by_ref();
+        }
+
+        poll
+    }
+}
+
+/// Registers a task to be woken, once a timer has finished counting down
+///
+/// See [`StepFuture::wake_with`]. This is implemented for closures that take
+/// a `&Waker`. Only available with the `async` feature.
+#[cfg(feature = "async")]
+pub trait TimerWaker {
+    /// Register the waker of the task that waits for the timer
+    ///
+    /// Replaces any waker that has been registered before.
+    fn register(&self, waker: &Waker);
+}
+
+#[cfg(feature = "async")]
+impl<F> TimerWaker for F
+where
+    F: Fn(&Waker),
+{
+    fn register(&self, waker: &Waker) {
+        self(waker)
+    }
+}
+
+/// A [`StepFuture`] that gets woken by the timer's interrupt
+///
+/// Returned by [`StepFuture::wake_with`]. Only available with the `async`
+/// feature.
+#[cfg(feature = "async")]
+#[must_use]
+pub struct WakingStepFuture<Driver, Timer, TW> {
+    future: StepFuture<Driver, Timer>,
+    timer_waker: TW,
+}
+
+#[cfg(feature = "async")]
+impl<Driver, Timer, TW> WakingStepFuture<Driver, Timer, TW> {
+    /// Drop the future and release the resources that were moved into it
+    pub fn release(self) -> (Driver, Timer) {
+        (self.future.driver, self.future.timer)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Driver, Timer, TW> Future for WakingStepFuture<Driver, Timer, TW>
+where
+    Driver: Step + Unpin,
+    Timer: timer::CountDown + Unpin,
+    Timer::Time: TryFrom<Nanoseconds>,
+    TW: TimerWaker + Unpin,
+{
+    type Output = <StepFuture<Driver, Timer> as Future>::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        // Register first. If the timer finishes while we're polling, the
+        // interrupt must find the waker, or the wake-up would be lost.
+        this.timer_waker.register(cx.waker());
+        StepFuture::poll(&mut this.future)
+    }
 }
 
 enum State {
     Initial,
+
+    /// The STEP pin has been set, but the timer hasn't been started
+    PulseActive,
+
     PulseStarted,
     Finished,
+    Failed,
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{convert::TryFrom, task::Poll};
+
+    use embedded_hal::{
+        digital::{
+            blocking::OutputPin as _,
+            PinState::{self, High, Low},
+        },
+        timer::nb::{self as timer, CountDown as _},
+    };
+    use embedded_time::duration::Nanoseconds;
+
+    use crate::{
+        compat::Ticks,
+        test_util::{
+            BusyMockTimer, Event, Log, MockDriver, MockPin, MockTimer,
+        },
+        traits::Step,
+        SignalError,
+    };
+
+    use super::StepFuture;
+
+    /// A timer that fails while waiting
+    struct FailingTimer;
+
+    impl timer::CountDown for FailingTimer {
+        type Error = ();
+        type Time = Ticks<u32, 1_000_000>;
+
+        fn start<T>(&mut self, _: T) -> Result<(), Self::Error>
+        where
+            T: Into<Self::Time>,
+        {
+            Ok(())
+        }
+
+        fn wait(&mut self) -> nb::Result<(), Self::Error> {
+            Err(nb::Error::Other(()))
+        }
+    }
+
+    /// A timer with a range too small for nanoseconds to convert into
+    struct NarrowTimer;
+
+    impl timer::CountDown for NarrowTimer {
+        type Error = ();
+        type Time = NarrowTicks;
+
+        fn start<T>(&mut self, _: T) -> Result<(), Self::Error>
+        where
+            T: Into<Self::Time>,
+        {
+            Ok(())
+        }
+
+        fn wait(&mut self) -> nb::Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    /// Ticks of a 1 GHz timer, but limited to 8 bits
+    struct NarrowTicks;
+
+    impl TryFrom<Nanoseconds> for NarrowTicks {
+        type Error = ();
+
+        fn try_from(value: Nanoseconds) -> Result<Self, Self::Error> {
+            u8::try_from(value.0).map(|_| Self).map_err(|_| ())
+        }
+    }
+
+    /// Wraps the mock driver, to invert its STEP pulse
+    struct InvertedMockDriver(MockDriver);
+
+    impl Step for InvertedMockDriver {
+        const PULSE_LENGTH: Nanoseconds = MockDriver::PULSE_LENGTH;
+        const PULSE_POLARITY: PinState = Low;
+
+        type Step = MockPin;
+        type Error = <MockDriver as Step>::Error;
+
+        fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+            self.0.step()
+        }
+    }
+
+    #[test]
+    fn step_should_pulse_high_by_default() {
+        let log = Log::new();
+        StepFuture::new(MockDriver::new(&log), MockTimer::new(&log))
+            .wait()
+            .unwrap();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("step", High),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+            ]
+        );
+    }
+
+    #[test]
+    fn step_should_pulse_low_with_inverted_polarity() {
+        let log = Log::new();
+        StepFuture::new(
+            InvertedMockDriver(MockDriver::new(&log)),
+            MockTimer::new(&log),
+        )
+        .wait()
+        .unwrap();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("step", Low),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                Event::Pin("step", High),
+            ]
+        );
+    }
+
+    #[test]
+    fn step_should_use_pulse_length_override() {
+        let log = Log::new();
+        StepFuture::new(MockDriver::new(&log), MockTimer::new(&log))
+            .with_pulse_length(Nanoseconds(5_000))
+            .wait()
+            .unwrap();
+
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("step", High),
+                Event::TimerStart(5),
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+            ]
+        );
+    }
+
+    #[test]
+    fn step_should_not_start_timer_for_zero_pulse_length() {
+        let log = Log::new();
+        StepFuture::new(MockDriver::new(&log), MockTimer::new(&log))
+            .with_pulse_length(Nanoseconds(0))
+            .wait()
+            .unwrap();
+
+        assert_eq!(
+            log.events(),
+            [Event::Pin("step", High), Event::Pin("step", Low)]
+        );
+    }
+
+    #[test]
+    fn step_should_end_pulse_if_timer_fails() {
+        let log = Log::new();
+        let result =
+            StepFuture::new(MockDriver::new(&log), FailingTimer).wait();
+
+        assert_eq!(result, Err(SignalError::Timer(())));
+        assert_eq!(
+            log.events(),
+            [Event::Pin("step", High), Event::Pin("step", Low)]
+        );
+    }
+
+    #[test]
+    fn step_should_keep_failing_after_timer_error() {
+        let log = Log::new();
+        let mut future = StepFuture::new(MockDriver::new(&log), FailingTimer);
+
+        assert_eq!(future.wait(), Err(SignalError::Timer(())));
+        for _ in 0..3 {
+            assert_eq!(
+                future.poll(),
+                Poll::Ready(Err(SignalError::AlreadyFailed))
+            );
+        }
+
+        // The STEP pin isn't touched again.
+        assert_eq!(
+            log.events(),
+            [Event::Pin("step", High), Event::Pin("step", Low)]
+        );
+    }
+
+    #[test]
+    fn step_should_retry_starting_busy_timer() {
+        let log = Log::new();
+        let mut future =
+            StepFuture::new(MockDriver::new(&log), BusyMockTimer::new(&log, 0));
+
+        assert_eq!(future.poll(), Poll::Ready(Err(SignalError::Timer(()))));
+        future.wait().unwrap();
+
+        // Still a single pulse of the full length.
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("step", High),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+            ]
+        );
+    }
+
+    #[test]
+    fn step_should_report_nanoseconds_that_failed_to_convert() {
+        let log = Log::new();
+        let result = StepFuture::new(MockDriver::new(&log), NarrowTimer).wait();
+
+        assert_eq!(
+            result,
+            Err(SignalError::NanosecondsToTicks {
+                nanoseconds: MockDriver::PULSE_LENGTH.0,
+                error: (),
+            })
+        );
+    }
+
+    #[test]
+    fn wait_with_should_call_idle_while_pending() {
+        let log = Log::new();
+        let mut idle_calls = 0;
+
+        StepFuture::new(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(3),
+        )
+        .wait_with(|| idle_calls += 1)
+        .unwrap();
+
+        // Once after starting the pulse, then once for every poll of the
+        // timer that didn't finish.
+        assert_eq!(idle_calls, 4);
+        assert_eq!(log.count(Event::Pin("step", Low)), 1);
+    }
+
+    #[test]
+    fn step_with_started_timer_should_only_end_pulse() {
+        let log = Log::new();
+
+        // Start the pulse, like a user of `with_started_timer` would.
+        let mut driver = MockDriver::new(&log);
+        let mut timer = MockTimer::new(&log).with_polls(1);
+        driver.step().unwrap().set_high().unwrap();
+        timer.start(Ticks(2)).unwrap();
+        log.clear();
+
+        let mut future = StepFuture::with_started_timer(driver, timer);
+
+        assert!(future.poll().is_pending());
+        assert_eq!(log.events(), []);
+
+        assert_eq!(future.poll(), Poll::Ready(Ok(())));
+        assert_eq!(
+            log.events(),
+            [Event::TimerFinished, Event::Pin("step", Low)]
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn waking_step_future_should_rely_on_registered_waker() {
+        use core::{
+            cell::RefCell,
+            future::Future as _,
+            pin::Pin,
+            sync::atomic::{AtomicUsize, Ordering},
+            task::{Context, Waker},
+        };
+        use std::{rc::Rc, sync::Arc, task::Wake};
+
+        /// Counts how often it has been woken
+        struct CountingWaker(AtomicUsize);
+
+        impl Wake for CountingWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let log = Log::new();
+        let wakes = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(wakes.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let registered = Rc::new(RefCell::new(None::<Waker>));
+        let mut future = StepFuture::new(
+            MockDriver::new(&log),
+            MockTimer::new(&log).with_polls(1),
+        )
+        .wake_with({
+            let registered = registered.clone();
+            move |waker: &Waker| *registered.borrow_mut() = Some(waker.clone())
+        });
+
+        // Starts the pulse, then waits for the timer.
+        for _ in 0..2 {
+            assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+        }
+        assert!(registered.borrow().is_some());
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 0);
+
+        // Simulate the timer's interrupt.
+        registered.borrow_mut().take().unwrap().wake();
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+
+        assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Ready(Ok(())));
+        assert_eq!(log.count(Event::Pin("step", Low)), 1);
+    }
 }
@@ -0,0 +1,332 @@
+use core::{
+    convert::{TryFrom, TryInto as _},
+    mem,
+    task::Poll,
+};
+
+use embedded_hal::{digital::blocking::OutputPin, timer::nb as timer};
+use embedded_time::duration::Nanoseconds;
+
+use crate::traits::Step;
+
+use super::{SignalError, StepFuture};
+
+/// The "future" returned by [`Stepper::step_n`]
+///
+/// Please note that this type provides a custom API and does not implement
+/// [`core::future::Future`]. This might change, when using futures for embedded
+/// development becomes more practical.
+///
+/// [`Stepper::step_n`]: crate::Stepper::step_n
+#[must_use]
+pub struct StepNFuture<Driver, Timer> {
+    state: State<Driver, Timer>,
+    count: u32,
+    delay: Nanoseconds,
+    steps_made: u32,
+}
+
+impl<Driver, Timer> StepNFuture<Driver, Timer>
+where
+    Driver: Step,
+    Timer: timer::CountDown,
+    Timer::Time: TryFrom<Nanoseconds>,
+{
+    /// Create new instance of `StepNFuture`
+    ///
+    /// This constructor is public to provide maximum flexibility for
+    /// non-standard use cases. Most users can ignore this and just use
+    /// [`Stepper::step_n`] instead.
+    ///
+    /// [`Stepper::step_n`]: crate::Stepper::step_n
+    pub fn new(
+        driver: Driver,
+        timer: Timer,
+        count: u32,
+        delay: Nanoseconds,
+    ) -> Self {
+        Self {
+            state: State::Idle { driver, timer },
+            count,
+            delay,
+            steps_made: 0,
+        }
+    }
+
+    /// Poll the future
+    ///
+    /// The future must be polled for the operation to make progress. The
+    /// operation won't start, until this method has been called once. Returns
+    /// [`Poll::Pending`], if the operation is not finished yet, or
+    /// [`Poll::Ready`], once it is.
+    ///
+    /// If this method returns [`Poll::Pending`], the user can opt to keep
+    /// calling it at a high frequency (see [`Self::wait`]) until the operation
+    /// completes, or set up an interrupt that fires once the timer finishes
+    /// counting down, and call this method again once it does.
+    #[allow(clippy::type_complexity)]
+    pub fn poll(
+        &mut self,
+    ) -> Poll<
+        Result<
+            (),
+            SignalError<
+                Driver::Error,
+                <Driver::Step as OutputPin>::Error,
+                <Timer::Time as TryFrom<Nanoseconds>>::Error,
+                Timer::Error,
+            >,
+        >,
+    > {
+        loop {
+            match mem::replace(&mut self.state, State::Invalid) {
+                State::Idle { driver, timer } => {
+                    if self.steps_made >= self.count {
+                        self.state = State::Idle { driver, timer };
+                        return Poll::Ready(Ok(()));
+                    }
+
+                    self.state = State::Step(StepFuture::new(driver, timer));
+                }
+                State::Step(mut future) => match future.poll() {
+                    Poll::Ready(Ok(())) => {
+                        self.steps_made += 1;
+
+                        let (driver, mut timer) = future.release();
+                        let delay = step_delay(&driver, self.delay);
+
+                        // Depending on the timer, starting it with zero ticks
+                        // might make it finish immediately or never. There's
+                        // nothing to wait for anyway.
+                        if delay.0 == 0 {
+                            self.state = State::Idle { driver, timer };
+                            continue;
+                        }
+
+                        let ticks: Result<Timer::Time, _> = delay.try_into();
+                        let result = match ticks {
+                            Ok(ticks) => {
+                                timer.start(ticks).map_err(SignalError::Timer)
+                            }
+                            Err(err) => Err(SignalError::NanosecondsToTicks {
+                                nanoseconds: delay.0,
+                                error: err,
+                            }),
+                        };
+
+                        if let Err(err) = result {
+                            self.state = State::Idle { driver, timer };
+                            return Poll::Ready(Err(err));
+                        }
+
+                        self.state = State::Delay { driver, timer };
+                    }
+                    Poll::Ready(Err(err)) => {
+                        let (driver, timer) = future.release();
+                        self.state = State::Idle { driver, timer };
+                        return Poll::Ready(Err(err));
+                    }
+                    Poll::Pending => {
+                        self.state = State::Step(future);
+                        return Poll::Pending;
+                    }
+                },
+                State::Delay { driver, mut timer } => match timer.wait() {
+                    Ok(()) => {
+                        self.state = State::Idle { driver, timer };
+                    }
+                    Err(nb::Error::Other(err)) => {
+                        self.state = State::Idle { driver, timer };
+                        return Poll::Ready(Err(SignalError::Timer(err)));
+                    }
+                    Err(nb::Error::WouldBlock) => {
+                        self.state = State::Delay { driver, timer };
+                        return Poll::Pending;
+                    }
+                },
+                State::Invalid => {
+                    // We only get here, if a previous call to this method
+                    // panicked, before it could restore the state.
+                    panic!(
+                        "Invalid internal state, caused by a previous panic."
+                    )
+                }
+            }
+        }
+    }
+
+    /// Wait until the operation completes
+    ///
+    /// This method will call [`Self::poll`] in a busy loop until the operation
+    /// has finished. See [`Self::wait_with`], if you need to do something
+    /// between calls.
+    #[allow(clippy::type_complexity)]
+    pub fn wait(
+        &mut self,
+    ) -> Result<
+        (),
+        SignalError<
+            Driver::Error,
+            <Driver::Step as OutputPin>::Error,
+            <Timer::Time as TryFrom<Nanoseconds>>::Error,
+            Timer::Error,
+        >,
+    > {
+        self.wait_with(|| {})
+    }
+
+    /// Wait until the operation completes, calling `idle` between polls
+    ///
+    /// Works like [`Self::wait`], except that `idle` is called every time
+    /// [`Self::poll`] returns [`Poll::Pending`]. See
+    /// [`StepFuture::wait_with`] for details.
+    ///
+    /// [`StepFuture::wait_with`]: crate::StepFuture::wait_with
+    #[allow(clippy::type_complexity)]
+    pub fn wait_with<F>(
+        &mut self,
+        mut idle: F,
+    ) -> Result<
+        (),
+        SignalError<
+            Driver::Error,
+            <Driver::Step as OutputPin>::Error,
+            <Timer::Time as TryFrom<Nanoseconds>>::Error,
+            Timer::Error,
+        >,
+    >
+    where
+        F: FnMut(),
+    {
+        loop {
+            if let Poll::Ready(result) = self.poll() {
+                return result;
+            }
+
+            idle();
+        }
+    }
+
+    /// The number of steps that have been made so far
+    ///
+    /// A step counts as made, once its STEP pulse has ended.
+    pub fn steps_made(&self) -> u32 {
+        self.steps_made
+    }
+
+    /// Drop the future and release the resources that were moved into it
+    ///
+    /// If a STEP pulse is ongoing, it is not ended. Only release the future
+    /// after it has finished, unless you take care of that yourself.
+    pub fn release(self) -> (Driver, Timer) {
+        match self.state {
+            State::Idle { driver, timer } | State::Delay { driver, timer } => {
+                (driver, timer)
+            }
+            State::Step(future) => future.release(),
+            State::Invalid => {
+                panic!("Invalid internal state, caused by a previous panic.")
+            }
+        }
+    }
+}
+
+/// The time to wait after a STEP pulse, before starting the next one
+///
+/// `delay` is the time from the start of one STEP pulse to the start of the
+/// next. The pulse itself is subtracted, but the driver's
+/// [`Step::LOW_TIME`] is always waited, even if `delay` is shorter.
+///
+/// If this returns zero, the next pulse can start right away.
+fn step_delay<Driver: Step>(
+    driver: &Driver,
+    delay: Nanoseconds,
+) -> Nanoseconds {
+    let delay_left = delay.0.saturating_sub(driver.pulse_length().0);
+    Nanoseconds(delay_left.max(driver.low_time().0))
+}
+
+enum State<Driver, Timer> {
+    Idle { driver: Driver, timer: Timer },
+    Step(StepFuture<Driver, Timer>),
+    Delay { driver: Driver, timer: Timer },
+    Invalid,
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use embedded_hal::digital::PinState::{High, Low};
+    use embedded_time::duration::Nanoseconds;
+
+    use crate::{
+        test_util::{Event, Log, MockDriver, MockTimer},
+        Stepper,
+    };
+
+    #[test]
+    fn step_n_should_make_count_steps_with_constant_spacing() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(MockDriver::new(&log));
+        let mut timer = MockTimer::new(&log);
+
+        let mut future = stepper.step_n(3, Nanoseconds(10_000), &mut timer);
+        future.wait().unwrap();
+        assert_eq!(future.steps_made(), 3);
+
+        // Every step consists of the 2 µs STEP pulse, followed by the 8 µs
+        // left until the next step.
+        let step = [
+            Event::Pin("step", High),
+            Event::TimerStart(2),
+            Event::TimerFinished,
+            Event::Pin("step", Low),
+            Event::TimerStart(8),
+            Event::TimerFinished,
+        ];
+        let expected: Vec<_> = step.iter().cycle().take(3 * 6).collect();
+        assert_eq!(log.events().iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn step_n_should_not_wait_a_negative_delay() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(MockDriver::new(&log));
+        let mut timer = MockTimer::new(&log);
+
+        // Shorter than the STEP pulse.
+        stepper
+            .step_n(1, Nanoseconds(1_000), &mut timer)
+            .wait()
+            .unwrap();
+
+        // The driver's LOW_TIME is zero, so there's nothing left to wait for.
+        assert_eq!(
+            log.events(),
+            [
+                Event::Pin("step", High),
+                Event::TimerStart(2),
+                Event::TimerFinished,
+                Event::Pin("step", Low),
+            ]
+        );
+    }
+
+    #[test]
+    fn step_n_wait_with_should_call_idle_while_pending() {
+        let log = Log::new();
+        let mut stepper = Stepper::from_driver(MockDriver::new(&log));
+        let mut timer = MockTimer::new(&log);
+        let mut idle_calls = 0;
+
+        stepper
+            .step_n(2, Nanoseconds(10_000), &mut timer)
+            .wait_with(|| idle_calls += 1)
+            .unwrap();
+
+        // Once after starting each STEP pulse. The delays after them are
+        // waited for in the same poll, as the mock timer is done right away.
+        assert_eq!(idle_calls, 2);
+    }
+}
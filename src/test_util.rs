@@ -0,0 +1,560 @@
+//! Mock implementations of drivers and timers, for testing without hardware
+//!
+//! All mocks record what they're doing into a shared [`Log`], so tests can
+//! make assertions about the exact sequence of pin changes and timer
+//! operations.
+//!
+//! This module is only available, if the `test-util` feature is enabled.
+//!
+//! ``` rust
+//! use stepper::{
+//!     embedded_hal::digital::PinState,
+//!     ramp_maker::Flat,
+//!     test_util::{Event, Log, MockDelayToTicks, MockDriver, MockTimer},
+//!     traits::MotionControl as _,
+//!     motion_control::SoftwareMotionControl,
+//! };
+//!
+//! let log = Log::new();
+//! let mut motion_control = SoftwareMotionControl::new(
+//!     MockDriver::new(&log),
+//!     MockTimer::new(&log).with_polls(3),
+//!     Flat::<f32>::new(),
+//!     MockDelayToTicks,
+//! );
+//!
+//! motion_control.move_to_position(0.01, 2).unwrap();
+//! while motion_control.update().unwrap() {}
+//!
+//! assert_eq!(log.count(Event::Pin("step", PinState::High)), 2);
+//! assert_eq!(log.count(Event::Pin("step", PinState::Low)), 2);
+//! ```
+
+use alloc::{rc::Rc, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    convert::Infallible,
+};
+
+use embedded_hal::{
+    digital::{
+        blocking::{InputPin, OutputPin, StatefulOutputPin},
+        PinState,
+    },
+    i2c::blocking as i2c,
+    serial::blocking as serial,
+    spi::blocking as spi,
+    timer::nb as timer,
+};
+use embedded_time::duration::Nanoseconds;
+use ramp_maker::Flat;
+
+use crate::{
+    compat::Ticks,
+    motion_control::{DelayToTicks, SoftwareMotionControl},
+    step_mode::StepMode256,
+    traits::{SetDirection, SetStepMode, Step},
+};
+
+/// An event recorded by one of the mocks
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Event {
+    /// A pin was set to the given state
+    Pin(&'static str, PinState),
+
+    /// A timer was started with the given number of ticks
+    TimerStart(u32),
+
+    /// A running timer finished counting down
+    TimerFinished,
+
+    /// A running timer was canceled
+    TimerCanceled,
+
+    /// A step mode configuration was applied, given in microsteps per step
+    ApplyModeConfig(u16),
+
+    /// The driver was re-enabled after applying a step mode configuration
+    EnableDriver,
+
+    /// A byte was written to an SPI bus
+    SpiWrite(u8),
+
+    /// A byte was written to an I2C bus, addressed to the given device
+    I2cWrite(u8, u8),
+
+    /// A byte was written to a serial interface
+    SerialWrite(u8),
+}
+
+/// A log of events, shared between mocks
+#[derive(Clone, Default)]
+pub struct Log(Rc<RefCell<Vec<Event>>>);
+
+impl Log {
+    /// Create a new, empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an event
+    pub fn push(&self, event: Event) {
+        self.0.borrow_mut().push(event);
+    }
+
+    /// Remove all events recorded so far
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+
+    /// Return a copy of all events recorded so far, in order
+    pub fn events(&self) -> Vec<Event> {
+        self.0.borrow().clone()
+    }
+
+    /// Count the events that are equal to the given one
+    pub fn count(&self, event: Event) -> usize {
+        self.0.borrow().iter().filter(|&&e| e == event).count()
+    }
+}
+
+/// A mock output pin
+///
+/// Remembers the level it was last set to, which [`StatefulOutputPin`]
+/// reports.
+pub struct MockPin {
+    name: &'static str,
+    log: Log,
+    state: PinState,
+}
+
+impl MockPin {
+    /// Create a new mock pin that records its changes under `name`
+    ///
+    /// The pin starts out low.
+    pub fn new(name: &'static str, log: &Log) -> Self {
+        Self {
+            name,
+            log: log.clone(),
+            state: PinState::Low,
+        }
+    }
+}
+
+impl OutputPin for MockPin {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.log.push(Event::Pin(self.name, PinState::Low));
+        self.state = PinState::Low;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.log.push(Event::Pin(self.name, PinState::High));
+        self.state = PinState::High;
+        Ok(())
+    }
+}
+
+impl StatefulOutputPin for MockPin {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.state == PinState::High)
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(self.state == PinState::Low)
+    }
+}
+
+/// A mock input pin, whose state can be changed from the outside
+///
+/// All clones share the same state, so a test can keep one clone to change the
+/// state, while another has been moved into the code under test.
+#[derive(Clone)]
+pub struct MockInputPin(Rc<Cell<PinState>>);
+
+impl MockInputPin {
+    /// Create a new mock input pin with the given initial state
+    pub fn new(state: PinState) -> Self {
+        Self(Rc::new(Cell::new(state)))
+    }
+
+    /// Change the state of the pin
+    pub fn set(&self, state: PinState) {
+        self.0.set(state);
+    }
+}
+
+impl InputPin for MockInputPin {
+    type Error = Infallible;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.0.get() == PinState::High)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(self.0.get() == PinState::Low)
+    }
+}
+
+/// A mock SPI bus
+///
+/// Records every byte written as [`Event::SpiWrite`].
+pub struct MockSpi {
+    log: Log,
+}
+
+impl MockSpi {
+    /// Create a new mock SPI bus
+    pub fn new(log: &Log) -> Self {
+        Self { log: log.clone() }
+    }
+}
+
+impl spi::Write<u8> for MockSpi {
+    // SPI errors need to implement `embedded_hal::spi::Error`, which
+    // `Infallible` doesn't. This mock never fails, though.
+    type Error = embedded_hal::spi::ErrorKind;
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        for &word in words {
+            self.log.push(Event::SpiWrite(word));
+        }
+        Ok(())
+    }
+}
+
+/// A mock serial interface
+///
+/// Records every byte written as [`Event::SerialWrite`].
+pub struct MockSerial {
+    log: Log,
+}
+
+impl MockSerial {
+    /// Create a new mock serial interface
+    pub fn new(log: &Log) -> Self {
+        Self { log: log.clone() }
+    }
+}
+
+impl serial::Write<u8> for MockSerial {
+    // Serial errors need to implement `embedded_hal::serial::Error`, which
+    // `Infallible` doesn't. This mock never fails, though.
+    type Error = embedded_hal::serial::ErrorKind;
+
+    fn write(&mut self, buffer: &[u8]) -> Result<(), Self::Error> {
+        for &word in buffer {
+            self.log.push(Event::SerialWrite(word));
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// A mock I2C bus
+///
+/// Records every byte written as [`Event::I2cWrite`].
+pub struct MockI2c {
+    log: Log,
+}
+
+impl MockI2c {
+    /// Create a new mock I2C bus
+    pub fn new(log: &Log) -> Self {
+        Self { log: log.clone() }
+    }
+}
+
+impl i2c::Write for MockI2c {
+    // I2C errors need to implement `embedded_hal::i2c::Error`, which
+    // `Infallible` doesn't. This mock never fails, though.
+    type Error = embedded_hal::i2c::ErrorKind;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        for &byte in bytes {
+            self.log.push(Event::I2cWrite(address, byte));
+        }
+        Ok(())
+    }
+}
+
+/// A mock timer with frequency `FREQ`
+///
+/// After being started, [`timer::CountDown::wait`] returns
+/// `nb::Error::WouldBlock` for a configurable number of polls, then finishes.
+pub struct MockTimer<const FREQ: u32 = 1_000_000> {
+    log: Log,
+    polls: u32,
+    polls_left: Option<u32>,
+}
+
+impl MockTimer {
+    /// Create a new mock timer that runs at 1 MHz
+    pub fn new(log: &Log) -> Self {
+        Self::with_frequency(log)
+    }
+}
+
+impl<const FREQ: u32> MockTimer<FREQ> {
+    /// Create a new mock timer with the frequency given by `FREQ`
+    pub fn with_frequency(log: &Log) -> Self {
+        Self {
+            log: log.clone(),
+            polls: 0,
+            polls_left: None,
+        }
+    }
+
+    /// Set the number of polls before the timer finishes
+    ///
+    /// After the timer has been started, [`timer::CountDown::wait`] returns
+    /// `nb::Error::WouldBlock` `polls` times, before returning `Ok`. The
+    /// default is `0`, meaning the timer finishes on the first call to `wait`.
+    pub fn with_polls(mut self, polls: u32) -> Self {
+        self.polls = polls;
+        self
+    }
+}
+
+impl<const FREQ: u32> timer::CountDown for MockTimer<FREQ> {
+    type Error = Infallible;
+    type Time = Ticks<u32, FREQ>;
+
+    fn start<T>(&mut self, count: T) -> Result<(), Self::Error>
+    where
+        T: Into<Self::Time>,
+    {
+        let ticks = count.into();
+        self.log.push(Event::TimerStart(ticks.0));
+        self.polls_left = Some(self.polls);
+        Ok(())
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        match self.polls_left {
+            Some(0) => {
+                self.log.push(Event::TimerFinished);
+                self.polls_left = None;
+                Ok(())
+            }
+            Some(polls_left) => {
+                self.polls_left = Some(polls_left - 1);
+                Err(nb::Error::WouldBlock)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl<const FREQ: u32> timer::Cancel for MockTimer<FREQ> {
+    fn cancel(&mut self) -> Result<(), Self::Error> {
+        // A real timer would return an error, if it isn't running. It's up to
+        // the test to check that this is only called when it should be.
+        if self.polls_left.take().is_some() {
+            self.log.push(Event::TimerCanceled);
+        }
+        Ok(())
+    }
+}
+
+/// Wraps [`MockTimer`], failing one call to [`timer::CountDown::start`]
+///
+/// Stands in for timers that can't be started while they're still counting
+/// down. The failed call isn't recorded in the log.
+pub struct BusyMockTimer {
+    timer: MockTimer,
+    starts: u32,
+    busy_at: u32,
+}
+
+impl BusyMockTimer {
+    /// Create a new timer, whose start number `busy_at` (counting from zero)
+    /// fails
+    pub fn new(log: &Log, busy_at: u32) -> Self {
+        Self {
+            timer: MockTimer::new(log),
+            starts: 0,
+            busy_at,
+        }
+    }
+}
+
+impl timer::CountDown for BusyMockTimer {
+    type Error = ();
+    type Time = Ticks<u32, 1_000_000>;
+
+    fn start<T>(&mut self, count: T) -> Result<(), Self::Error>
+    where
+        T: Into<Self::Time>,
+    {
+        let start = self.starts;
+        self.starts += 1;
+
+        if start == self.busy_at {
+            return Err(());
+        }
+
+        self.timer.start(count).map_err(|err| match err {})
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Self::Error> {
+        self.timer.wait().map_err(|err| err.map(|err| match err {}))
+    }
+}
+
+/// Converts `f32` delay values, given in timer ticks, for [`MockTimer`]
+pub struct MockDelayToTicks<const FREQ: u32 = 1_000_000>;
+
+impl<const FREQ: u32> DelayToTicks<f32> for MockDelayToTicks<FREQ> {
+    type Ticks = Ticks<u32, FREQ>;
+    type Error = Infallible;
+
+    fn delay_to_ticks(&self, delay: f32) -> Result<Self::Ticks, Self::Error> {
+        Ok(Ticks(delay as u32))
+    }
+}
+
+/// A fixed-point number, for tests that use [`Trapezoidal`]
+///
+/// RampMaker only implements the math `Trapezoidal` needs for `f32` with its
+/// `std` or `libm` features, so tests use this instead.
+///
+/// [`Trapezoidal`]: ramp_maker::Trapezoidal
+#[cfg(test)]
+pub type Num = fixed::FixedI64<typenum::U32>;
+
+/// Converts [`Num`] delay values, given in timer ticks, for [`MockTimer`]
+#[cfg(test)]
+pub struct NumToTicks;
+
+#[cfg(test)]
+impl DelayToTicks<Num> for NumToTicks {
+    type Ticks = Ticks<u32, 1_000_000>;
+    type Error = Infallible;
+
+    fn delay_to_ticks(&self, delay: Num) -> Result<Self::Ticks, Self::Error> {
+        Ok(Ticks(delay.to_num()))
+    }
+}
+
+/// A mock driver that supports step, direction, and step mode control
+///
+/// Records STEP changes under the name `"step"` and DIR changes under the name
+/// `"dir"`.
+pub struct MockDriver {
+    step: MockPin,
+    dir: MockPin,
+    log: Log,
+    step_mode: Option<StepMode256>,
+}
+
+impl MockDriver {
+    /// Create a new mock driver
+    pub fn new(log: &Log) -> Self {
+        Self {
+            step: MockPin::new("step", log),
+            dir: MockPin::new("dir", log),
+            log: log.clone(),
+            step_mode: None,
+        }
+    }
+}
+
+impl Step for MockDriver {
+    const PULSE_LENGTH: Nanoseconds = Nanoseconds(2_000);
+
+    type Step = MockPin;
+    type Error = Infallible;
+
+    fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
+        Ok(&mut self.step)
+    }
+}
+
+impl SetDirection for MockDriver {
+    const SETUP_TIME: Nanoseconds = Nanoseconds(1_000);
+
+    type Dir = MockPin;
+    type Error = Infallible;
+
+    fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
+        Ok(&mut self.dir)
+    }
+}
+
+impl SetStepMode for MockDriver {
+    const SETUP_TIME: Nanoseconds = Nanoseconds(1_000);
+    const HOLD_TIME: Nanoseconds = Nanoseconds(1_000);
+
+    type Error = Infallible;
+    type StepMode = StepMode256;
+
+    fn apply_mode_config(
+        &mut self,
+        step_mode: Self::StepMode,
+    ) -> Result<(), Self::Error> {
+        self.log.push(Event::ApplyModeConfig(step_mode.into()));
+        self.step_mode = Some(step_mode);
+        Ok(())
+    }
+
+    fn enable_driver(&mut self) -> Result<(), Self::Error> {
+        self.log.push(Event::EnableDriver);
+        Ok(())
+    }
+
+    fn step_mode(&self) -> Option<Self::StepMode> {
+        self.step_mode
+    }
+}
+
+/// [`SoftwareMotionControl`] with the mocks from this module
+///
+/// Uses a flat motion profile, so all steps are made at the same velocity. A
+/// velocity of `0.01` steps per tick makes a step every 100 ticks.
+pub type MockMotionControl<Driver = MockDriver, Timer = MockTimer> =
+    SoftwareMotionControl<Driver, Timer, Flat<f32>, MockDelayToTicks>;
+
+/// Create a [`MockMotionControl`] from a [`MockDriver`] and a [`MockTimer`]
+pub fn mock_motion_control(log: &Log) -> MockMotionControl {
+    mock_motion_control_with(MockDriver::new(log), MockTimer::new(log))
+}
+
+/// Create a [`MockMotionControl`] from any driver and timer
+///
+/// This is useful for tests that need a driver that wraps [`MockDriver`], or
+/// a timer that behaves differently from the default [`MockTimer`].
+pub fn mock_motion_control_with<Driver, Timer>(
+    driver: Driver,
+    timer: Timer,
+) -> MockMotionControl<Driver, Timer>
+where
+    Timer: timer::CountDown,
+{
+    SoftwareMotionControl::new(driver, timer, Flat::new(), MockDelayToTicks)
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_hal::timer::nb::CountDown as _;
+
+    use super::{Event, Log, MockTimer};
+
+    #[test]
+    fn mock_timer_should_finish_after_configured_number_of_polls() {
+        let log = Log::new();
+        let mut timer = MockTimer::new(&log).with_polls(2);
+
+        timer.start(super::Ticks(10)).unwrap();
+        assert_eq!(timer.wait(), Err(nb::Error::WouldBlock));
+        assert_eq!(timer.wait(), Err(nb::Error::WouldBlock));
+        assert_eq!(timer.wait(), Ok(()));
+
+        assert_eq!(log.events(), [Event::TimerStart(10), Event::TimerFinished]);
+    }
+}
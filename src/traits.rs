@@ -18,12 +18,18 @@
 //! This approach also provides a lot of flexibility for non-standard use cases,
 //! for example if not all driver capabilities are controlled by software.
 //!
+//! In addition, [`Capabilities`] describes which capabilities a driver
+//! supports, before any of them have been enabled.
+//!
 //! [`Stepper`]: crate::Stepper
 
-use embedded_hal::digital::blocking::OutputPin;
+use embedded_hal::digital::{
+    blocking::{InputPin, OutputPin},
+    PinState,
+};
 use embedded_time::duration::Nanoseconds;
 
-use crate::step_mode::StepMode;
+use crate::{step_mode::StepMode, Direction};
 
 /// Enable microstepping mode control for a driver
 ///
@@ -48,6 +54,29 @@ pub trait SetStepMode {
     /// The time the mode signals need to be held after re-enabling the driver
     const HOLD_TIME: Nanoseconds;
 
+    /// The minimum time the driver needs to be held in reset
+    ///
+    /// [`SetStepModeFuture`] keeps the driver in reset (or disabled) between
+    /// [`SetStepMode::apply_mode_config`] and [`SetStepMode::enable_driver`]
+    /// for at least this long, or for [`SetStepMode::SETUP_TIME`], whichever
+    /// is longer. Defaults to zero, for drivers that don't have a minimum reset
+    /// pulse length.
+    ///
+    /// [`SetStepModeFuture`]: crate::SetStepModeFuture
+    const RESET_TIME: Nanoseconds = Nanoseconds(0);
+
+    /// The time to wait after changing a mode pin, before changing the next
+    ///
+    /// If this isn't zero, [`SetStepModeFuture`] applies the step mode
+    /// configuration using [`SetStepMode::apply_mode_pin`], waiting for this
+    /// long between calls. This is intended for drivers that glitch, if
+    /// multiple mode pins change at the same time. Defaults to zero, which
+    /// means [`SetStepMode::apply_mode_config`] is used to change all mode pins
+    /// at once.
+    ///
+    /// [`SetStepModeFuture`]: crate::SetStepModeFuture
+    const MODE_PIN_SETTLE_TIME: Nanoseconds = Nanoseconds(0);
+
     /// The error that can occur while using this trait
     type Error;
 
@@ -65,8 +94,140 @@ pub trait SetStepMode {
         step_mode: Self::StepMode,
     ) -> Result<(), Self::Error>;
 
+    /// Apply the new step mode configuration, one mode pin at a time
+    ///
+    /// Used instead of [`SetStepMode::apply_mode_config`], if
+    /// [`SetStepMode::MODE_PIN_SETTLE_TIME`] isn't zero. `index` is zero for
+    /// the first call, and goes up by one with every call after that.
+    /// Typically the first call puts the driver into reset, and every call sets
+    /// the mode pin at `index`. Returns `true`, once the configuration has been
+    /// applied completely.
+    ///
+    /// The default implementation applies the whole configuration on the first
+    /// call, using [`SetStepMode::apply_mode_config`].
+    fn apply_mode_pin(
+        &mut self,
+        step_mode: Self::StepMode,
+        index: usize,
+    ) -> Result<bool, Self::Error> {
+        let _ = index;
+        self.apply_mode_config(step_mode)?;
+        Ok(true)
+    }
+
     /// Re-enable the driver after the mode has been set
     fn enable_driver(&mut self) -> Result<(), Self::Error>;
+
+    /// Returns the step mode that was last applied
+    ///
+    /// Drivers typically can't read back their step mode, so this reflects the
+    /// last successful call to [`SetStepMode::apply_mode_config`]. Returns
+    /// `None`, if it hasn't been called yet, regardless of the step mode the
+    /// driver starts up in.
+    ///
+    /// The default implementation always returns `None`, for drivers that
+    /// don't keep track of their step mode.
+    fn step_mode(&self) -> Option<Self::StepMode> {
+        None
+    }
+}
+
+/// Enable driver control for a driver
+///
+/// The `Resources` type parameter defines the hardware resources required for
+/// enabling and disabling the driver.
+pub trait EnableDriverControl<Resources> {
+    /// The type of the driver after driver control has been enabled
+    type WithDriverControl: SetEnable;
+
+    /// Enable driver control
+    fn enable_driver_control(self, res: Resources) -> Self::WithDriverControl;
+}
+
+/// Implemented by drivers that support enabling and disabling the motor outputs
+///
+/// This typically controls the driver's ENABLE signal. While disabled, the
+/// motor coils are not energized, which saves power and allows the shaft to be
+/// moved by hand.
+pub trait SetEnable {
+    /// The time the driver needs after being enabled, before it can step
+    const HOLD_TIME: Nanoseconds;
+
+    /// The error that can occur while using this trait
+    type Error;
+
+    /// Enable the driver, energizing the motor coils
+    fn enable(&mut self) -> Result<(), Self::Error>;
+
+    /// Disable the driver, de-energizing the motor coils
+    fn disable(&mut self) -> Result<(), Self::Error>;
+
+    /// Indicate whether the driver is enabled
+    ///
+    /// Drivers typically can't read back their ENABLE signal, so this reflects
+    /// the last successful call to [`SetEnable::enable`] or
+    /// [`SetEnable::disable`]. Returns `None`, if neither has been called yet,
+    /// regardless of the initial state of the ENABLE signal.
+    ///
+    /// The default implementation always returns `None`, for drivers that
+    /// don't keep track of their enable state.
+    fn is_enabled(&self) -> Option<bool> {
+        None
+    }
+}
+
+/// Implemented by drivers that can switch between a quiet and a strong mode
+///
+/// Many drivers have more than one chopper mode, which controls how the
+/// current through the motor coils is regulated. Typically, one of them is
+/// quiet at low velocities (like stealthChop on Trinamic drivers), while
+/// another provides more torque at high velocities (like spreadCycle). Drivers
+/// might switch between them using a pin or by writing a register.
+///
+/// Drivers that switch using a register might need to be configured first,
+/// before this trait is available. Check the driver's documentation.
+pub trait ChopperMode {
+    /// The error that can occur while using this trait
+    type Error;
+
+    /// Select the quiet chopper mode, if `quiet` is `true`
+    ///
+    /// Selects the mode that provides more torque, if `quiet` is `false`.
+    fn set_quiet(&mut self, quiet: bool) -> Result<(), Self::Error>;
+}
+
+/// Enable stall detection for a driver
+///
+/// The `Resources` type parameter defines the hardware resources required for
+/// detecting stalls.
+pub trait EnableStallDetection<Resources> {
+    /// The type of the driver after stall detection has been enabled
+    type WithStallDetection: StallDetection;
+
+    /// Enable stall detection
+    fn enable_stall_detection(self, res: Resources)
+        -> Self::WithStallDetection;
+}
+
+/// Implemented by drivers that can detect a stalled motor
+///
+/// This typically works by reading a diagnostic output of the driver hardware,
+/// like the DIAG pin of drivers that support sensorless stall detection.
+pub trait StallDetection {
+    /// The type of the DIAG pin
+    type Diag: InputPin;
+
+    /// The error that can occur while accessing or reading the DIAG pin
+    type Error;
+
+    /// Provides access to the DIAG pin
+    fn diag(&mut self) -> Result<&mut Self::Diag, Self::Error>;
+
+    /// Indicates whether the driver has detected a stall
+    ///
+    /// Implementations typically read the DIAG pin, taking into account
+    /// whether the hardware signals a stall with a high or a low level.
+    fn is_stalled(&mut self) -> Result<bool, Self::Error>;
 }
 
 /// Enable direction control for a driver
@@ -87,6 +248,9 @@ pub trait EnableDirectionControl<Resources> {
 /// Implemented by drivers that support controlling the DIR signal
 pub trait SetDirection {
     /// The time that the DIR signal must be held for a change to apply
+    ///
+    /// This crate reads the setup time through [`SetDirection::setup_time`],
+    /// which returns this by default.
     const SETUP_TIME: Nanoseconds;
 
     /// The type of the DIR pin
@@ -97,6 +261,25 @@ pub trait SetDirection {
 
     /// Provides access to the DIR pin
     fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error>;
+
+    /// The setup time of this driver instance
+    ///
+    /// Returns [`SetDirection::SETUP_TIME`]. Drivers whose timing is only
+    /// known at runtime can override this.
+    fn setup_time(&self) -> Nanoseconds {
+        Self::SETUP_TIME
+    }
+
+    /// The level of the DIR pin for [`Direction::Forward`]
+    ///
+    /// [`Direction::Backward`] uses the opposite level. Returns
+    /// [`PinState::High`] by default. Drivers whose outputs go through an
+    /// inverting buffer can override this. See [`ActiveLow`].
+    ///
+    /// [`ActiveLow`]: crate::drivers::active_low::ActiveLow
+    fn dir_polarity(&self) -> PinState {
+        PinState::High
+    }
 }
 
 /// Enable step control for a driver
@@ -114,8 +297,32 @@ pub trait EnableStepControl<Resources> {
 /// Implemented by drivers that support controlling the STEP signal
 pub trait Step {
     /// The minimum length of a STEP pulse
+    ///
+    /// This crate reads the pulse length through [`Step::pulse_length`], which
+    /// returns this by default.
     const PULSE_LENGTH: Nanoseconds;
 
+    /// The state of the STEP pin during a STEP pulse
+    ///
+    /// Defaults to [`PinState::High`], meaning the STEP pin is low while idle.
+    /// Drivers with an active-low STEP input, like some opto-isolated ones, can
+    /// override this with [`PinState::Low`], to get a pulse that goes from high
+    /// to low and back.
+    const PULSE_POLARITY: PinState = PinState::High;
+
+    /// The minimum time the STEP pin must be idle between two STEP pulses
+    ///
+    /// Defaults to zero, meaning a new STEP pulse can start right after the
+    /// previous one has ended. [`SoftwareMotionControl`] never waits less than
+    /// this after a STEP pulse, even if the motion profile asks for a shorter
+    /// delay.
+    ///
+    /// This crate reads the low time through [`Step::low_time`], which returns
+    /// this by default.
+    ///
+    /// [`SoftwareMotionControl`]: crate::motion_control::SoftwareMotionControl
+    const LOW_TIME: Nanoseconds = Nanoseconds(0);
+
     /// The type of the STEP pin
     type Step: OutputPin;
 
@@ -124,6 +331,73 @@ pub trait Step {
 
     /// Provides access to the STEP pin
     fn step(&mut self) -> Result<&mut Self::Step, Self::Error>;
+
+    /// The pulse polarity of this driver instance
+    ///
+    /// Returns [`Step::PULSE_POLARITY`]. Drivers whose outputs go through an
+    /// inverting buffer can override this. See [`ActiveLow`].
+    ///
+    /// [`ActiveLow`]: crate::drivers::active_low::ActiveLow
+    fn pulse_polarity(&self) -> PinState {
+        Self::PULSE_POLARITY
+    }
+
+    /// The pulse length of this driver instance
+    ///
+    /// Returns [`Step::PULSE_LENGTH`]. Drivers whose timing is only known at
+    /// runtime can override this.
+    fn pulse_length(&self) -> Nanoseconds {
+        Self::PULSE_LENGTH
+    }
+
+    /// The low time of this driver instance
+    ///
+    /// Returns [`Step::LOW_TIME`]. Drivers whose timing is only known at
+    /// runtime can override this.
+    fn low_time(&self) -> Nanoseconds {
+        Self::LOW_TIME
+    }
+}
+
+/// Implemented by drivers that generate STEP pulses in hardware
+///
+/// This is an alternative to [`Step`], for high step rates. Instead of
+/// toggling the STEP pin in software, a PWM or output compare channel
+/// generates one STEP pulse per period. This is used by
+/// [`PwmMotionControl`], which reprograms the period after every step.
+///
+/// A PWM peripheral that implements [`embedded_hal::pwm::blocking::Pwm`]
+/// provides everything [`PwmStep::set_period`] and [`PwmStep::stop`] need:
+/// [`Pwm::set_period`], [`Pwm::set_duty`] (to make the pulse long enough,
+/// typically half the period), [`Pwm::enable`], and [`Pwm::disable`].
+/// `embedded-hal` has no trait for counting PWM periods though, so
+/// [`PwmStep::take_steps`] typically relies on an interrupt handler that
+/// counts the compare events.
+///
+/// [`PwmMotionControl`]: crate::motion_control::PwmMotionControl
+/// [`Pwm::set_period`]: embedded_hal::pwm::blocking::Pwm::set_period
+/// [`Pwm::set_duty`]: embedded_hal::pwm::blocking::Pwm::set_duty
+/// [`Pwm::enable`]: embedded_hal::pwm::blocking::Pwm::enable
+/// [`Pwm::disable`]: embedded_hal::pwm::blocking::Pwm::disable
+pub trait PwmStep {
+    /// The type used to define the period between STEP pulses
+    type Period;
+
+    /// The error that can occur while generating STEP pulses
+    type Error;
+
+    /// Start generating STEP pulses, one per `period`
+    ///
+    /// If pulses are already being generated, this changes the period,
+    /// starting with the next one. Any pulse must meet the driver's minimum
+    /// pulse length.
+    fn set_period(&mut self, period: Self::Period) -> Result<(), Self::Error>;
+
+    /// Stop generating STEP pulses
+    fn stop(&mut self) -> Result<(), Self::Error>;
+
+    /// Return the number of STEP pulses generated since the last call
+    fn take_steps(&mut self) -> Result<u32, Self::Error>;
 }
 
 /// Enable motion control for a driver
@@ -162,12 +436,89 @@ pub trait MotionControl {
         target_step: i32,
     ) -> Result<(), Self::Error>;
 
+    /// Rotate in the given direction indefinitely
+    ///
+    /// This method must arrange for the motion to start, but must not block.
+    /// Unlike a motion started by [`MotionControl::move_to_position`], this
+    /// motion never ends on its own. It continues until it is aborted (see
+    /// [`MotionControl::abort`]), or until another motion is started.
+    fn rotate(
+        &mut self,
+        direction: Direction,
+        velocity: Self::Velocity,
+    ) -> Result<(), Self::Error>;
+
+    /// Change the maximum velocity of the ongoing motion
+    ///
+    /// This method must apply the new maximum velocity to the current motion
+    /// without interrupting it, and without changing its target. If no motion
+    /// is ongoing, it should have no effect.
+    ///
+    /// How the motor gets from the old to the new velocity is up to the
+    /// implementation, which should document it. [`SoftwareMotionControl`]
+    /// re-enters position mode of its motion profile, which continues the
+    /// current ramp for all of RampMaker's motion profiles, and never returns
+    /// an error.
+    ///
+    /// [`SoftwareMotionControl`]: crate::motion_control::SoftwareMotionControl
+    fn set_max_speed(
+        &mut self,
+        max_velocity: Self::Velocity,
+    ) -> Result<(), Self::Error>;
+
     /// Reset internal position to the given value
     ///
     /// This method must not start a motion. Its only purpose is to change the
-    /// driver's internal position value, for example for homing.
+    /// driver's internal position value, for example for homing. It may return
+    /// an error, if a motion is ongoing.
     fn reset_position(&mut self, step: i32) -> Result<(), Self::Error>;
 
+    /// Return the current position
+    ///
+    /// This method must not start or influence a motion. Its only purpose is
+    /// to report the driver's internal position value, as of the last step
+    /// that was completed.
+    fn current_step(&mut self) -> Result<i32, Self::Error>;
+
+    /// Return the current direction
+    ///
+    /// This method must not start or influence a motion. Its only purpose is
+    /// to report the direction of the ongoing motion, or of the last motion,
+    /// if none is ongoing.
+    fn current_direction(&mut self) -> Result<Direction, Self::Error>;
+
+    /// Reverse the ongoing motion
+    ///
+    /// This method must arrange for the rest of the ongoing motion to be made
+    /// in the opposite direction, but must not block. A motion started by
+    /// [`MotionControl::move_to_position`] covers the same number of steps as
+    /// it had left, after reversing. A rotation started by
+    /// [`MotionControl::rotate`] continues in the opposite direction. If no
+    /// motion is ongoing, this method should have no effect.
+    fn reverse(&mut self) -> Result<(), Self::Error>;
+
+    /// Return the number of steps left in the current motion
+    ///
+    /// This method must not start or influence a motion. Returns `None`, if
+    /// the driver has no way of knowing the number of steps left, or if the
+    /// current motion has no end (see [`MotionControl::rotate`]).
+    ///
+    /// The default implementation always returns `None`, for drivers that
+    /// don't keep track of their motion.
+    fn remaining_steps(&mut self) -> Result<Option<u32>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Abort any ongoing motion immediately
+    ///
+    /// Unlike starting a new motion, this must not ramp down. Any motion must
+    /// be stopped right away, and the rest of it discarded. On return, the
+    /// driver must be idle.
+    ///
+    /// Since a step might have been interrupted, the internal position value
+    /// might be off by one step after calling this method.
+    fn abort(&mut self) -> Result<(), Self::Error>;
+
     /// Update an ongoing motion
     ///
     /// This method may contain any code required to maintain an ongoing motion,
@@ -178,3 +529,206 @@ pub trait MotionControl {
     /// called again, until starting another motion.
     fn update(&mut self) -> Result<bool, Self::Error>;
 }
+
+/// Implemented by encoders that measure the position of the motor
+///
+/// Unlike the other traits in this module, this isn't implemented by drivers,
+/// but by whatever reads the encoder (for example, a quadrature decoder
+/// peripheral). This crate doesn't decode encoder signals itself. It only uses
+/// the position to detect lost steps (see [`Stepper::lost_steps`]).
+///
+/// [`Stepper::lost_steps`]: crate::Stepper::lost_steps
+pub trait Encoder {
+    /// The error that can occur while reading the encoder
+    type Error;
+
+    /// Returns the measured position of the motor
+    ///
+    /// The position must be given in the same unit as the position tracked by
+    /// motion control, meaning (micro-)steps, and must be zero at the same
+    /// point. If the encoder has a different resolution, this method needs to
+    /// scale its reading accordingly.
+    fn position(&mut self) -> Result<i32, Self::Error>;
+}
+
+/// Describes the capabilities of a driver at compile time
+///
+/// The other traits in this module are only implemented for a driver, once
+/// the respective capability has been enabled. This trait, on the other hand,
+/// describes what the driver supports, regardless of which capabilities have
+/// been enabled so far. Generic code can use these constants to decide what to
+/// do with a driver, without requiring the traits as bounds.
+///
+/// A constant is `true`, if the driver's implementation in this crate supports
+/// the respective capability. All constants are `false` by default, so
+/// implementations only need to override the ones that apply.
+pub trait Capabilities {
+    /// Whether the microstepping mode can be controlled
+    ///
+    /// See [`EnableStepModeControl`] and [`SetStepMode`].
+    const SUPPORTS_STEP_MODE_CONTROL: bool = false;
+
+    /// Whether the driver can be enabled and disabled
+    ///
+    /// See [`EnableDriverControl`] and [`SetEnable`].
+    const SUPPORTS_DRIVER_CONTROL: bool = false;
+
+    /// Whether the chopper mode can be switched
+    ///
+    /// See [`ChopperMode`].
+    const SUPPORTS_CHOPPER_MODE: bool = false;
+
+    /// Whether the motor current can be set by software
+    ///
+    /// There's no common trait for this, so check the driver's documentation.
+    const SUPPORTS_CURRENT_CONTROL: bool = false;
+
+    /// Whether the driver can detect a stalled motor
+    ///
+    /// See [`EnableStallDetection`] and [`StallDetection`].
+    const SUPPORTS_STALL_DETECTION: bool = false;
+
+    /// Whether the driver is configured over a UART interface
+    const SUPPORTS_UART_CONFIG: bool = false;
+
+    /// Whether the driver is configured over an SPI interface
+    const SUPPORTS_SPI_CONFIG: bool = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use embedded_time::duration::Nanoseconds;
+
+    use crate::{step_mode::StepMode16, util::ref_mut::RefMut, Direction};
+
+    use super::{Capabilities, MotionControl, SetStepMode};
+
+    struct Basic;
+
+    impl Capabilities for Basic {}
+
+    struct Smart;
+
+    impl Capabilities for Smart {
+        const SUPPORTS_STEP_MODE_CONTROL: bool = true;
+        const SUPPORTS_STALL_DETECTION: bool = true;
+        const SUPPORTS_UART_CONFIG: bool = true;
+    }
+
+    fn needs_homing_switch<Driver: Capabilities>(_: &Driver) -> bool {
+        !Driver::SUPPORTS_STALL_DETECTION
+    }
+
+    fn configuration<Driver: Capabilities>(_: &Driver) -> (bool, bool, bool) {
+        (
+            Driver::SUPPORTS_STEP_MODE_CONTROL,
+            Driver::SUPPORTS_UART_CONFIG,
+            Driver::SUPPORTS_SPI_CONFIG,
+        )
+    }
+
+    #[test]
+    fn capabilities_should_be_queryable_in_generic_code() {
+        assert!(needs_homing_switch(&Basic));
+        assert!(!needs_homing_switch(&Smart));
+
+        assert_eq!(configuration(&Basic), (false, false, false));
+        assert_eq!(configuration(&Smart), (true, true, false));
+
+        // Wrappers report the capabilities of the wrapped driver.
+        let mut smart = Smart;
+        assert!(!needs_homing_switch(&RefMut(&mut smart)));
+        assert_eq!(configuration(&RefMut(&mut smart)), (true, true, false));
+    }
+
+    #[test]
+    fn step_mode_should_default_to_none() {
+        struct Untracked;
+
+        impl SetStepMode for Untracked {
+            const SETUP_TIME: Nanoseconds = Nanoseconds(0);
+            const HOLD_TIME: Nanoseconds = Nanoseconds(0);
+
+            type Error = Infallible;
+            type StepMode = StepMode16;
+
+            fn apply_mode_config(
+                &mut self,
+                _: Self::StepMode,
+            ) -> Result<(), Self::Error> {
+                Ok(())
+            }
+
+            fn enable_driver(&mut self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+
+        let mut driver = Untracked;
+        driver.apply_mode_config(StepMode16::M4).unwrap();
+        assert_eq!(driver.step_mode(), None);
+    }
+
+    #[test]
+    fn remaining_steps_should_default_to_none() {
+        struct Untracked;
+
+        impl MotionControl for Untracked {
+            type Velocity = f32;
+            type Error = Infallible;
+
+            fn move_to_position(
+                &mut self,
+                _: Self::Velocity,
+                _: i32,
+            ) -> Result<(), Self::Error> {
+                Ok(())
+            }
+
+            fn rotate(
+                &mut self,
+                _: Direction,
+                _: Self::Velocity,
+            ) -> Result<(), Self::Error> {
+                Ok(())
+            }
+
+            fn set_max_speed(
+                &mut self,
+                _: Self::Velocity,
+            ) -> Result<(), Self::Error> {
+                Ok(())
+            }
+
+            fn reset_position(&mut self, _: i32) -> Result<(), Self::Error> {
+                Ok(())
+            }
+
+            fn current_step(&mut self) -> Result<i32, Self::Error> {
+                Ok(0)
+            }
+
+            fn current_direction(&mut self) -> Result<Direction, Self::Error> {
+                Ok(Direction::Forward)
+            }
+
+            fn reverse(&mut self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+
+            fn abort(&mut self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+
+            fn update(&mut self) -> Result<bool, Self::Error> {
+                Ok(false)
+            }
+        }
+
+        let mut driver = Untracked;
+        driver.move_to_position(1.0, 3).unwrap();
+        assert_eq!(driver.remaining_steps().unwrap(), None);
+    }
+}
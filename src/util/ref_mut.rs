@@ -2,10 +2,16 @@
 //!
 //! See [`RefMut`] for more information.
 
-use embedded_hal::timer::nb as timer;
+use embedded_hal::{digital::PinState, timer::nb as timer};
 use embedded_time::duration::Nanoseconds;
 
-use crate::traits::{MotionControl, SetDirection, SetStepMode, Step};
+use crate::{
+    traits::{
+        Capabilities, ChopperMode, MotionControl, SetDirection, SetEnable,
+        SetStepMode, StallDetection, Step,
+    },
+    Direction,
+};
 
 /// Generic wrapper around a mutable reference
 ///
@@ -52,10 +58,45 @@ where
         self.0.move_to_position(max_velocity, target_step)
     }
 
+    fn rotate(
+        &mut self,
+        direction: Direction,
+        velocity: Self::Velocity,
+    ) -> Result<(), Self::Error> {
+        self.0.rotate(direction, velocity)
+    }
+
+    fn set_max_speed(
+        &mut self,
+        max_velocity: Self::Velocity,
+    ) -> Result<(), Self::Error> {
+        self.0.set_max_speed(max_velocity)
+    }
+
     fn reset_position(&mut self, step: i32) -> Result<(), Self::Error> {
         self.0.reset_position(step)
     }
 
+    fn current_step(&mut self) -> Result<i32, Self::Error> {
+        self.0.current_step()
+    }
+
+    fn current_direction(&mut self) -> Result<Direction, Self::Error> {
+        self.0.current_direction()
+    }
+
+    fn reverse(&mut self) -> Result<(), Self::Error> {
+        self.0.reverse()
+    }
+
+    fn remaining_steps(&mut self) -> Result<Option<u32>, Self::Error> {
+        self.0.remaining_steps()
+    }
+
+    fn abort(&mut self) -> Result<(), Self::Error> {
+        self.0.abort()
+    }
+
     fn update(&mut self) -> Result<bool, Self::Error> {
         self.0.update()
     }
@@ -73,6 +114,75 @@ where
     fn dir(&mut self) -> Result<&mut Self::Dir, Self::Error> {
         self.0.dir()
     }
+
+    fn setup_time(&self) -> Nanoseconds {
+        self.0.setup_time()
+    }
+
+    fn dir_polarity(&self) -> PinState {
+        self.0.dir_polarity()
+    }
+}
+
+impl<'r, T> Capabilities for RefMut<'r, T>
+where
+    T: Capabilities,
+{
+    const SUPPORTS_STEP_MODE_CONTROL: bool = T::SUPPORTS_STEP_MODE_CONTROL;
+    const SUPPORTS_DRIVER_CONTROL: bool = T::SUPPORTS_DRIVER_CONTROL;
+    const SUPPORTS_CHOPPER_MODE: bool = T::SUPPORTS_CHOPPER_MODE;
+    const SUPPORTS_CURRENT_CONTROL: bool = T::SUPPORTS_CURRENT_CONTROL;
+    const SUPPORTS_STALL_DETECTION: bool = T::SUPPORTS_STALL_DETECTION;
+    const SUPPORTS_UART_CONFIG: bool = T::SUPPORTS_UART_CONFIG;
+    const SUPPORTS_SPI_CONFIG: bool = T::SUPPORTS_SPI_CONFIG;
+}
+
+impl<'r, T> ChopperMode for RefMut<'r, T>
+where
+    T: ChopperMode,
+{
+    type Error = T::Error;
+
+    fn set_quiet(&mut self, quiet: bool) -> Result<(), Self::Error> {
+        self.0.set_quiet(quiet)
+    }
+}
+
+impl<'r, T> SetEnable for RefMut<'r, T>
+where
+    T: SetEnable,
+{
+    const HOLD_TIME: Nanoseconds = T::HOLD_TIME;
+
+    type Error = T::Error;
+
+    fn enable(&mut self) -> Result<(), Self::Error> {
+        self.0.enable()
+    }
+
+    fn disable(&mut self) -> Result<(), Self::Error> {
+        self.0.disable()
+    }
+
+    fn is_enabled(&self) -> Option<bool> {
+        self.0.is_enabled()
+    }
+}
+
+impl<'r, T> StallDetection for RefMut<'r, T>
+where
+    T: StallDetection,
+{
+    type Diag = T::Diag;
+    type Error = T::Error;
+
+    fn diag(&mut self) -> Result<&mut Self::Diag, Self::Error> {
+        self.0.diag()
+    }
+
+    fn is_stalled(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_stalled()
+    }
 }
 
 impl<'r, T> SetStepMode for RefMut<'r, T>
@@ -81,6 +191,8 @@ where
 {
     const SETUP_TIME: Nanoseconds = T::SETUP_TIME;
     const HOLD_TIME: Nanoseconds = T::HOLD_TIME;
+    const RESET_TIME: Nanoseconds = T::RESET_TIME;
+    const MODE_PIN_SETTLE_TIME: Nanoseconds = T::MODE_PIN_SETTLE_TIME;
 
     type Error = T::Error;
     type StepMode = T::StepMode;
@@ -92,9 +204,21 @@ where
         self.0.apply_mode_config(step_mode)
     }
 
+    fn apply_mode_pin(
+        &mut self,
+        step_mode: Self::StepMode,
+        index: usize,
+    ) -> Result<bool, Self::Error> {
+        self.0.apply_mode_pin(step_mode, index)
+    }
+
     fn enable_driver(&mut self) -> Result<(), Self::Error> {
         self.0.enable_driver()
     }
+
+    fn step_mode(&self) -> Option<Self::StepMode> {
+        self.0.step_mode()
+    }
 }
 
 impl<'r, T> Step for RefMut<'r, T>
@@ -102,6 +226,8 @@ where
     T: Step,
 {
     const PULSE_LENGTH: Nanoseconds = T::PULSE_LENGTH;
+    const PULSE_POLARITY: PinState = T::PULSE_POLARITY;
+    const LOW_TIME: Nanoseconds = T::LOW_TIME;
 
     type Step = T::Step;
     type Error = T::Error;
@@ -109,4 +235,16 @@ where
     fn step(&mut self) -> Result<&mut Self::Step, Self::Error> {
         self.0.step()
     }
+
+    fn pulse_polarity(&self) -> PinState {
+        self.0.pulse_polarity()
+    }
+
+    fn pulse_length(&self) -> Nanoseconds {
+        self.0.pulse_length()
+    }
+
+    fn low_time(&self) -> Nanoseconds {
+        self.0.low_time()
+    }
 }